@@ -0,0 +1,212 @@
+/* File: access.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for who may change what in the database.
+//!
+//! Every call that changes the database is made by an [`Actor`], and the
+//! database rejects it unless the actor's [`Role`] has the [`Permission`]
+//! it needs. Operators get an actor by logging in with
+//! [`crate::db::DB::login_operator`] and providers with
+//! [`crate::db::DB::login_provider`]. The first operator of a new database
+//! is added with [`crate::db::DB::add_first_operator`].
+//!
+//! | Role       | Permissions                                            |
+//! |------------|--------------------------------------------------------|
+//! | `auditor`  | None, so they can only look                            |
+//! | `clerk`    | Edit members                                           |
+//! | `manager`  | Every permission                                       |
+//! | provider   | Record their own consultations, get their directory    |
+//! | system     | Every permission, for the scheduler                    |
+//!
+//! An operator of any role may also change their own password.
+
+/// What an operator account may do, or what kind of actor it is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    /// Can look at everything but change nothing.
+    Auditor,
+    /// Enters members.
+    Clerk,
+    /// Runs ChocAn and may do anything.
+    Manager,
+    /// A provider at a provider terminal, with their id.
+    Provider(u32),
+    /// The program itself, such as the scheduler.
+    System,
+}
+
+impl Role {
+    /// The roles an operator account can have.
+    pub const OPERATOR_ROLES: [&'static str; 3] =
+        ["auditor", "clerk", "manager"];
+
+    /// Parses the role of an operator account.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the text is not `auditor`, `clerk` or `manager`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.to_lowercase().as_str() {
+            "auditor" => Ok(Role::Auditor),
+            "clerk" => Ok(Role::Clerk),
+            "manager" => Ok(Role::Manager),
+            _ => Err(format!(
+                "Invalid role, enter {}: {}",
+                Role::OPERATOR_ROLES.join(", "),
+                text
+            )),
+        }
+    }
+
+    /// Checks if the role has a permission.
+    pub fn allows(&self, permission: Permission) -> bool {
+        match self {
+            Role::Manager | Role::System => true,
+            Role::Clerk => permission == Permission::EditMembers,
+            Role::Provider(_) => matches!(
+                permission,
+                Permission::RecordConsultations | Permission::SendDirectory
+            ),
+            Role::Auditor => false,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Role::Auditor => "auditor",
+            Role::Clerk => "clerk",
+            Role::Manager => "manager",
+            Role::Provider(_) => "provider",
+            Role::System => "system",
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Something that changes the database.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Permission {
+    /// Add, change, suspend, reinstate and remove members.
+    EditMembers,
+    /// Add, change and remove providers, their bank accounts and PINs.
+    EditProviders,
+    /// Add and remove services and change their fees.
+    EditServices,
+    /// Record consultations.
+    RecordConsultations,
+    /// Send reports and payments, choose how they are sent and re-send
+    /// emails.
+    SendReports,
+    /// Email the provider directory.
+    SendDirectory,
+    /// Add, change, unlock and remove operator accounts.
+    EditOperators,
+    /// Back up the database.
+    Backup,
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Permission::EditMembers => "edit members",
+            Permission::EditProviders => "edit providers",
+            Permission::EditServices => "edit services",
+            Permission::RecordConsultations => "record consultations",
+            Permission::SendReports => "send reports",
+            Permission::SendDirectory => "send the provider directory",
+            Permission::EditOperators => "edit operator accounts",
+            Permission::Backup => "back up the database",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Who is making a change to the database.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Actor {
+    name: String,
+    role: Role,
+}
+
+impl Actor {
+    /// Create the actor of a logged in operator or provider. Only the
+    /// database creates these, once the password or PIN was checked.
+    pub(crate) fn new(name: &str, role: Role) -> Self {
+        Actor {
+            name: name.to_string(),
+            role,
+        }
+    }
+
+    /// Create an actor for the program itself, which has every permission.
+    /// Only the scheduler, the commands and the tests of this crate use it,
+    /// so other programs must log in.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - What is acting, such as `scheduler`.
+    pub(crate) fn system(name: &str) -> Self {
+        Actor::new(name, Role::System)
+    }
+
+    /// The username, provider id or program part that is acting.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The role of the actor.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Checks if the actor has a permission.
+    pub fn allows(&self, permission: Permission) -> bool {
+        self.role.allows(permission)
+    }
+}
+
+impl std::fmt::Display for Actor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.role, self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_permissions() {
+        assert!(Role::Manager.allows(Permission::EditServices));
+        assert!(Role::Clerk.allows(Permission::EditMembers));
+        assert!(!Role::Clerk.allows(Permission::EditServices));
+        assert!(!Role::Auditor.allows(Permission::EditMembers));
+        assert!(Role::Provider(1).allows(Permission::RecordConsultations));
+        assert!(!Role::Provider(1).allows(Permission::SendReports));
+        assert!(Actor::system("scheduler").allows(Permission::SendReports));
+    }
+
+    #[test]
+    fn test_role_parse() {
+        assert_eq!(Role::parse("Clerk"), Ok(Role::Clerk));
+        assert!(Role::parse("system").is_err());
+        assert_eq!(Actor::new("ann", Role::Clerk).to_string(), "clerk ann");
+    }
+}
@@ -0,0 +1,236 @@
+/* File: auth.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the credentials that providers and operators log in with.
+//!
+//! Provider PINs and operator passwords are never stored. Only a salted,
+//! slow hash of them is, made with PBKDF2-HMAC-SHA256 and written as:
+//!
+//! ```text
+//! pbkdf2-sha256$ITERATIONS$SALT$HASH
+//! ```
+//!
+//! where the salt and hash are hex. The number of iterations is kept with
+//! each hash so it can be raised later without breaking older hashes.
+use sha2::Sha256;
+
+/// The fewest digits in a provider PIN.
+pub const MIN_PIN_SIZE: usize = 4;
+/// The most digits in a provider PIN.
+pub const MAX_PIN_SIZE: usize = 8;
+/// The fewest characters in an operator password.
+pub const MIN_PASSWORD_SIZE: usize = 8;
+/// The most characters in an operator username.
+pub const MAX_USERNAME_SIZE: usize = 25;
+
+/// How many times new hashes are iterated.
+const HASH_ITERATIONS: u32 = 100_000;
+/// The name of the hash written before its parameters.
+const HASH_SCHEME: &str = "pbkdf2-sha256";
+/// The number of random bytes in a salt.
+const SALT_SIZE: usize = 16;
+/// Checks that a provider PIN is made of enough digits.
+///
+/// # Failure
+///
+/// Will return `Err` with what is wrong with the PIN.
+pub fn check_pin(pin: &str) -> Result<(), String> {
+    if !pin.chars().all(|c| c.is_ascii_digit())
+        || !(MIN_PIN_SIZE..=MAX_PIN_SIZE).contains(&pin.len())
+    {
+        return Err(format!(
+            "Invalid PIN, enter {} to {} digits",
+            MIN_PIN_SIZE, MAX_PIN_SIZE
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that an operator password is long enough.
+///
+/// # Failure
+///
+/// Will return `Err` with what is wrong with the password.
+pub fn check_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_SIZE {
+        return Err(format!(
+            "Invalid password, enter at least {} characters",
+            MIN_PASSWORD_SIZE
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that an operator username is short and made of letters, digits,
+/// `.`, `_` or `-`.
+///
+/// # Failure
+///
+/// Will return `Err` with what is wrong with the username.
+pub fn check_username(username: &str) -> Result<(), String> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || ".-_".contains(c);
+    if username.is_empty()
+        || username.len() > MAX_USERNAME_SIZE
+        || !username.chars().all(allowed)
+    {
+        return Err(format!(
+            "Invalid username, enter up to {} letters, digits, '.', '_' or \
+             '-': {}",
+            MAX_USERNAME_SIZE, username
+        ));
+    }
+    Ok(())
+}
+
+/// Hashes a PIN or password with a new random salt.
+///
+/// # Arguments
+///
+/// * `secret` - The PIN or password to hash.
+///
+/// # Success
+///
+/// Will return the hash with its parameters, ready to be stored.
+pub fn hash_secret(secret: &str) -> String {
+    let salt = new_salt();
+    let hash = pbkdf2(secret.as_bytes(), &salt, HASH_ITERATIONS);
+    format!(
+        "{}${}${}${}",
+        HASH_SCHEME,
+        HASH_ITERATIONS,
+        to_hex(&salt),
+        to_hex(&hash)
+    )
+}
+
+/// Checks a PIN or password against a stored hash.
+///
+/// # Arguments
+///
+/// * `secret` - The PIN or password that was entered.
+/// * `stored` - The hash made by [`hash_secret`].
+///
+/// # Success
+///
+/// Will return `true` if the secret matches, or `false` if it does not or
+/// the stored hash is not valid.
+pub fn verify_secret(secret: &str, stored: &str) -> bool {
+    let parts: Vec<&str> = stored.split('$').collect();
+    let [scheme, iterations, salt, hash] = parts.as_slice() else {
+        return false;
+    };
+    let (Ok(iterations), Some(salt), Some(hash)) =
+        (iterations.parse::<u32>(), from_hex(salt), from_hex(hash))
+    else {
+        return false;
+    };
+    if *scheme != HASH_SCHEME || iterations == 0 {
+        return false;
+    }
+    let actual = pbkdf2(secret.as_bytes(), &salt, iterations);
+    // Compares every byte so the time taken does not hint at the hash.
+    hash.len() == actual.len()
+        && hash
+            .iter()
+            .zip(actual.iter())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Takes as long as checking a secret against a stored hash, for logins
+/// by someone with no stored hash, so the time taken does not tell whether
+/// they exist.
+///
+/// # Arguments
+///
+/// * `secret` - The PIN or password that was entered.
+pub fn verify_missing(secret: &str) {
+    std::hint::black_box(pbkdf2(
+        secret.as_bytes(),
+        &[0; SALT_SIZE],
+        HASH_ITERATIONS,
+    ));
+}
+
+/// Derives a 32 byte key with PBKDF2, using HMAC-SHA256.
+fn pbkdf2(secret: &[u8], salt: &[u8], iterations: u32) -> [u8; 32] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(secret, salt, iterations)
+}
+
+/// Makes a new salt from the operating system's random numbers.
+///
+/// # Panics
+///
+/// Will panic if the operating system has no random numbers to give, since
+/// no hash should be stored without a salt.
+fn new_salt() -> [u8; SALT_SIZE] {
+    let mut salt = [0; SALT_SIZE];
+    getrandom::fill(&mut salt)
+        .expect("the operating system has random numbers");
+    salt
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pbkdf2_known_answer() {
+        // From the PBKDF2-HMAC-SHA256 test vectors of RFC 7914.
+        let key = pbkdf2(b"passwd", b"salt", 1);
+        assert_eq!(
+            to_hex(&key),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    #[test]
+    fn test_hash_and_verify_secret() {
+        let stored = hash_secret("1234");
+        assert!(stored.starts_with("pbkdf2-sha256$100000$"));
+        assert!(verify_secret("1234", &stored));
+        assert!(!verify_secret("4321", &stored));
+        assert_ne!(stored, hash_secret("1234"));
+        assert!(!verify_secret("1234", "1234"));
+        assert!(!verify_secret("1234", "pbkdf2-sha256$0$00$00"));
+    }
+
+    #[test]
+    fn test_check_credentials() {
+        assert!(check_pin("1234").is_ok());
+        assert!(check_pin("123").is_err());
+        assert!(check_pin("123456789").is_err());
+        assert!(check_pin("12a4").is_err());
+        assert!(check_password("long enough").is_ok());
+        assert!(check_password("short").is_err());
+        assert!(check_username("ann.smith").is_ok());
+        assert!(check_username("").is_err());
+        assert!(check_username("ann smith").is_err());
+    }
+}
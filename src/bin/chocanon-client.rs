@@ -0,0 +1,50 @@
+/* File: chocanon-client.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Provider terminal that connects to a ChocAn provider server.
+
+use chocanon::client::{self, Client};
+use chocanon::config::LISTEN_VAR;
+use chocanon::prompt;
+use chocanon::server::DEFAULT_ADDRESS;
+use std::io;
+
+const USAGE: &str = "Usage: chocanon-client [ADDRESS]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let address = match args.as_slice() {
+        [] => std::env::var(LISTEN_VAR)
+            .unwrap_or_else(|_| DEFAULT_ADDRESS.to_string()),
+        [address] if !address.starts_with('-') => address.clone(),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    let mut client = match Client::connect(&address) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Error connecting to {}: {}", address, err);
+            std::process::exit(1);
+        }
+    };
+    prompt::reads_stdin();
+    let mut input = io::stdin().lock();
+    if let Err(err) = client::run(&mut client, &mut input, &mut io::stdout()) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
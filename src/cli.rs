@@ -0,0 +1,515 @@
+/* File: cli.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the non-interactive commands, for use by scripts and cron.
+//!
+//! Each command calls the same database API as the terminals and prints a
+//! single JSON object to standard output when it succeeds. When it fails,
+//! a JSON object with an `error` field is printed to standard error
+//! instead.
+//!
+//! ```text
+//! member add --id ID --name NAME --address ADDRESS --city CITY
+//!            --state STATE --zip ZIP --email EMAIL
+//! member suspend ID
+//! member reinstate ID
+//! service list
+//! consultation add --provider ID --member ID --service CODE
+//!                  --date MM-DD-YYYY [--comments TEXT]
+//! report send --type TYPE --week YYYY-MM-DD [--force]
+//! db backup PATH
+//! operator add USERNAME [--role ROLE]
+//! operator password USERNAME
+//! operator role USERNAME ROLE
+//! operator remove USERNAME
+//! operator unlock USERNAME
+//! operator list
+//! provider pin ID
+//! provider unlock ID
+//! ```
+//!
+//! The report type is one of `member`, `provider`, `manager`, `eft` or
+//! `nacha`, and the week is given by the Friday it ends on. An operator's
+//! role is one of `auditor`, `clerk` or `manager`, which is the default.
+//!
+//! Every command logs in as the operator given by `--user USERNAME` and
+//! runs with that operator's role, the same as the manager terminal. The
+//! operator's password is taken from the `CHOCANON_PASSWORD` environment
+//! variable, or else from the first line of standard input. A new password
+//! or PIN is read from the next line of standard input, so neither is shown
+//! in the list of running processes.
+//!
+//! The only command that does not log in is `operator add` without
+//! `--user` on a database with no operators, which adds the first operator
+//! as a manager.
+//!
+//! | Exit code | Meaning                                          |
+//! |-----------|--------------------------------------------------|
+//! | 0         | The command succeeded                            |
+//! | 1         | The command failed, such as an unknown member    |
+//! | 2         | The command or its arguments are not valid       |
+//! | 3         | A report was sent but some recipients failed     |
+use crate::access::{Actor, Role};
+use crate::config::Config;
+use crate::db::{DB, Error, LocationInfo, PersonInfo};
+use crate::ops::{self, OpError};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+
+/// The exit code when a command succeeded.
+pub const EXIT_SUCCESS: i32 = 0;
+/// The exit code when a command failed.
+pub const EXIT_FAILURE: i32 = 1;
+/// The exit code when a command or its arguments are not valid.
+pub const EXIT_USAGE: i32 = 2;
+/// The exit code when a report was not delivered to every recipient.
+pub const EXIT_UNDELIVERED: i32 = 3;
+
+/// The usage of every command.
+pub const COMMANDS_USAGE: &str = "member add --id ID --name NAME \
+--address ADDRESS --city CITY --state STATE --zip ZIP --email EMAIL
+       member suspend ID
+       member reinstate ID
+       service list
+       consultation add --provider ID --member ID --service CODE \
+--date MM-DD-YYYY [--comments TEXT]
+       report send --type TYPE --week YYYY-MM-DD [--force]
+       db backup PATH
+       operator add USERNAME [--role ROLE]
+       operator password|remove|unlock USERNAME
+       operator role USERNAME ROLE
+       operator list
+       provider pin|unlock ID
+Every command needs --user USERNAME, with the password in CHOCANON_PASSWORD \
+or on the first line of standard input, except adding the first operator.";
+
+/// The environment variable the operator's password may be given in.
+pub const PASSWORD_VAR: &str = "CHOCANON_PASSWORD";
+
+/// The options that are not followed by a value.
+const FLAGS: [&str; 1] = ["--force"];
+
+/// Why a command did not succeed.
+#[derive(Debug)]
+pub enum CliError {
+    /// The command or its arguments are not valid.
+    Usage(String),
+    /// The arguments were understood but could not be used.
+    Invalid(String),
+    /// The database returned an error.
+    Db(Error),
+    /// A report was sent but not to every recipient, with the summary.
+    Undelivered(Value),
+}
+
+impl CliError {
+    /// The exit code the program should exit with.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::Invalid(_) | CliError::Db(_) => EXIT_FAILURE,
+            CliError::Undelivered(_) => EXIT_UNDELIVERED,
+        }
+    }
+
+    /// Converts the error to JSON with an `error` field.
+    pub fn to_json(&self) -> Value {
+        match self {
+            CliError::Undelivered(summary) => {
+                let mut summary = summary.clone();
+                summary["error"] = json!(self.to_string());
+                summary
+            }
+            _ => json!({ "error": self.to_string() }),
+        }
+    }
+}
+
+impl From<OpError> for CliError {
+    fn from(err: OpError) -> Self {
+        match err {
+            OpError::Invalid(msg) => CliError::Invalid(msg),
+            OpError::Db(err) => CliError::Db(err),
+            OpError::Undelivered(summary) => CliError::Undelivered(summary),
+        }
+    }
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CliError::Usage(msg) => write!(f, "Usage error: {}", msg),
+            CliError::Invalid(msg) => write!(f, "{}", msg),
+            CliError::Db(err) => write!(f, "{}", err),
+            CliError::Undelivered(_) => {
+                write!(f, "Some recipients could not be delivered to")
+            }
+        }
+    }
+}
+
+/// Checks if the arguments are a command, which may come after
+/// `--user USERNAME`.
+///
+/// # Arguments
+///
+/// * `args` - The command line arguments, not including the program name or
+///   configuration options.
+pub fn is_command(args: &[String]) -> bool {
+    let arg = match args {
+        [user, _, arg, ..] if user == "--user" => arg,
+        [arg, ..] => arg,
+        [] => return false,
+    };
+    matches!(
+        arg.as_str(),
+        "member"
+            | "service"
+            | "consultation"
+            | "report"
+            | "db"
+            | "operator"
+            | "provider"
+    )
+}
+
+/// Runs a command.
+///
+/// # Arguments
+///
+/// * `db` - The database to run the command on.
+/// * `config` - The configuration, which has the NACHA originator.
+/// * `args` - The command and its arguments, such as
+///   `["member", "suspend", "123456789"]`.
+/// * `password` - The operator's password, if it was not given on `input`.
+/// * `input` - Where the operator's password and a new password or PIN are
+///   read from.
+///
+/// # Success
+///
+/// Will return the result of the command as JSON.
+///
+/// # Failure
+///
+/// Will return `Err` if the command is not valid or did not succeed.
+pub fn run(
+    db: &DB,
+    config: &Config,
+    args: &[String],
+    password: Option<&str>,
+    input: &mut impl BufRead,
+) -> Result<Value, CliError> {
+    let (command, options) = parse_options(args)?;
+    let command: Vec<&str> = command.iter().map(String::as_str).collect();
+    let username = match (command.as_slice(), options.get("--user")) {
+        (_, Some(username)) => username,
+        (["operator", "add", username], None) => {
+            return add_first_operator(db, username, &options, input);
+        }
+        (_, None) => {
+            return Err(CliError::Usage("--user is required".to_string()));
+        }
+    };
+    let password = match password {
+        Some(password) => password.to_string(),
+        None => read_secret(input)?,
+    };
+    let actor = db
+        .login_operator(username, &password)
+        .map_err(CliError::Db)?;
+    match command.as_slice() {
+        ["member", "add"] => add_member(db, &actor, &options),
+        ["member", "suspend", id] => {
+            let id = parse_number(id, "member id")?;
+            db.suspend_member(&actor, id).map_err(CliError::Db)?;
+            Ok(json!({ "member": id, "suspended": true }))
+        }
+        ["member", "reinstate", id] => {
+            let id = parse_number(id, "member id")?;
+            db.reinstate_member(&actor, id).map_err(CliError::Db)?;
+            Ok(json!({ "member": id, "suspended": false }))
+        }
+        ["service", "list"] => ops::list_services(db).map_err(CliError::Db),
+        ["consultation", "add"] => add_consultation(db, &actor, &options),
+        ["report", "send"] => send_report(db, &actor, config, &options),
+        ["db", "backup", path] => {
+            db.backup(&actor, Path::new(path)).map_err(CliError::Db)?;
+            Ok(json!({ "backup": path }))
+        }
+        ["operator", "add", username] => {
+            let role = match options.get("--role") {
+                Some(role) => Role::parse(role).map_err(CliError::Usage)?,
+                None => Role::Manager,
+            };
+            let password = read_secret(input)?;
+            db.add_operator(&actor, username, &password, role)
+                .map_err(CliError::Db)?;
+            Ok(
+                json!({ "operator": username, "role": role.to_string(), "added": true }),
+            )
+        }
+        ["operator", "password", username] => {
+            let password = read_secret(input)?;
+            db.set_operator_password(&actor, username, &password)
+                .map_err(CliError::Db)?;
+            Ok(json!({ "operator": username, "password_changed": true }))
+        }
+        ["operator", "role", username, role] => {
+            let role = Role::parse(role).map_err(CliError::Usage)?;
+            db.set_operator_role(&actor, username, role)
+                .map_err(CliError::Db)?;
+            Ok(json!({ "operator": username, "role": role.to_string() }))
+        }
+        ["operator", "remove", username] => {
+            db.remove_operator(&actor, username).map_err(CliError::Db)?;
+            Ok(json!({ "operator": username, "removed": true }))
+        }
+        ["operator", "unlock", username] => {
+            db.unlock_operator(&actor, username).map_err(CliError::Db)?;
+            Ok(json!({ "operator": username, "locked": false }))
+        }
+        ["operator", "list"] => list_operators(db),
+        ["provider", "pin", id] => {
+            let id = parse_number(id, "provider id")?;
+            let pin = read_secret(input)?;
+            db.set_provider_pin(&actor, id, &pin)
+                .map_err(CliError::Db)?;
+            Ok(json!({ "provider": id, "pin_set": true }))
+        }
+        ["provider", "unlock", id] => {
+            let id = parse_number(id, "provider id")?;
+            db.unlock_provider(&actor, id).map_err(CliError::Db)?;
+            Ok(json!({ "provider": id, "locked": false }))
+        }
+        _ => Err(CliError::Usage(format!(
+            "unknown command: {}",
+            command.join(" ")
+        ))),
+    }
+}
+
+/// Splits arguments into the command words and the `--name value` options.
+fn parse_options(
+    args: &[String],
+) -> Result<(Vec<String>, HashMap<String, String>), CliError> {
+    let mut command = Vec::new();
+    let mut options = HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            command.push(arg.clone());
+        } else if FLAGS.contains(&arg.as_str()) {
+            options.insert(arg.clone(), String::new());
+        } else {
+            match args.next() {
+                Some(value) => {
+                    options.insert(arg.clone(), value.clone());
+                }
+                None => {
+                    return Err(CliError::Usage(format!(
+                        "{} needs a value",
+                        arg
+                    )));
+                }
+            }
+        }
+    }
+    Ok((command, options))
+}
+
+/// Gets an option that must be given.
+fn required<'a>(
+    options: &'a HashMap<String, String>,
+    name: &str,
+) -> Result<&'a str, CliError> {
+    options
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| CliError::Usage(format!("{} is required", name)))
+}
+
+/// Parses a number such as an id, naming what it is if it is not valid.
+fn parse_number(text: &str, what: &str) -> Result<u32, CliError> {
+    text.parse()
+        .map_err(|_| CliError::Usage(format!("invalid {}: {}", what, text)))
+}
+
+/// Reads a password or PIN from the first line of the input.
+fn read_secret(input: &mut impl BufRead) -> Result<String, CliError> {
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) => Err(CliError::Usage(
+            "the password or PIN must be given on standard input".to_string(),
+        )),
+        Ok(_) => Ok(line.trim_end_matches(['\r', '\n']).to_string()),
+        Err(err) => Err(CliError::Db(Error::Io(err))),
+    }
+}
+
+/// Adds the first operator of a new database, who is always a manager.
+fn add_first_operator(
+    db: &DB,
+    username: &str,
+    options: &HashMap<String, String>,
+    input: &mut impl BufRead,
+) -> Result<Value, CliError> {
+    if let Some(role) = options.get("--role")
+        && Role::parse(role).map_err(CliError::Usage)? != Role::Manager
+    {
+        return Err(CliError::Usage(
+            "the first operator must be a manager".to_string(),
+        ));
+    }
+    let password = read_secret(input)?;
+    db.add_first_operator(username, &password)
+        .map_err(CliError::Db)?;
+    Ok(json!({ "operator": username, "role": "manager", "added": true }))
+}
+
+fn list_operators(db: &DB) -> Result<Value, CliError> {
+    let accounts = db.get_operators().map_err(CliError::Db)?;
+    let operators: Vec<Value> = accounts
+        .iter()
+        .map(|account| {
+            json!({
+                "username": account.username,
+                "role": account.role.to_string(),
+                "failed_logins": account.failed_logins,
+                "locked_until": account.locked_until.map(|until| {
+                    until.format("%Y-%m-%d %H:%M:%S").to_string()
+                }),
+                "created_at": account
+                    .created_at
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+            })
+        })
+        .collect();
+    Ok(json!({ "operators": operators }))
+}
+
+fn add_member(
+    db: &DB,
+    actor: &Actor,
+    options: &HashMap<String, String>,
+) -> Result<Value, CliError> {
+    let id = parse_number(required(options, "--id")?, "member id")?;
+    let zipcode = parse_number(required(options, "--zip")?, "zipcode")?;
+    let location = LocationInfo::new(
+        required(options, "--address")?,
+        required(options, "--city")?,
+        required(options, "--state")?,
+        zipcode,
+    )
+    .map_err(CliError::Invalid)?;
+    let person = PersonInfo::new(
+        required(options, "--name")?,
+        id,
+        &location,
+        required(options, "--email")?,
+    )
+    .map_err(CliError::Invalid)?;
+    db.add_member(actor, &person).map_err(CliError::Db)?;
+    Ok(json!({ "member": ops::person_json(&person) }))
+}
+
+fn add_consultation(
+    db: &DB,
+    actor: &Actor,
+    options: &HashMap<String, String>,
+) -> Result<Value, CliError> {
+    let provider_id =
+        parse_number(required(options, "--provider")?, "provider id")?;
+    let member_id = parse_number(required(options, "--member")?, "member id")?;
+    let service_code =
+        parse_number(required(options, "--service")?, "service code")?;
+    let service_date = required(options, "--date")?;
+    let comments = options.get("--comments").map_or("", String::as_str);
+    Ok(ops::record_consultation(
+        db,
+        actor,
+        provider_id,
+        member_id,
+        service_code,
+        service_date,
+        comments,
+    )?)
+}
+
+fn send_report(
+    db: &DB,
+    actor: &Actor,
+    config: &Config,
+    options: &HashMap<String, String>,
+) -> Result<Value, CliError> {
+    let report_type = required(options, "--type")?;
+    let week = required(options, "--week")?;
+    let force = options.contains_key("--force");
+    let (report_type, period) =
+        ops::parse_report_week(report_type, week).map_err(CliError::Usage)?;
+    Ok(ops::send_week_report(
+        db,
+        actor,
+        config,
+        report_type,
+        &period,
+        force,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let (command, options) =
+            parse_options(&args("report send --type member --force")).unwrap();
+        assert_eq!(command, vec!["report", "send"]);
+        assert_eq!(options["--type"], "member");
+        assert!(options.contains_key("--force"));
+        match parse_options(&args("report send --type")) {
+            Err(CliError::Usage(msg)) => {
+                assert_eq!(msg, "--type needs a value")
+            }
+            other => panic!("expected a usage error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_is_command() {
+        assert!(is_command(&args("member suspend 1")));
+        assert!(is_command(&args("--user ann member suspend 1")));
+        assert!(!is_command(&args("--user ann")));
+        assert!(!is_command(&args("--serve")));
+        assert!(!is_command(&[]));
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(CliError::Usage(String::new()).exit_code(), EXIT_USAGE);
+        assert_eq!(CliError::Db(Error::NoDataFound).exit_code(), EXIT_FAILURE);
+        let err = CliError::Undelivered(json!({ "failed": [] }));
+        assert_eq!(err.exit_code(), EXIT_UNDELIVERED);
+        assert_eq!(err.to_json()["failed"], json!([]));
+        assert!(err.to_json()["error"].is_string());
+    }
+}
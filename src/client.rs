@@ -0,0 +1,270 @@
+/* File: client.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the networked provider terminal, which uses the provider
+//! server in [`crate::server`] instead of a local database.
+use crate::prompt;
+use crate::server::Request;
+use serde_json::Value;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+/// A connection to the provider server.
+#[derive(Debug)]
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    /// Connects to the provider server.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the server (E.g. `127.0.0.1:7314`).
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the server could not be reached.
+    pub fn connect(address: &str) -> io::Result<Self> {
+        let writer = TcpStream::connect(address)?;
+        Ok(Client {
+            reader: BufReader::new(writer.try_clone()?),
+            writer,
+        })
+    }
+
+    /// Sends a request and waits for its response.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to send.
+    ///
+    /// # Success
+    ///
+    /// Will return the result of the request, or the server's error message
+    /// if it could not be carried out.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the connection was lost.
+    pub fn send(
+        &mut self,
+        request: &Request,
+    ) -> io::Result<Result<Value, String>> {
+        let line = serde_json::to_string(request).map_err(io::Error::other)?;
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "the server closed the connection",
+            ));
+        }
+        let response: Value = serde_json::from_str(&line)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if response["ok"] == Value::Bool(true) {
+            Ok(Ok(response))
+        } else {
+            let error = response["error"].as_str().unwrap_or("unknown error");
+            Ok(Err(error.to_string()))
+        }
+    }
+}
+
+/// Runs the provider terminal against the server with user input and
+/// output.
+///
+/// # Arguments
+///
+/// * `client` - The connection to the server.
+/// * `input` - Where the provider's input is read from.
+/// * `out` - Where the terminal is written to.
+///
+/// The terminal ends cleanly when the input ends.
+///
+/// # Failure
+///
+/// Will return `Err` if the connection was lost or the output could not be
+/// written.
+pub fn run(
+    client: &mut Client,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    prompt::end_on_eof(run_session(client, input, out))?;
+    client.send(&Request::Logout)?.map_err(io::Error::other)?;
+    Ok(())
+}
+
+/// Runs the provider terminal until the provider quits.
+fn run_session(
+    client: &mut Client,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "Enter your provider number/id: ")?;
+    let Some(provider) = prompt::id("", input, out)? else {
+        return Ok(());
+    };
+    let Some(pin) = prompt::secret("Enter your PIN: ", input, out)? else {
+        return Ok(());
+    };
+    match client.send(&Request::Login { provider, pin })? {
+        Ok(response) => writeln!(
+            out,
+            "Welcome, {}.",
+            response["provider"]["name"].as_str().unwrap_or("")
+        )?,
+        Err(error) => {
+            writeln!(out, "Could not log in: {}", error)?;
+            return Ok(());
+        }
+    }
+
+    loop {
+        writeln!(out, "---Provider Terminal---")?;
+        writeln!(out, "( 0 ) Quit")?;
+        writeln!(out, "( 1 ) Add Consultation Record")?;
+        writeln!(out, "( 2 ) Get Provider Directory")?;
+        writeln!(out, "( 3 ) Validate Member")?;
+        let Some(choice) =
+            prompt::choice("Choice: ", &["0", "1", "2", "3"], input, out)?
+        else {
+            continue;
+        };
+        match choice {
+            "0" => {
+                writeln!(out, "Exiting provider terminal...")?;
+                return Ok(());
+            }
+            "1" => add_consultation(client, input, out)?,
+            "2" => match client.send(&Request::Directory { email: true })? {
+                Ok(response) => {
+                    writeln!(out, "The directory was emailed to you.")?;
+                    for service in
+                        response["services"].as_array().into_iter().flatten()
+                    {
+                        writeln!(
+                            out,
+                            "{:06} {} ${:.2}",
+                            service["code"].as_u64().unwrap_or(0),
+                            service["name"].as_str().unwrap_or(""),
+                            service["fee"].as_f64().unwrap_or(0.0)
+                        )?;
+                    }
+                }
+                Err(error) => {
+                    writeln!(out, "Failed to get the directory: {}", error)?
+                }
+            },
+            _ => {
+                if validate_member(client, input, out)?.is_some() {
+                    writeln!(out, "Validated")?;
+                }
+            }
+        }
+        writeln!(out)?;
+    }
+}
+
+/// Asks for a member id and checks it with the server.
+///
+/// # Success
+///
+/// Will return the id if it is valid, or `None` if it is not valid or was
+/// cancelled.
+///
+/// # Failure
+///
+/// Will return `Err` if the input has ended or the connection was lost.
+fn validate_member(
+    client: &mut Client,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<u32>> {
+    let Some(member) = prompt::id("Enter the member number/id: ", input, out)?
+    else {
+        return Ok(None);
+    };
+    match client.send(&Request::ValidateMember { member })? {
+        Ok(response) if response["valid"] == Value::Bool(true) => {
+            Ok(Some(member))
+        }
+        Ok(_) => {
+            writeln!(out, "Invalid Number")?;
+            Ok(None)
+        }
+        Err(error) => {
+            writeln!(out, "Error validating member: {}", error)?;
+            Ok(None)
+        }
+    }
+}
+
+fn add_consultation(
+    client: &mut Client,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(member) = validate_member(client, input, out)? else {
+        return Ok(());
+    };
+    writeln!(out, "Validated")?;
+    writeln!(out, "\n---Add Consultation Record---")?;
+    let Some(date) = prompt::date("Service date (MM-DD-YYYY): ", input, out)?
+    else {
+        return Ok(());
+    };
+    let service = loop {
+        let Some(code) = prompt::id("Service code: ", input, out)? else {
+            return Ok(());
+        };
+        match client.send(&Request::Service { code })? {
+            Ok(response) => {
+                writeln!(
+                    out,
+                    "Service Name: {}",
+                    response["service"]["name"].as_str().unwrap_or("")
+                )?;
+                match prompt::yes_no("Is the name correct (y/n): ", input, out)?
+                {
+                    Some(true) => break code,
+                    Some(false) => (),
+                    None => return Ok(()),
+                }
+            }
+            Err(error) => writeln!(out, "{}", error)?,
+        }
+    };
+    let comments = prompt::free_text("Comments: ", input, out)?;
+    let request = Request::SubmitConsultation {
+        member,
+        service,
+        date: date.format(prompt::DATE_FORMAT).to_string(),
+        comments,
+    };
+    match client.send(&request)? {
+        Ok(response) => writeln!(
+            out,
+            "Consultation record added successfully. Fee: ${:.2}",
+            response["consultation"]["fee"].as_f64().unwrap_or(0.0)
+        ),
+        Err(error) => {
+            writeln!(out, "Failed to add consultation record: {}", error)
+        }
+    }
+}
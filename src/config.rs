@@ -0,0 +1,702 @@
+/* File: config.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for configuring ChocAn.
+//!
+//! The configuration is loaded once at startup and passed to the database,
+//! the email layer and the terminals. Every setting has a default, which is
+//! overridden in order by the configuration file, an environment variable
+//! and a command line option, so that several environments can run on one
+//! machine.
+//!
+//! The configuration file is `./chocanon.toml` unless `--config PATH` or
+//! `CHOCANON_CONFIG` names another one. The default file may be missing, but
+//! a file that is named must exist. Every key is optional:
+//!
+//! ```toml
+//! database = "./chocanon.db3"
+//! templates = "./templates"
+//! schedule = "Sat 00:00"
+//! listen = "127.0.0.1:7314"
+//! http_listen = "127.0.0.1:8314"
+//!
+//! [email]
+//! transport = "file"
+//! output = "./emails"
+//! sender = "testing@chocan.com"
+//! managers = ["ManagerName <manager@pdx.edu>"]
+//!
+//! [nacha]
+//! immediate_destination = "123000220"
+//! destination_name = "CHOCAN BANK"
+//! immediate_origin = "1234567890"
+//! origin_name = "CHOCOHOLICS ANONYMOUS"
+//! company_name = "CHOCAN"
+//! company_id = "1234567890"
+//! originating_dfi = "12300022"
+//! ```
+//!
+//! The `[nacha]` settings are checked when the file is loaded, since a bank
+//! rejects an EFT file with a bad routing number or an overlong field. The
+//! `immediate_destination` is a routing number with a valid check digit,
+//! `immediate_origin` is up to 10 digits, `originating_dfi` is 8 digits and
+//! the names and `company_id` are printable ASCII that fits the file.
+//!
+//! | Option          | Variable                   | Setting                   |
+//! |-----------------|----------------------------|---------------------------|
+//! | `--config`      | `CHOCANON_CONFIG`          | Configuration file        |
+//! | `--db`          | `CHOCANON_DB_PATH`         | Database file             |
+//! | `--templates`   | `CHOCANON_TEMPLATES_PATH`  | Report template directory |
+//! | `--schedule`    | `CHOCANON_SCHEDULE`        | Weekly accounting run     |
+//! | `--listen`      | `CHOCANON_LISTEN`          | Provider server address   |
+//! | `--http-listen` | `CHOCANON_HTTP_LISTEN`     | HTTP API address          |
+//! | `--transport`   | `CHOCANON_EMAIL_TRANSPORT` | How emails are delivered  |
+//! | `--output`      | `CHOCANON_OUTPUT_PATH`     | Directory for emails, EFT |
+//! |                 |                            | files and manifests       |
+//! | `--sender`      | `CHOCANON_SENDER`          | Email address sent from   |
+//! | `--managers`    | `CHOCANON_MANAGERS`        | Comma separated managers, |
+//! |                 |                            | as `Name <email>`         |
+use crate::esend::{
+    DEFAULT_OUTPUT_PATH, EmailTransport, FileTransport, parse_transport,
+};
+use crate::nacha::Originator;
+use crate::sched::Schedule;
+use crate::server::DEFAULT_ADDRESS;
+use crate::template::TEMPLATE_PATH;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The configuration file read when no other is named.
+pub const CONFIG_PATH: &str = "./chocanon.toml";
+
+/// The environment variable naming the configuration file.
+pub const CONFIG_VAR: &str = "CHOCANON_CONFIG";
+/// The environment variable overriding the database file.
+pub const DB_PATH_VAR: &str = "CHOCANON_DB_PATH";
+/// The environment variable overriding the report template directory.
+pub const TEMPLATES_PATH_VAR: &str = "CHOCANON_TEMPLATES_PATH";
+/// The environment variable overriding the weekly accounting run.
+pub const SCHEDULE_VAR: &str = "CHOCANON_SCHEDULE";
+/// The environment variable overriding the address the provider server
+/// listens on.
+pub const LISTEN_VAR: &str = "CHOCANON_LISTEN";
+/// The environment variable overriding the address the HTTP API listens on.
+pub const HTTP_LISTEN_VAR: &str = "CHOCANON_HTTP_LISTEN";
+/// The environment variable overriding how emails are delivered, such as
+/// `smtp://localhost:25`.
+pub const TRANSPORT_VAR: &str = "CHOCANON_EMAIL_TRANSPORT";
+/// The environment variable overriding the output directory.
+pub const OUTPUT_PATH_VAR: &str = "CHOCANON_OUTPUT_PATH";
+/// The environment variable overriding the sender email address.
+pub const SENDER_VAR: &str = "CHOCANON_SENDER";
+/// The environment variable overriding the manager report recipients.
+pub const MANAGERS_VAR: &str = "CHOCANON_MANAGERS";
+
+/// The address the HTTP API listens on by default.
+pub const DEFAULT_HTTP_ADDRESS: &str = "127.0.0.1:8314";
+
+/// The command line options and the variables they override.
+const OPTIONS: [(&str, &str); 10] = [
+    ("--config", CONFIG_VAR),
+    ("--db", DB_PATH_VAR),
+    ("--templates", TEMPLATES_PATH_VAR),
+    ("--schedule", SCHEDULE_VAR),
+    ("--listen", LISTEN_VAR),
+    ("--http-listen", HTTP_LISTEN_VAR),
+    ("--transport", TRANSPORT_VAR),
+    ("--output", OUTPUT_PATH_VAR),
+    ("--sender", SENDER_VAR),
+    ("--managers", MANAGERS_VAR),
+];
+
+/// The usage of the command line options.
+pub const OPTIONS_USAGE: &str = "[--config FILE] [--db FILE] \
+[--templates DIR] [--schedule \"DAY HH:MM\"] [--listen ADDRESS] \
+[--http-listen ADDRESS] [--transport SPEC] [--output DIR] \
+[--sender EMAIL] [--managers LIST]";
+
+/// An email address along with the name of its owner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mailbox {
+    /// The name of the owner.
+    pub name: String,
+    /// The email address.
+    pub email: String,
+}
+
+impl Mailbox {
+    /// Create a mailbox from text such as `Name <name@pdx.edu>`.
+    ///
+    /// A bare email address is named after the part before the `@`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The mailbox to parse.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the text is not a valid mailbox.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let (name, email) = match text.strip_suffix('>') {
+            Some(rest) => match rest.rsplit_once('<') {
+                Some((name, email)) => (name.trim(), email.trim()),
+                None => return Err(format!("invalid mailbox: {}", text)),
+            },
+            None => ("", text),
+        };
+        let Some((user, domain)) = email.split_once('@') else {
+            return Err(format!("invalid email address: {}", email));
+        };
+        if user.is_empty()
+            || domain.is_empty()
+            || email.chars().any(|c| c.is_whitespace() || c.is_control())
+        {
+            return Err(format!("invalid email address: {}", email));
+        }
+        let name = if name.is_empty() { user } else { name };
+        Ok(Mailbox {
+            name: name.trim_matches('"').to_string(),
+            email: email.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for Mailbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
+
+/// The configuration of ChocAn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// The database file.
+    pub db_path: String,
+    /// The directory the report templates are read from.
+    pub templates_path: String,
+    /// When the weekly accounting procedure runs.
+    pub schedule: Schedule,
+    /// The address the provider server listens on, such as
+    /// `127.0.0.1:7314`.
+    pub listen: String,
+    /// The address the HTTP API listens on, when it is built.
+    pub http_listen: String,
+    /// How emails are delivered, as understood by `parse_transport`.
+    pub transport: String,
+    /// The directory that emails and EFT files are written under.
+    pub output_path: PathBuf,
+    /// The email address ChocAn sends from.
+    pub sender: String,
+    /// The managers the manager report is sent to.
+    pub managers: Vec<Mailbox>,
+    /// The bank and company that originate NACHA files.
+    pub originator: Originator,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            db_path: "./chocanon.db3".to_string(),
+            templates_path: TEMPLATE_PATH.to_string(),
+            schedule: Schedule::default(),
+            listen: DEFAULT_ADDRESS.to_string(),
+            http_listen: DEFAULT_HTTP_ADDRESS.to_string(),
+            transport: "file".to_string(),
+            output_path: PathBuf::from(DEFAULT_OUTPUT_PATH),
+            sender: "testing@chocan.com".to_string(),
+            managers: vec![Mailbox {
+                name: "ManagerName".to_string(),
+                email: "manager@pdx.edu".to_string(),
+            }],
+            originator: Originator::chocan(),
+        }
+    }
+}
+
+/// The contents of a configuration file, where every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    database: Option<String>,
+    templates: Option<String>,
+    schedule: Option<String>,
+    listen: Option<String>,
+    http_listen: Option<String>,
+    #[serde(default)]
+    email: EmailSection,
+    #[serde(default)]
+    nacha: NachaSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmailSection {
+    transport: Option<String>,
+    output: Option<String>,
+    sender: Option<String>,
+    managers: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NachaSection {
+    immediate_destination: Option<String>,
+    destination_name: Option<String>,
+    immediate_origin: Option<String>,
+    origin_name: Option<String>,
+    company_name: Option<String>,
+    company_id: Option<String>,
+    originating_dfi: Option<String>,
+}
+
+impl Config {
+    /// Loads the configuration from the configuration file, the environment
+    /// and the command line.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The command line arguments, not including the program
+    ///   name.
+    ///
+    /// # Success
+    ///
+    /// Will return the configuration and the arguments that were not
+    /// configuration options.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the configuration file could not be read or a
+    /// setting is invalid.
+    pub fn load(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        Config::load_with(
+            args,
+            |name| std::env::var(name).ok(),
+            |path| std::fs::read_to_string(path),
+        )
+    }
+
+    /// Loads the configuration like `load`, but with the environment and the
+    /// file system given.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The command line arguments, not including the program
+    ///   name.
+    /// * `var` - Gets the value of an environment variable, if it is set.
+    /// * `read` - Reads a configuration file.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the configuration file could not be read or a
+    /// setting is invalid.
+    pub fn load_with<V, R>(
+        args: &[String],
+        var: V,
+        read: R,
+    ) -> Result<(Self, Vec<String>), String>
+    where
+        V: Fn(&str) -> Option<String>,
+        R: Fn(&str) -> std::io::Result<String>,
+    {
+        let mut options = HashMap::new();
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match OPTIONS.iter().find(|(option, _)| option == arg) {
+                Some((option, name)) => match args.next() {
+                    Some(value) => {
+                        options.insert(*name, value.clone());
+                    }
+                    None => return Err(format!("{} needs a value", option)),
+                },
+                None => rest.push(arg.clone()),
+            }
+        }
+        let var = |name: &str| options.get(name).cloned().or_else(|| var(name));
+
+        let mut config = Config::default();
+        let text = match var(CONFIG_VAR) {
+            Some(path) => {
+                Some(read(&path).map_err(|err| format!("{}: {}", path, err))?)
+            }
+            None => match read(CONFIG_PATH) {
+                Ok(text) => Some(text),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => return Err(format!("{}: {}", CONFIG_PATH, err)),
+            },
+        };
+        if let Some(text) = text {
+            config = config.with_file(&text)?;
+        }
+        Ok((config.with_overrides(var)?, rest))
+    }
+
+    /// Overrides settings with those in a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The contents of the configuration file as TOML.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the file is not valid TOML or a setting is
+    /// invalid.
+    pub fn with_file(mut self, text: &str) -> Result<Self, String> {
+        let file: ConfigFile = toml::from_str(text)
+            .map_err(|err| format!("invalid configuration: {}", err))?;
+        if let Some(database) = file.database {
+            self.db_path = database;
+        }
+        if let Some(templates) = file.templates {
+            self.templates_path = templates;
+        }
+        if let Some(schedule) = file.schedule {
+            self.schedule = Schedule::parse(&schedule)?;
+        }
+        if let Some(listen) = file.listen {
+            self.listen = listen;
+        }
+        if let Some(http_listen) = file.http_listen {
+            self.http_listen = http_listen;
+        }
+        if let Some(transport) = file.email.transport {
+            parse_transport(&transport)?;
+            self.transport = transport;
+        }
+        if let Some(output) = file.email.output {
+            self.output_path = PathBuf::from(output);
+        }
+        if let Some(sender) = file.email.sender {
+            self.sender = Mailbox::parse(&sender)?.email;
+        }
+        if let Some(managers) = file.email.managers {
+            self.managers =
+                Config::managers_from(managers.iter().map(String::as_str))?;
+        }
+        let nacha = file.nacha;
+        let originator = &mut self.originator;
+        for (value, setting) in [
+            (
+                nacha.immediate_destination,
+                &mut originator.immediate_destination,
+            ),
+            (nacha.destination_name, &mut originator.destination_name),
+            (nacha.immediate_origin, &mut originator.immediate_origin),
+            (nacha.origin_name, &mut originator.origin_name),
+            (nacha.company_name, &mut originator.company_name),
+            (nacha.company_id, &mut originator.company_id),
+            (nacha.originating_dfi, &mut originator.originating_dfi),
+        ] {
+            if let Some(value) = value {
+                *setting = value;
+            }
+        }
+        self.originator
+            .validate()
+            .map_err(|err| format!("[nacha] {}", err))?;
+        Ok(self)
+    }
+
+    /// Overrides settings with variables such as those in the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `var` - Gets the value of a variable, if it is set.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a variable is invalid.
+    pub fn with_overrides<F>(mut self, var: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let path = |name: &str| match var(name) {
+            Some(path) if path.trim().is_empty() => {
+                Err(format!("{} is empty", name))
+            }
+            path => Ok(path),
+        };
+        if let Some(db_path) = path(DB_PATH_VAR)? {
+            self.db_path = db_path;
+        }
+        if let Some(templates_path) = path(TEMPLATES_PATH_VAR)? {
+            self.templates_path = templates_path;
+        }
+        if let Some(output_path) = path(OUTPUT_PATH_VAR)? {
+            self.output_path = PathBuf::from(output_path);
+        }
+        if let Some(schedule) = var(SCHEDULE_VAR) {
+            self.schedule = Schedule::parse(&schedule)
+                .map_err(|err| format!("{}: {}", SCHEDULE_VAR, err))?;
+        }
+        if let Some(listen) = path(LISTEN_VAR)? {
+            self.listen = listen;
+        }
+        if let Some(http_listen) = path(HTTP_LISTEN_VAR)? {
+            self.http_listen = http_listen;
+        }
+        if let Some(transport) = var(TRANSPORT_VAR) {
+            parse_transport(&transport)
+                .map_err(|err| format!("{}: {}", TRANSPORT_VAR, err))?;
+            self.transport = transport;
+        }
+        if let Some(sender) = var(SENDER_VAR) {
+            self.sender = Mailbox::parse(&sender)
+                .map_err(|err| format!("{}: {}", SENDER_VAR, err))?
+                .email;
+        }
+        if let Some(managers) = var(MANAGERS_VAR) {
+            self.managers = Config::parse_managers(&managers)
+                .map_err(|err| format!("{}: {}", MANAGERS_VAR, err))?;
+        }
+        Ok(self)
+    }
+
+    /// Parses a comma separated list of manager mailboxes.
+    ///
+    /// Commas inside a quoted name or angle brackets do not separate
+    /// mailboxes, so `"Lee, Ann" <ann@pdx.edu>` is one manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The mailboxes (E.g. `Ann <ann@pdx.edu>, bob@pdx.edu`).
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a mailbox is invalid or there are none.
+    pub fn parse_managers(text: &str) -> Result<Vec<Mailbox>, String> {
+        let mut mailboxes = Vec::new();
+        let mut start = 0;
+        let mut quoted = false;
+        let mut bracketed = false;
+        for (i, c) in text.char_indices() {
+            match c {
+                '"' if !bracketed => quoted = !quoted,
+                '<' if !quoted => bracketed = true,
+                '>' if !quoted => bracketed = false,
+                ',' if !quoted && !bracketed => {
+                    mailboxes.push(&text[start..i]);
+                    start = i + 1;
+                }
+                _ => (),
+            }
+        }
+        mailboxes.push(&text[start..]);
+        Config::managers_from(mailboxes)
+    }
+
+    /// Parses manager mailboxes, skipping blank ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `mailboxes` - The mailboxes, one in each item.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a mailbox is invalid or there are none.
+    fn managers_from<'a>(
+        mailboxes: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<Mailbox>, String> {
+        let managers = mailboxes
+            .into_iter()
+            .filter(|mailbox| !mailbox.trim().is_empty())
+            .map(Mailbox::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if managers.is_empty() {
+            return Err("at least one manager is needed".to_string());
+        }
+        Ok(managers)
+    }
+
+    /// Creates the transport emails are delivered through. Emails written
+    /// as files go under the output directory.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the transport is not understood.
+    pub fn email_transport(&self) -> Result<Box<dyn EmailTransport>, String> {
+        if self.transport.trim() == "file" {
+            return Ok(Box::new(FileTransport::new(&self.output_path)));
+        }
+        parse_transport(&self.transport)
+    }
+
+    /// The directory that EFT and NACHA files are written in.
+    pub fn eft_path(&self) -> PathBuf {
+        self.output_path.join("eft")
+    }
+
+    /// The directory that the manifest of each report run is written in.
+    pub fn manifest_path(&self) -> PathBuf {
+        self.output_path.join("manifests")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Error, ErrorKind};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn no_file(_: &str) -> std::io::Result<String> {
+        Err(Error::from(ErrorKind::NotFound))
+    }
+
+    #[test]
+    fn test_parse_mailbox() {
+        let mailbox = Mailbox::parse(" Ann Lee <ann@pdx.edu> ").unwrap();
+        assert_eq!(mailbox.name, "Ann Lee");
+        assert_eq!(mailbox.email, "ann@pdx.edu");
+        assert_eq!(mailbox.to_string(), "Ann Lee <ann@pdx.edu>");
+        let mailbox = Mailbox::parse("bob@pdx.edu").unwrap();
+        assert_eq!(mailbox.name, "bob");
+        for text in [
+            "",
+            "bob",
+            "@pdx.edu",
+            "Bob <bob>",
+            "Bob bob@pdx.edu",
+            "bob@pdx.edu\r\nBcc: eve@evil.com",
+        ] {
+            if Mailbox::parse(text).is_ok() {
+                panic!("Expected error for mailbox: {}", text)
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_overrides() {
+        let config = Config::default()
+            .with_overrides(|name| match name {
+                OUTPUT_PATH_VAR => Some("/tmp/chocanon-test".to_string()),
+                MANAGERS_VAR => {
+                    Some("Ann <ann@pdx.edu>, bob@pdx.edu,".to_string())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(config.output_path, PathBuf::from("/tmp/chocanon-test"));
+        assert_eq!(config.eft_path(), PathBuf::from("/tmp/chocanon-test/eft"));
+        assert_eq!(config.sender, Config::default().sender);
+        assert_eq!(config.managers.len(), 2);
+        assert_eq!(config.managers[1].email, "bob@pdx.edu");
+
+        let managers = Config::parse_managers(
+            "\"Lee, Ann\" <ann@pdx.edu>, \"Bob <B>\" <bob@pdx.edu>",
+        )
+        .unwrap();
+        assert_eq!(managers.len(), 2);
+        assert_eq!(managers[0].name, "Lee, Ann");
+        assert_eq!(managers[0].email, "ann@pdx.edu");
+        assert_eq!(managers[1].name, "Bob <B>");
+
+        let empty = Config::default().with_overrides(|name| match name {
+            MANAGERS_VAR => Some(" , ".to_string()),
+            _ => None,
+        });
+        if empty.is_ok() {
+            panic!("Expected error for no managers")
+        }
+    }
+
+    #[test]
+    fn test_with_file() {
+        let config = Config::default()
+            .with_file(
+                r#"
+                database = "./other.db3"
+                schedule = "Fri 23:30"
+                listen = "0.0.0.0:7000"
+
+                [email]
+                transport = "mbox:./other.mbox"
+                managers = ["\"Lee, Ann\" <ann@pdx.edu>", "bob@pdx.edu"]
+
+                [nacha]
+                company_name = "OTHER"
+                "#,
+            )
+            .unwrap();
+        assert_eq!(config.db_path, "./other.db3");
+        assert_eq!(config.schedule, Schedule::parse("Fri 23:30").unwrap());
+        assert_eq!(config.listen, "0.0.0.0:7000");
+        assert_eq!(config.transport, "mbox:./other.mbox");
+        assert_eq!(config.managers.len(), 2);
+        assert_eq!(config.managers[0].name, "Lee, Ann");
+        assert_eq!(config.originator.company_name, "OTHER");
+        assert_eq!(config.originator.company_id, "1234567890");
+        assert_eq!(config.templates_path, TEMPLATE_PATH);
+
+        for text in [
+            "datbase = \"./typo.db3\"",
+            "schedule = \"Someday\"",
+            "[email]\ntransport = \"pigeon\"",
+            "[email]\nmanagers = []",
+            "[nacha]\nimmediate_destination = \"123000221\"",
+            "[nacha]\noriginating_dfi = \"1230-0022\"",
+            "[nacha]\ncompany_name = \"CHOCOHOLICS ANONYMOUS\"",
+        ] {
+            if Config::default().with_file(text).is_ok() {
+                panic!("Expected error for configuration: {}", text)
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_precedence() {
+        let read = |path: &str| match path {
+            CONFIG_PATH => Ok("database = \"./file.db3\"\n\
+                 templates = \"./file_templates\"\n\
+                 [email]\nsender = \"file@chocan.com\"\n"
+                .to_string()),
+            _ => no_file(path),
+        };
+        let var = |name: &str| match name {
+            DB_PATH_VAR => Some("./env.db3".to_string()),
+            SENDER_VAR => Some("env@chocan.com".to_string()),
+            _ => None,
+        };
+        let (config, rest) = Config::load_with(
+            &args(&["--scheduler", "--db", "./cli.db3"]),
+            var,
+            read,
+        )
+        .unwrap();
+        assert_eq!(rest, args(&["--scheduler"]));
+        assert_eq!(config.db_path, "./cli.db3");
+        assert_eq!(config.sender, "env@chocan.com");
+        assert_eq!(config.templates_path, "./file_templates");
+
+        let (config, _) = Config::load_with(&[], |_| None, no_file).unwrap();
+        assert_eq!(config, Config::default());
+        if Config::load_with(
+            &args(&["--config", "./missing.toml"]),
+            |_| None,
+            no_file,
+        )
+        .is_ok()
+        {
+            panic!("A named configuration file must exist")
+        }
+        if Config::load_with(&args(&["--db"]), |_| None, no_file).is_ok() {
+            panic!("An option without a value is an error")
+        }
+    }
+}
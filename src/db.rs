@@ -14,30 +14,65 @@
  */
 
 //! Module for the Chocaholics Anonymous database.
+//!
+//! Every method that changes the database takes the [`Actor`] making the
+//! change and returns [`Error::Unauthorized`] if their role does not allow
+//! it, so no front end can skip the check. See [`crate::access`].
 
-use crate::esend::*;
-use chrono::{Duration, Local};
+use crate::access::{Actor, Permission, Role};
+use crate::auth;
+use crate::config::Config;
+use crate::eft::{EftRecord, write_eft_file, write_nacha_file};
+use crate::esend::{Email, EmailKind, EmailTransport, FileTransport};
+use crate::manifest::{Manifest, ManifestEntry, file_checksum};
+use crate::mime::Attachment;
+use crate::nacha::{NachaEntry, Originator, create_nacha_file};
+use crate::render::{CsvRenderer, Format, Renderer, email_bodies};
+use crate::report::{
+    DirectoryService, ManagerReport, ManagerReportLine, MemberConsultation,
+    MemberReport, ProviderConsultation, ProviderDirectory, ProviderReport,
+};
+use crate::template::{TemplateError, Templates};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Weekday};
 use regex::Regex;
 use rusqlite::{Connection, OpenFlags};
-use std::collections::HashMap;
-use std::collections::hash_map::Entry;
-
-const MAX_NAME_SIZE: u32 = 25;
-const MAX_MEMBER_ID: u32 = 999999999; // 9 Digits
-const MAX_PROVIDER_ID: u32 = 999999999; // 9 Digits
-const MAX_ADDRESS_SIZE: u32 = 25;
-const MAX_CITY_SIZE: u32 = 14;
-const STATE_SIZE: usize = 2;
-const MAX_ZIPCODE: u32 = 99999; // 5 Digits
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::path::Path;
+
+pub const MAX_NAME_SIZE: u32 = 25;
+pub const MAX_MEMBER_ID: u32 = 999999999; // 9 Digits
+pub const MAX_PROVIDER_ID: u32 = 999999999; // 9 Digits
+pub const MAX_ADDRESS_SIZE: u32 = 25;
+pub const MAX_CITY_SIZE: u32 = 14;
+pub const STATE_SIZE: usize = 2;
+pub const MAX_ZIPCODE: u32 = 99999; // 5 Digits
 //
 const DATE_TIME_SIZE: u32 = 19; // MM-DD-YYYY HH:MM:SS
 const SERVICE_DATE_SIZE: u32 = 10; // MM-DD-YYYY
-const MAX_SERVICE_CODE: u32 = 999999; // 6 Digits
-const MAX_COMMENT_SIZE: u32 = 100;
+pub const MAX_SERVICE_CODE: u32 = 999999; // 6 Digits
+pub const MAX_COMMENT_SIZE: u32 = 100;
+//
+const ROUTING_NUMBER_SIZE: usize = 9;
+const MAX_ACCOUNT_NUMBER_SIZE: usize = 17;
 //
-/// Reports newer than this many days ago will be sent.
-const REPORT_DATE_RANGE: i64 = 7;
-const CHOCAN_EMAIL: &str = "testing@chocan.com";
+/// The date format of a service date.
+const SERVICE_DATE_FORMAT: &str = "%m-%d-%Y";
+/// Sortable date formats used for recording runs.
+const RUN_DATE_FORMAT: &str = "%Y-%m-%d";
+const RUN_DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// How many times an email is tried before it is marked as failed.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+/// How long to wait before the first retry of an email.
+const RETRY_BASE_SECONDS: i64 = 60;
+/// How many failed logins in a row lock a provider or operator out.
+const MAX_FAILED_LOGINS: u32 = 5;
+/// How long a provider or operator is locked out for.
+const LOCKOUT_MINUTES: i64 = 15;
+/// Holds for an operator account unless it is the only manager, so that a
+/// database never loses the last operator who can manage the others.
+const KEEPS_A_MANAGER: &str = "(role != 'manager'
+    OR (SELECT COUNT(*) FROM operators WHERE role = 'manager') > 1)";
 
 #[derive(Debug)]
 pub enum Error {
@@ -46,6 +81,14 @@ pub enum Error {
     Regex(regex::Error),
     EmptyInput,
     NoDataFound,
+    NoBankInfo(u32),
+    AlreadySent,
+    Template(TemplateError),
+    InvalidCredential(String),
+    LoginFailed,
+    LockedOut(NaiveDateTime),
+    Unauthorized(String),
+    Nacha(String),
 }
 
 impl std::fmt::Display for Error {
@@ -56,6 +99,26 @@ impl std::fmt::Display for Error {
             Error::Regex(ref err) => write!(f, "Regex error: {}", err),
             Error::EmptyInput => write!(f, "Empty input error"),
             Error::NoDataFound => write!(f, "No data found error"),
+            Error::NoBankInfo(id) => {
+                write!(f, "No bank information for provider: {}", id)
+            }
+            Error::AlreadySent => {
+                write!(f, "Already sent for this period error")
+            }
+            Error::Template(ref err) => write!(f, "Template error: {}", err),
+            Error::InvalidCredential(ref msg) => {
+                write!(f, "Invalid credential error: {}", msg)
+            }
+            Error::LoginFailed => write!(f, "Login failed error"),
+            Error::LockedOut(until) => write!(
+                f,
+                "Locked out until {} error",
+                until.format("%m-%d-%Y %H:%M:%S")
+            ),
+            Error::Unauthorized(ref msg) => {
+                write!(f, "Unauthorized error: {}", msg)
+            }
+            Error::Nacha(ref msg) => write!(f, "NACHA file error: {}", msg),
         }
     }
 }
@@ -64,6 +127,8 @@ impl std::fmt::Display for Error {
 #[derive(Debug)]
 pub struct DB {
     conn: Connection,
+    transport: Box<dyn EmailTransport>,
+    config: Config,
 }
 
 impl DB {
@@ -77,6 +142,43 @@ impl DB {
     ///
     /// Will return `Err` if database could not be established.
     pub fn new(path: &str) -> Result<Self, Error> {
+        DB::with_transport(path, Box::new(FileTransport::default()))
+    }
+
+    /// Create a ChocAn database that sends emails through a transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the database file (E.g. `./database.db3`).
+    /// * `transport` - How emails such as reports are delivered.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if database could not be established.
+    pub fn with_transport(
+        path: &str,
+        transport: Box<dyn EmailTransport>,
+    ) -> Result<Self, Error> {
+        DB::with_config(path, transport, Config::default())
+    }
+
+    /// Create a ChocAn database that sends emails through a transport, with
+    /// its output directory, sender and managers set by a configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the database file (E.g. `./database.db3`).
+    /// * `transport` - How emails such as reports are delivered.
+    /// * `config` - Where files are written and who emails are from and to.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if database could not be established.
+    pub fn with_config(
+        path: &str,
+        transport: Box<dyn EmailTransport>,
+        config: Config,
+    ) -> Result<Self, Error> {
         let conn = Connection::open_with_flags(
             path,
             OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
@@ -174,18 +276,250 @@ impl DB {
             MAX_SERVICE_CODE,
         );
         conn.execute(&sql, []).map_err(Error::Sql)?;
-        Ok(DB { conn })
+        sql = format!(
+            "CREATE TABLE IF NOT EXISTS provider_bank_accounts (
+                provider_id     INTEGER NOT NULL PRIMARY KEY CHECK (
+                    provider_id <= {}
+                    AND provider_id >= 0
+                ),
+                routing_number  TEXT NOT NULL CHECK (
+                    length(routing_number) == {}
+                ),
+                account_number  TEXT NOT NULL CHECK (
+                    length(account_number) <= {}
+                ),
+                account_type    TEXT NOT NULL CHECK (
+                    account_type IN ('checking', 'savings')
+                )
+            )",
+            MAX_PROVIDER_ID, ROUTING_NUMBER_SIZE, MAX_ACCOUNT_NUMBER_SIZE,
+        );
+        conn.execute(&sql, []).map_err(Error::Sql)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS report_runs (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                report_type     TEXT NOT NULL,
+                period_start    TEXT NOT NULL,
+                period_end      TEXT NOT NULL,
+                recipients      TEXT NOT NULL,
+                files           TEXT NOT NULL,
+                status          TEXT NOT NULL CHECK (
+                    status IN ('success', 'failed')
+                ),
+                created_at      TEXT NOT NULL,
+                scheduled_for   TEXT,
+                message         TEXT NOT NULL DEFAULT ''
+            )",
+            [],
+        )
+        .map_err(Error::Sql)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS report_formats (
+                report_type     TEXT NOT NULL,
+                recipient       TEXT NOT NULL,
+                format          TEXT NOT NULL CHECK (
+                    format IN ('text', 'html', 'csv', 'json')
+                ),
+                PRIMARY KEY (report_type, recipient)
+            )",
+            [],
+        )
+        .map_err(Error::Sql)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS report_csv_attachments (
+                report_type     TEXT NOT NULL,
+                recipient       TEXT NOT NULL,
+                attach          INTEGER NOT NULL CHECK (attach IN (0, 1)),
+                PRIMARY KEY (report_type, recipient)
+            )",
+            [],
+        )
+        .map_err(Error::Sql)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS outbox (
+                id              INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind            TEXT NOT NULL,
+                recipient       TEXT NOT NULL,
+                recipient_name  TEXT NOT NULL,
+                sender          TEXT NOT NULL,
+                subject         TEXT NOT NULL,
+                body            TEXT NOT NULL,
+                html            TEXT,
+                status          TEXT NOT NULL CHECK (
+                    status IN ('queued', 'sent', 'failed')
+                ),
+                attempts        INTEGER NOT NULL,
+                last_error      TEXT,
+                next_attempt_at TEXT NOT NULL,
+                delivered_to    TEXT,
+                created_at      TEXT NOT NULL,
+                updated_at      TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(Error::Sql)?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS outbox_attachments (
+                outbox_id       INTEGER NOT NULL REFERENCES outbox (id),
+                position        INTEGER NOT NULL,
+                file_name       TEXT NOT NULL,
+                content_type    TEXT NOT NULL,
+                data            BLOB NOT NULL,
+                PRIMARY KEY (outbox_id, position)
+            )",
+            [],
+        )
+        .map_err(Error::Sql)?;
+        sql = format!(
+            "CREATE TABLE IF NOT EXISTS provider_pins (
+                provider_id     INTEGER NOT NULL PRIMARY KEY CHECK (
+                    provider_id <= {}
+                    AND provider_id >= 0
+                ),
+                secret_hash     TEXT NOT NULL,
+                failed_logins   INTEGER NOT NULL,
+                locked_until    TEXT
+            )",
+            MAX_PROVIDER_ID,
+        );
+        conn.execute(&sql, []).map_err(Error::Sql)?;
+        sql = format!(
+            "CREATE TABLE IF NOT EXISTS operators (
+                username        TEXT NOT NULL PRIMARY KEY CHECK (
+                    length(username) <= {}
+                ),
+                secret_hash     TEXT NOT NULL,
+                failed_logins   INTEGER NOT NULL,
+                locked_until    TEXT,
+                created_at      TEXT NOT NULL,
+                role            TEXT NOT NULL CHECK (
+                    role IN ('auditor', 'clerk', 'manager')
+                )
+            )",
+            auth::MAX_USERNAME_SIZE,
+        );
+        conn.execute(&sql, []).map_err(Error::Sql)?;
+        Ok(DB {
+            conn,
+            transport,
+            config,
+        })
     }
 
     /// Sends out all member reports to all ChocAn members.
     ///
-    /// Reports will only be sent to those with recent activity.
+    /// Reports will only be sent to those with activity during the period.
     /// Reports will only be sent valid persons.
     ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `period` - The period of time to report on.
+    /// * `force` - Send the reports even if the member reports for the
+    ///   period were already sent, which is otherwise refused.
+    ///
+    /// # Success
+    ///
+    /// Will return who the reports were delivered to and any recipients
+    /// whose report could not be delivered.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the reports could not be built, or if the reports
+    /// for the period were already sent and `force` is not set.
+    pub fn send_member_reports(
+        &self,
+        actor: &Actor,
+        period: &ReportPeriod,
+        force: bool,
+    ) -> Result<DeliverySummary, Error> {
+        authorize(actor, Permission::SendReports)?;
+        self.run_report(ReportType::Member, period, force, |_| {
+            let reports = self.build_member_reports(period)?;
+            self.queue_emails(&self.member_report_emails(&reports)?)
+        })
+    }
+
+    /// Creates the emails for member reports.
+    ///
+    /// # Arguments
+    ///
+    /// * `reports` - The reports to email.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the templates or report formats could not be
+    /// read.
+    fn member_report_emails(
+        &self,
+        reports: &[MemberReport],
+    ) -> Result<Vec<Email>, Error> {
+        let templates = self.load_templates()?;
+        let mut emails = Vec::new();
+        for report in reports {
+            let format = self
+                .get_report_format(ReportType::Member, &report.member.email)?;
+            let bodies =
+                email_bodies(format, &templates, report, |renderer, report| {
+                    renderer.member_report(report)
+                });
+            emails.push(self.report_email(
+                EmailKind::MemberReport,
+                &report.member.email,
+                &report.member.name,
+                &report.subject(),
+                bodies,
+            ));
+        }
+        Ok(emails)
+    }
+
+    /// Previews the member reports for a period without sending them.
+    ///
+    /// Nothing is queued and the period is not marked as sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time to report on.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if there is no activity during the period or the
+    /// reports could not be built.
+    pub fn preview_member_reports(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<ReportPreview, Error> {
+        let reports = self.build_member_reports(period)?;
+        let consultations: usize = reports
+            .iter()
+            .map(|report| report.consultations.len())
+            .sum();
+        Ok(ReportPreview {
+            report_type: ReportType::Member,
+            emails: self.member_report_emails(&reports)?,
+            totals: vec![
+                ("Members".to_string(), reports.len().to_string()),
+                ("Consultations".to_string(), consultations.to_string()),
+            ],
+        })
+    }
+
+    /// Builds the member reports for every member with activity during the
+    /// period, ordered by member id.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time to report on.
+    ///
     /// # Failure
     ///
-    /// Will return `Err` if any reports are not sent.
-    pub fn send_member_reports(&self) -> Result<(), Error> {
+    /// Will return `Err` if there is no activity during the period or the
+    /// reports could not be built.
+    pub fn build_member_reports(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<Vec<MemberReport>, Error> {
         let mut stmt = self
             .conn
             .prepare(
@@ -208,76 +542,167 @@ impl DB {
             })
             .map_err(Error::Sql)?;
 
-        let mut num_emails: u64 = 0;
-        let mut reports = HashMap::new();
+        let mut reports = BTreeMap::new();
         for (service_date, member_id, provider_id, service_id) in rows.flatten()
         {
-            if service_date
-                < (Local::now() - Duration::days(REPORT_DATE_RANGE))
-                    .format("%m-%d-%Y")
-                    .to_string()
-            {
+            if !period.contains_service_date(&service_date) {
                 continue;
             }
-            let member: PersonInfo = self.get_member_info(member_id)?;
             let provider: PersonInfo = self.get_provider_info(provider_id)?;
             let service_name: String = self.get_service_name(service_id)?;
-            let subject = "Member Report for ".to_owned() + &member.name;
-            let consul_text = Self::create_consultation_text(
-                &service_date,
-                &provider.name,
-                &service_name,
-            );
 
             if let Entry::Vacant(e) = reports.entry(member_id) {
-                let body = Self::create_member_report_body(&member);
-                e.insert((member.email, subject, body, member.name));
+                e.insert(MemberReport {
+                    period: *period,
+                    member: self.get_member_info(member_id)?,
+                    consultations: Vec::new(),
+                });
             }
-            if let Some(values) = reports.get_mut(&member_id) {
-                values.2.push_str(&consul_text);
+            if let Some(report) = reports.get_mut(&member_id) {
+                report.consultations.push(MemberConsultation {
+                    service_date,
+                    provider_name: provider.name,
+                    service_name,
+                });
             }
         }
-
-        for (_key, (email, subject, body, name)) in reports {
-            num_emails += 1;
-            send_member_report(&email, CHOCAN_EMAIL, &subject, &body, &name)
-                .map_err(Error::Io)?;
-        }
-        if num_emails == 0 {
+        if reports.is_empty() {
             return Err(Error::NoDataFound);
         }
-        Ok(())
+        Ok(reports.into_values().collect())
+    }
+
+    /// Sends out all provider reports to all ChocAn providers.
+    ///
+    /// Reports will only be sent to those with activity during the period.
+    /// Reports will only be sent valid persons.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `period` - The period of time to report on.
+    /// * `force` - Send the reports even if the provider reports for the
+    ///   period were already sent, which is otherwise refused.
+    ///
+    /// # Success
+    ///
+    /// Will return who the reports were delivered to and any recipients
+    /// whose report could not be delivered.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the reports could not be built, or if the reports
+    /// for the period were already sent and `force` is not set.
+    pub fn send_provider_reports(
+        &self,
+        actor: &Actor,
+        period: &ReportPeriod,
+        force: bool,
+    ) -> Result<DeliverySummary, Error> {
+        authorize(actor, Permission::SendReports)?;
+        self.run_report(ReportType::Provider, period, force, |_| {
+            let reports = self.build_provider_reports(period)?;
+            self.queue_emails(&self.provider_report_emails(period, &reports)?)
+        })
     }
 
-    fn create_member_report_body(member: &PersonInfo) -> String {
-        format!("Member name: {}\n", member.name)
-            + &format!("Member number: {}\n", member.id)
-            + &format!("Member street address: {}\n", member.location.address)
-            + &format!("Member city: {}\n", member.location.city)
-            + &format!("Member state: {}\n", member.location.state)
-            + &format!("Member zip code: {}\n", member.location.zipcode)
+    /// Creates the emails for provider reports.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time being reported on.
+    /// * `reports` - The reports to email.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the templates or report settings could not be
+    /// read.
+    fn provider_report_emails(
+        &self,
+        period: &ReportPeriod,
+        reports: &[ProviderReport],
+    ) -> Result<Vec<Email>, Error> {
+        let templates = self.load_templates()?;
+        let mut emails = Vec::new();
+        for report in reports {
+            let format = self.get_report_format(
+                ReportType::Provider,
+                &report.provider.email,
+            )?;
+            let bodies =
+                email_bodies(format, &templates, report, |renderer, report| {
+                    renderer.provider_report(report)
+                });
+            let mut email = self.report_email(
+                EmailKind::ProviderReport,
+                &report.provider.email,
+                &report.provider.name,
+                &report.subject(),
+                bodies,
+            );
+            if self.get_csv_attachment(
+                ReportType::Provider,
+                &report.provider.email,
+            )? {
+                email.attachments.push(csv_attachment(
+                    "provider_report",
+                    period,
+                    CsvRenderer.provider_report(report),
+                ));
+            }
+            emails.push(email);
+        }
+        Ok(emails)
     }
 
-    fn create_consultation_text(
-        service_date: &str,
-        provider_name: &str,
-        service_name: &str,
-    ) -> String {
-        "----------------------------------------\n".to_string()
-            + &format!("Date of service: {}\n", service_date)
-            + &format!("Provider name: {}\n", provider_name)
-            + &format!("Service name: {}\n", service_name)
+    /// Previews the provider reports for a period without sending them.
+    ///
+    /// Nothing is queued and the period is not marked as sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time to report on.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if there is no activity during the period or the
+    /// reports could not be built.
+    pub fn preview_provider_reports(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<ReportPreview, Error> {
+        let reports = self.build_provider_reports(period)?;
+        let consultations: u32 = reports
+            .iter()
+            .map(|report| report.total_consultations)
+            .sum();
+        let fees: f64 = reports.iter().map(|report| report.total_fee).sum();
+        Ok(ReportPreview {
+            report_type: ReportType::Provider,
+            emails: self.provider_report_emails(period, &reports)?,
+            totals: vec![
+                ("Providers".to_string(), reports.len().to_string()),
+                ("Consultations".to_string(), consultations.to_string()),
+                ("Total fees".to_string(), format!("${:.2}", fees)),
+            ],
+        })
     }
 
-    /// Sends out all provider reports to all ChocAn providers.
+    /// Builds the provider reports for every provider with activity during
+    /// the period, ordered by provider id.
     ///
-    /// Reports will only be sent to those with recent activity.
-    /// Reports will only be sent valid persons.
+    /// # Arguments
+    ///
+    /// * `period` - The period of time to report on.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if any reports are not sent.
-    pub fn send_provider_reports(&self) -> Result<(), Error> {
+    /// Will return `Err` if there is no activity during the period or the
+    /// reports could not be built.
+    pub fn build_provider_reports(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<Vec<ProviderReport>, Error> {
         let mut stmt = self
             .conn
             .prepare(
@@ -308,8 +733,7 @@ impl DB {
             })
             .map_err(Error::Sql)?;
 
-        let mut num_emails: u64 = 0;
-        let mut reports = HashMap::new();
+        let mut reports = BTreeMap::new();
         for (
             service_date,
             member_id,
@@ -318,215 +742,813 @@ impl DB {
             current_date_time,
         ) in rows.flatten()
         {
-            if service_date
-                < (Local::now() - Duration::days(REPORT_DATE_RANGE))
-                    .format("%m-%d-%Y")
-                    .to_string()
-            {
+            if !period.contains_service_date(&service_date) {
                 continue;
             }
             let member: PersonInfo = self.get_member_info(member_id)?;
-            let provider: PersonInfo = self.get_provider_info(provider_id)?;
             let fee: f64 = self.get_service_fee(service_id)?;
-            let subject = "Provider Report for ".to_owned() + &provider.name;
-            let consul_text = Self::create_provider_consultation_text(
-                &service_date,
-                &current_date_time,
-                &member.name,
-                member_id,
-                service_id,
-                fee,
-            );
 
-            // Create the email if it doesn't exist
+            // Create the report if it doesn't exist
             if let Entry::Vacant(e) = reports.entry(provider_id) {
-                let body = Self::create_provider_report_header(&provider);
-                e.insert((
-                    provider.email,
-                    subject,
-                    body,
-                    provider.name,
-                    0,
-                    0.0,
-                ));
+                e.insert(ProviderReport {
+                    period: *period,
+                    provider: self.get_provider_info(provider_id)?,
+                    consultations: Vec::new(),
+                    total_consultations: 0,
+                    total_fee: 0.0,
+                });
             }
             // Append the consultation
-            if let Some(values) = reports.get_mut(&provider_id) {
-                values.2.push_str(&consul_text);
-                values.4 += 1;
-                values.5 += fee;
+            if let Some(report) = reports.get_mut(&provider_id) {
+                report.consultations.push(ProviderConsultation {
+                    service_date,
+                    received: current_date_time,
+                    member_name: member.name,
+                    member_id,
+                    service_code: service_id,
+                    fee,
+                });
+                report.total_consultations += 1;
+                report.total_fee += fee;
             }
         }
-
-        for (_key, (email, subject, mut body, name, consuls, fees)) in reports {
-            num_emails += 1;
-            let footer = Self::create_provider_report_footer(consuls, fees);
-            body.push_str(&footer);
-            send_provider_report(&email, CHOCAN_EMAIL, &subject, &body, &name)
-                .map_err(Error::Io)?;
-        }
-        if num_emails == 0 {
+        if reports.is_empty() {
             return Err(Error::NoDataFound);
         }
-        Ok(())
+        Ok(reports.into_values().collect())
     }
 
-    fn create_provider_report_header(provider: &PersonInfo) -> String {
-        format!("Provider name: {}\n", provider.name)
-            + &format!("Provider number: {}\n", provider.id)
-            + &format!(
-                "Provider street address: {}\n",
-                provider.location.address
-            )
-            + &format!("Provider city: {}\n", provider.location.city)
-            + &format!("Provider state: {}\n", provider.location.state)
-            + &format!("Provider zip code: {}\n", provider.location.zipcode)
+    /// Sends out a manager report to every configured ChocAn manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `period` - The period of time to report on.
+    /// * `force` - Send the report even if it was already sent for the
+    ///   period, which is otherwise refused.
+    ///
+    /// # Success
+    ///
+    /// Will return where the report was delivered, or the delivery failure.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the report could not be built, or if the report
+    /// for the period was already sent and `force` is not set.
+    pub fn send_manager_report(
+        &self,
+        actor: &Actor,
+        period: &ReportPeriod,
+        force: bool,
+    ) -> Result<DeliverySummary, Error> {
+        authorize(actor, Permission::SendReports)?;
+        self.run_report(ReportType::Manager, period, force, |_| {
+            let report = self.build_manager_report(period)?;
+            self.queue_emails(&self.manager_report_emails(period, &report)?)
+        })
     }
 
-    fn create_provider_consultation_text(
-        service_date: &str,
-        service_date_time: &str,
-        member_name: &str,
-        member_number: u32,
-        service_code: u32,
-        fee: f64,
-    ) -> String {
-        "----------------------------------------\n".to_string()
-            + &format!("Date of service: {}\n", service_date)
-            + &format!(
-                "Date and time data were received by the computer: {}\n",
-                service_date_time
-            )
-            + &format!("Member name: {}\n", member_name)
-            + &format!("Member number: {}\n", member_number)
-            + &format!("Service code: {}\n", service_code)
-            + &format!("Fee: {}\n", fee)
+    /// Creates the emails of the manager report, one for every manager.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time being reported on.
+    /// * `report` - The report to email.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the templates or report settings could not be
+    /// read.
+    fn manager_report_emails(
+        &self,
+        period: &ReportPeriod,
+        report: &ManagerReport,
+    ) -> Result<Vec<Email>, Error> {
+        let templates = self.load_templates()?;
+        let mut emails = Vec::new();
+        for manager in &self.config.managers {
+            let format =
+                self.get_report_format(ReportType::Manager, &manager.email)?;
+            let bodies =
+                email_bodies(format, &templates, report, |renderer, report| {
+                    renderer.manager_report(report)
+                });
+            let mut email = self.report_email(
+                EmailKind::ManagerReport,
+                &manager.email,
+                &manager.name,
+                &report.subject(),
+                bodies,
+            );
+            if self.get_csv_attachment(ReportType::Manager, &manager.email)? {
+                email.attachments.push(csv_attachment(
+                    "manager_report",
+                    period,
+                    CsvRenderer.manager_report(report),
+                ));
+            }
+            emails.push(email);
+        }
+        Ok(emails)
     }
 
-    fn create_provider_report_footer(
-        total_consultations: u32,
-        total_fee: f64,
-    ) -> String {
-        "----------------------------------------\n".to_string()
-            + &format!("Total consultations: {}\n", total_consultations)
-            + &format!("Total fee: {}\n", total_fee)
+    /// Previews the manager report for a period without sending it.
+    ///
+    /// Nothing is queued and the period is not marked as sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time to report on.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if there is no activity during the period or the
+    /// report could not be built.
+    pub fn preview_manager_report(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<ReportPreview, Error> {
+        let report = self.build_manager_report(period)?;
+        Ok(ReportPreview {
+            report_type: ReportType::Manager,
+            emails: self.manager_report_emails(period, &report)?,
+            totals: vec![
+                ("Providers".to_string(), report.total_providers.to_string()),
+                (
+                    "Consultations".to_string(),
+                    report.total_consultations.to_string(),
+                ),
+                (
+                    "Total fees".to_string(),
+                    format!("${:.2}", report.total_fees),
+                ),
+            ],
+        })
     }
 
-    /// Sends out a manager report to the ChocAn manager.
+    /// Builds the manager report for the period.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time to report on.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if any reports are not sent.
-    pub fn send_manager_report(&self) -> Result<(), Error> {
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT
-                service_date,
+    /// Will return `Err` if there is no activity during the period or the
+    /// report could not be built.
+    pub fn build_manager_report(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<ManagerReport, Error> {
+        let mut report = ManagerReport {
+            period: *period,
+            providers: Vec::new(),
+            total_providers: 0,
+            total_consultations: 0,
+            total_fees: 0.0,
+        };
+        for (provider_id, consultations, fees) in
+            self.get_provider_totals(period)?
+        {
+            report.total_providers += 1;
+            report.total_consultations += consultations;
+            report.total_fees += fees;
+            report.providers.push(ManagerReportLine {
                 provider_id,
-                service_code
-            FROM consultations
-            ORDER BY consultations.provider_id ASC",
-            )
-            .map_err(Error::Sql)?;
-        let rows = stmt
-            .query_map([], |row| {
-                let service_date: String = row.get(0)?;
-                let provider_id: u32 = row.get(1)?;
-                let service_code: u32 = row.get(2)?;
-                Ok((service_date, provider_id, service_code))
-            })
-            .map_err(Error::Sql)?;
-
-        let mut total_consuls: u32 = 0;
-        let mut total_providers: u32 = 0;
-        let mut total_fees: f64 = 0.0;
-        let mut providers = HashMap::new();
-        for (service_date, provider_id, service_code) in rows.flatten() {
-            if service_date
-                < (Local::now() - Duration::days(REPORT_DATE_RANGE))
-                    .format("%m-%d-%Y")
-                    .to_string()
-            {
-                continue;
-            }
-            let curr_fee = self.get_service_fee(service_code)?;
-            if let Entry::Vacant(e) = providers.entry(provider_id) {
-                e.insert((0, 0.0));
-            }
-            if let Some(values) = providers.get_mut(&provider_id) {
-                values.0 += 1;
-                values.1 += curr_fee;
-            }
-            total_consuls += 1;
-        }
-        if total_consuls == 0 {
-            return Err(Error::NoDataFound);
-        }
-        let mut report_body: String = "".to_string();
-        for (key, (provider_consuls, provider_fee)) in providers {
-            total_providers += 1;
-            total_fees += provider_fee;
-            report_body.push_str(&Self::create_manager_report_section(
-                key,
-                provider_consuls,
-                provider_fee,
-            ));
+                consultations,
+                fees,
+            });
         }
-        report_body.push_str(&Self::create_manager_report_footer(
-            total_providers,
-            total_consuls,
-            total_fees,
-        ));
-        send_manager_report(
-            "manager@pdx.edu",
-            CHOCAN_EMAIL,
-            "Manager report",
-            &report_body,
-            "ManagerName",
-        )
-        .map_err(Error::Io)?;
-        Ok(())
-    }
-
-    fn create_manager_report_section(
-        provider_id: u32,
-        consultations: u32,
-        fees: f64,
-    ) -> String {
-        "- ".to_string()
-            + &format!("ID: {}, ", provider_id)
-            + &format!("Consultations: {}, ", consultations)
-            + &format!("Fees: {}\n", fees)
-    }
-
-    fn create_manager_report_footer(
-        total_providers: u32,
-        total_consultations: u32,
-        total_fees: f64,
-    ) -> String {
-        "----------------------------------------\n".to_string()
-            + &format!("Total providers: {}\n", total_providers)
-            + &format!("Total consultations: {}\n", total_consultations)
-            + &format!("Total fees: {}\n", total_fees)
+        Ok(report)
     }
 
-    /// Sends out the provider directory to the specified provider.
+    /// Writes out an EFT file to pay every provider with activity during
+    /// the period.
+    ///
+    /// The totals are the same as the ones in the manager report.
     ///
     /// # Arguments
     ///
-    /// * `id - The id of the provider to send the report to.
+    /// * `actor` - Who is making the change.
+    /// * `period` - The period of time to pay providers for.
+    /// * `force` - Write another EFT file even if one was already written
+    ///   for the period, which could pay the providers twice.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if not sent.
-    pub fn send_provider_directory(&self, id: u32) -> Result<(), Error> {
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT
-                service_id,
-                name,
+    /// Will return `Err` if the EFT file is not written, or if the EFT
+    /// records for the period were already written and `force` is not set.
+    pub fn send_eft_records(
+        &self,
+        actor: &Actor,
+        period: &ReportPeriod,
+        force: bool,
+    ) -> Result<DeliverySummary, Error> {
+        authorize(actor, Permission::SendReports)?;
+        self.run_report(ReportType::Eft, period, force, |run| {
+            self.deliver_eft_records(period, run)
+        })
+    }
+
+    fn deliver_eft_records(
+        &self,
+        period: &ReportPeriod,
+        run: &mut ReportRun,
+    ) -> Result<Vec<u32>, Error> {
+        let providers = self.get_provider_totals(period)?;
+
+        let mut records = Vec::new();
+        for (provider_id, _consuls, fee) in providers {
+            let provider = self.get_provider_info(provider_id)?;
+            run.recipients.push(provider.email);
+            records.push(EftRecord {
+                provider_name: provider.name,
+                provider_id,
+                amount: fee,
+            });
+        }
+        let file = write_eft_file(&records, &self.config.eft_path())
+            .map_err(Error::Io)?;
+        run.files.push(file);
+        Ok(Vec::new())
+    }
+
+    /// Writes out a NACHA ACH file to pay every provider with activity
+    /// during the period.
+    ///
+    /// The totals are the same as the ones in the manager report.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `period` - The period of time to pay providers for.
+    /// * `originator` - The bank and company sending the payments.
+    /// * `force` - Write another file even if one was already written for
+    ///   the period, which could pay the providers twice.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a provider has no bank information or the file
+    /// is not written, or if the file for the period was already written
+    /// and `force` is not set.
+    pub fn send_nacha_file(
+        &self,
+        actor: &Actor,
+        period: &ReportPeriod,
+        originator: &Originator,
+        force: bool,
+    ) -> Result<DeliverySummary, Error> {
+        authorize(actor, Permission::SendReports)?;
+        self.run_report(ReportType::Nacha, period, force, |run| {
+            self.deliver_nacha_file(period, originator, run)
+        })
+    }
+
+    fn deliver_nacha_file(
+        &self,
+        period: &ReportPeriod,
+        originator: &Originator,
+        run: &mut ReportRun,
+    ) -> Result<Vec<u32>, Error> {
+        let providers = self.get_provider_totals(period)?;
+
+        let mut entries = Vec::new();
+        for (provider_id, _consuls, fee) in providers {
+            let provider = self.get_provider_info(provider_id)?;
+            let bank = match self.get_provider_bank_info(provider_id) {
+                Ok(bank) => bank,
+                Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows)) => {
+                    return Err(Error::NoBankInfo(provider_id));
+                }
+                Err(err) => return Err(err),
+            };
+            run.recipients.push(provider.email);
+            entries.push(NachaEntry {
+                provider_name: provider.name,
+                provider_id,
+                bank,
+                amount_cents: (fee * 100.0).round() as u64,
+            });
+        }
+
+        let now = Local::now();
+        let mut effective_date = now.date_naive() + Duration::days(1);
+        while effective_date.weekday().num_days_from_monday() >= 5 {
+            effective_date += Duration::days(1);
+        }
+        let contents = create_nacha_file(
+            originator,
+            &entries,
+            effective_date,
+            now.naive_local(),
+        )
+        .map_err(Error::Nacha)?;
+        let file = write_nacha_file(&contents, &self.config.eft_path())
+            .map_err(Error::Io)?;
+        run.files.push(file);
+        Ok(Vec::new())
+    }
+
+    /// Sends a report for a period and records the run.
+    ///
+    /// Every email for the report is queued in the outbox before any are
+    /// delivered, so that an email that could not be delivered is retried
+    /// later instead of being lost. Nothing is queued if the report fails.
+    /// No run is recorded if there is no activity during the period.
+    ///
+    /// # Arguments
+    ///
+    /// * `report_type` - The type of report being sent.
+    /// * `period` - The period of time being reported on.
+    /// * `force` - Send the report even if it was already sent.
+    /// * `queue` - Queues the emails for the report, returning their ids in
+    ///   the outbox, and adds any files it writes to the run along with who
+    ///   they are for, such as the providers an EFT file pays.
+    ///
+    /// # Success
+    ///
+    /// Will return the recipients and files that were delivered, including
+    /// those added to the run by `queue`, and the recipients that failed. The run is recorded as sent along with its
+    /// queued emails, before any are delivered, so that a run that fails
+    /// part way is never sent again. The manifest is written after the
+    /// emails are delivered, so a manifest that could not be written is
+    /// given in the summary instead of failing the run.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the report was already sent and `force` is not
+    /// set, if `queue` fails or if the run was not recorded.
+    fn run_report<F>(
+        &self,
+        report_type: ReportType,
+        period: &ReportPeriod,
+        force: bool,
+        queue: F,
+    ) -> Result<DeliverySummary, Error>
+    where
+        F: FnOnce(&mut ReportRun) -> Result<Vec<u32>, Error>,
+    {
+        if !force && self.is_report_sent(report_type, period)? {
+            return Err(Error::AlreadySent);
+        }
+        let mut run = ReportRun {
+            id: 0,
+            report_type,
+            period: *period,
+            recipients: Vec::new(),
+            files: Vec::new(),
+            success: true,
+            created_at: Local::now().naive_local(),
+            scheduled_for: None,
+            message: String::new(),
+        };
+        let tx = self.conn.unchecked_transaction().map_err(Error::Sql)?;
+        let queued = match queue(&mut run) {
+            Ok(queued) => queued,
+            Err(Error::NoDataFound) => return Err(Error::NoDataFound),
+            Err(err) => {
+                drop(tx);
+                run.success = false;
+                self.add_report_run(&run)?;
+                return Err(err);
+            }
+        };
+        run.id = self.add_report_run(&run)?;
+        tx.commit().map_err(Error::Sql)?;
+
+        let mut summary = DeliverySummary::default();
+        for id in queued {
+            self.deliver_queued(id, run.created_at, &mut summary)?;
+        }
+        let manifest = self.run_manifest(&run, &summary).and_then(|manifest| {
+            manifest
+                .write(&self.config.manifest_path())
+                .map_err(Error::Io)
+        });
+        run.recipients.extend(summary.recipients.iter().cloned());
+        run.files.extend(summary.files.iter().cloned());
+        self.update_report_run(&run)?;
+        summary.recipients = run.recipients;
+        summary.files = run.files;
+        match manifest {
+            Ok(manifest) => summary.manifest = Some(manifest),
+            Err(err) => summary.manifest_error = Some(err.to_string()),
+        }
+        Ok(summary)
+    }
+
+    /// Builds the manifest of everything a report run delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `run` - The run, with the files it wrote itself and who they are
+    ///   for.
+    /// * `summary` - The emails the run delivered and those that failed.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a file could not be read for its checksum.
+    fn run_manifest(
+        &self,
+        run: &ReportRun,
+        summary: &DeliverySummary,
+    ) -> Result<Manifest, Error> {
+        let report_type = run.report_type.to_string();
+        let mut entries = Vec::new();
+        for file in &run.files {
+            let sha256 = Some(file_checksum(file).map_err(Error::Io)?);
+            if run.recipients.is_empty() {
+                entries.push(ManifestEntry {
+                    file: file.clone(),
+                    recipient: None,
+                    report_type: report_type.clone(),
+                    sha256,
+                });
+                continue;
+            }
+            for recipient in &run.recipients {
+                entries.push(ManifestEntry {
+                    file: file.clone(),
+                    recipient: Some(recipient.clone()),
+                    report_type: report_type.clone(),
+                    sha256: sha256.clone(),
+                });
+            }
+        }
+        for (recipient, file) in summary.recipients.iter().zip(&summary.files) {
+            let sha256 = if self.transport.writes_files() {
+                Some(file_checksum(file).map_err(Error::Io)?)
+            } else {
+                None
+            };
+            entries.push(ManifestEntry {
+                file: file.clone(),
+                recipient: Some(recipient.clone()),
+                report_type: report_type.clone(),
+                sha256,
+            });
+        }
+        Ok(Manifest {
+            report_type,
+            period_start: run
+                .period
+                .start()
+                .format(RUN_DATE_FORMAT)
+                .to_string(),
+            period_end: run.period.end().format(RUN_DATE_FORMAT).to_string(),
+            created_at: run.created_at.format(RUN_DATE_TIME_FORMAT).to_string(),
+            entries,
+            failed: summary
+                .failed
+                .iter()
+                .map(|failure| {
+                    (failure.recipient.clone(), failure.error.clone())
+                })
+                .collect(),
+        })
+    }
+
+    /// Checks if a report was already successfully sent for a period.
+    ///
+    /// # Arguments
+    ///
+    /// * `report_type` - The type of report to check.
+    /// * `period` - The period of time to check.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error.
+    pub fn is_report_sent(
+        &self,
+        report_type: ReportType,
+        period: &ReportPeriod,
+    ) -> Result<bool, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT COUNT(*) FROM report_runs
+                WHERE report_type = ?1
+                AND period_start = ?2
+                AND period_end = ?3
+                AND status = 'success'",
+            )
+            .map_err(Error::Sql)?;
+        let count: u32 = stmt
+            .query_row(
+                rusqlite::params![
+                    report_type.as_str(),
+                    period.start().format(RUN_DATE_FORMAT).to_string(),
+                    period.end().format(RUN_DATE_FORMAT).to_string(),
+                ],
+                |row| row.get(0),
+            )
+            .map_err(Error::Sql)?;
+        Ok(count > 0)
+    }
+
+    /// Records a report run.
+    ///
+    /// # Success
+    ///
+    /// Will return the id of the run.
+    fn add_report_run(&self, run: &ReportRun) -> Result<u32, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "INSERT INTO report_runs (
+                report_type,
+                period_start,
+                period_end,
+                recipients,
+                files,
+                status,
+                created_at,
+                scheduled_for,
+                message
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )
+            .map_err(Error::Sql)?;
+        stmt.execute(rusqlite::params![
+            run.report_type.as_str(),
+            run.period.start().format(RUN_DATE_FORMAT).to_string(),
+            run.period.end().format(RUN_DATE_FORMAT).to_string(),
+            run.recipients.join("\n"),
+            run.files.join("\n"),
+            if run.success { "success" } else { "failed" },
+            run.created_at.format(RUN_DATE_TIME_FORMAT).to_string(),
+            run.scheduled_for
+                .map(|time| time.format(RUN_DATE_TIME_FORMAT).to_string()),
+            &run.message,
+        ])
+        .map_err(Error::Sql)?;
+        u32::try_from(self.conn.last_insert_rowid())
+            .map_err(|err| Error::Io(std::io::Error::other(err)))
+    }
+
+    /// Records the recipients and files a report run delivered to.
+    fn update_report_run(&self, run: &ReportRun) -> Result<(), Error> {
+        self.conn
+            .execute(
+                "UPDATE report_runs SET recipients = ?2, files = ?3
+                WHERE id = ?1",
+                rusqlite::params![
+                    run.id,
+                    run.recipients.join("\n"),
+                    run.files.join("\n"),
+                ],
+            )
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Gets every recorded report run.
+    ///
+    /// # Success
+    ///
+    /// Will return the runs ordered from oldest to newest.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error, or if a run has a report type
+    /// or date that is not valid.
+    pub fn get_report_runs(&self) -> Result<Vec<ReportRun>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                id,
+                report_type,
+                period_start,
+                period_end,
+                recipients,
+                files,
+                status,
+                created_at,
+                scheduled_for,
+                message
+                FROM report_runs
+                ORDER BY id ASC",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([], |row| {
+                let report_type: String = row.get(1)?;
+                let start: String = row.get(2)?;
+                let end: String = row.get(3)?;
+                let recipients: String = row.get(4)?;
+                let files: String = row.get(5)?;
+                let status: String = row.get(6)?;
+                let created_at: String = row.get(7)?;
+                let scheduled_for: Option<String> = row.get(8)?;
+                let date = |column: usize, text: &str| {
+                    NaiveDate::parse_from_str(text, RUN_DATE_FORMAT).map_err(
+                        |_| {
+                            invalid_text(
+                                column,
+                                format!("invalid date: {text}"),
+                            )
+                        },
+                    )
+                };
+                let date_time = |column: usize, text: &str| {
+                    parse_run_date_time(text).ok_or_else(|| {
+                        invalid_text(column, format!("invalid date: {text}"))
+                    })
+                };
+                let split = |text: &str| -> Vec<String> {
+                    text.lines().map(|line| line.to_string()).collect()
+                };
+                Ok(ReportRun {
+                    id: row.get(0)?,
+                    report_type: ReportType::from_str(&report_type)
+                        .ok_or_else(|| {
+                            invalid_text(
+                                1,
+                                format!("invalid report type: {report_type}"),
+                            )
+                        })?,
+                    period: ReportPeriod {
+                        start: date(2, &start)?,
+                        end: date(3, &end)?,
+                    },
+                    recipients: split(&recipients),
+                    files: split(&files),
+                    success: status == "success",
+                    created_at: date_time(7, &created_at)?,
+                    scheduled_for: scheduled_for
+                        .map(|time| date_time(8, &time))
+                        .transpose()?,
+                    message: row.get(9)?,
+                })
+            })
+            .map_err(Error::Sql)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Error::Sql)
+    }
+
+    /// Gets the number of consultations and total fee for every provider
+    /// with activity during the period.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period of time to total.
+    ///
+    /// # Success
+    ///
+    /// Will return tuples of provider id, consultations and fees, ordered by
+    /// provider id.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if there is no activity during the period.
+    fn get_provider_totals(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<Vec<(u32, u32, f64)>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                service_date,
+                provider_id,
+                service_code
+            FROM consultations
+            ORDER BY consultations.provider_id ASC",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([], |row| {
+                let service_date: String = row.get(0)?;
+                let provider_id: u32 = row.get(1)?;
+                let service_code: u32 = row.get(2)?;
+                Ok((service_date, provider_id, service_code))
+            })
+            .map_err(Error::Sql)?;
+
+        let mut providers: Vec<(u32, u32, f64)> = Vec::new();
+        for (service_date, provider_id, service_code) in rows.flatten() {
+            if !period.contains_service_date(&service_date) {
+                continue;
+            }
+            let curr_fee = self.get_service_fee(service_code)?;
+            match providers.last_mut() {
+                Some(values) if values.0 == provider_id => {
+                    values.1 += 1;
+                    values.2 += curr_fee;
+                }
+                _ => providers.push((provider_id, 1, curr_fee)),
+            }
+        }
+        if providers.is_empty() {
+            return Err(Error::NoDataFound);
+        }
+        Ok(providers)
+    }
+
+    /// Sends out the provider directory to the specified provider.
+    ///
+    /// The directory is queued in the outbox first, so that it is retried
+    /// later if it could not be delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id - The id of the provider to send the report to.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if not sent.
+    pub fn send_provider_directory(
+        &self,
+        actor: &Actor,
+        id: u32,
+    ) -> Result<(), Error> {
+        authorize_for_provider(actor, Permission::SendDirectory, id)?;
+        let directory = self.build_provider_directory()?;
+        let email = self.provider_directory_email(&directory, id)?;
+        let id = self.queue_email(&email)?;
+        let mut summary = DeliverySummary::default();
+        self.deliver_queued(id, Local::now().naive_local(), &mut summary)?;
+        match summary.failed.pop() {
+            Some(failure) => {
+                Err(Error::Io(std::io::Error::other(failure.error)))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Previews the provider directory for a provider without sending it.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the provider the directory would be sent to.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if there are no services or the provider does not
+    /// exist.
+    pub fn preview_provider_directory(
+        &self,
+        id: u32,
+    ) -> Result<ReportPreview, Error> {
+        let directory = self.build_provider_directory()?;
+        Ok(ReportPreview {
+            report_type: ReportType::Directory,
+            emails: vec![self.provider_directory_email(&directory, id)?],
+            totals: vec![(
+                "Services".to_string(),
+                directory.services.len().to_string(),
+            )],
+        })
+    }
+
+    /// Creates the email of the provider directory for a provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory to email.
+    /// * `id` - The id of the provider to email.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the provider does not exist or the templates or
+    /// report format could not be read.
+    fn provider_directory_email(
+        &self,
+        directory: &ProviderDirectory,
+        id: u32,
+    ) -> Result<Email, Error> {
+        let templates = self.load_templates()?;
+        let provider = self.get_provider_info(id)?;
+        let format =
+            self.get_report_format(ReportType::Directory, &provider.email)?;
+        let bodies = email_bodies(
+            format,
+            &templates,
+            directory,
+            |renderer, directory| renderer.provider_directory(directory),
+        );
+        Ok(self.report_email(
+            EmailKind::ProviderDirectory,
+            &provider.email,
+            &provider.name,
+            &directory.subject(),
+            bodies,
+        ))
+    }
+
+    /// Builds the provider directory, ordered by service name.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if there are no services or the directory could not
+    /// be built.
+    pub fn build_provider_directory(&self) -> Result<ProviderDirectory, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                service_id,
+                name,
                 fee
             FROM provider_directory
             ORDER BY name ASC",
@@ -541,28 +1563,18 @@ impl DB {
             })
             .map_err(Error::Sql)?;
 
-        let mut total_services = 0;
-        let mut email_body: String = "".to_string();
+        let mut services = Vec::new();
         for (service_id, name, fee) in rows.flatten() {
-            total_services += 1;
-            email_body.push_str(&format!(
-                "{}, ID: {}, Fee: {}\n",
-                name, service_id, fee
-            ));
+            services.push(DirectoryService {
+                service_id,
+                name,
+                fee,
+            });
         }
-        if total_services == 0 {
+        if services.is_empty() {
             return Err(Error::NoDataFound);
         }
-        let provider = self.get_provider_info(id)?;
-        send_provider_directory(
-            &provider.email,
-            CHOCAN_EMAIL,
-            "Provider Directory",
-            &email_body,
-            &provider.name,
-        )
-        .map_err(Error::Io)?;
-        Ok(())
+        Ok(ProviderDirectory { services })
     }
 
     /// Checks if the member id belongs to a member in the database.
@@ -650,12 +1662,18 @@ impl DB {
     ///
     /// # Arguments
     ///
+    /// * `actor` - Who is making the change.
     /// * `person` - The member to add to the database.
     ///
     /// # Failure
     ///
     /// Will return `Err` if the member was not added.
-    pub fn add_member(&self, person: &PersonInfo) -> Result<(), Error> {
+    pub fn add_member(
+        &self,
+        actor: &Actor,
+        person: &PersonInfo,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditMembers)?;
         let mut stmt = self
             .conn
             .prepare(
@@ -689,12 +1707,18 @@ impl DB {
     ///
     /// # Arguments
     ///
+    /// * `actor` - Who is making the change.
     /// * `person` - The provider to add to the database.
     ///
     /// # Failure
     ///
     /// Will return `Err` if the provider was not added.
-    pub fn add_provider(&self, person: &PersonInfo) -> Result<(), Error> {
+    pub fn add_provider(
+        &self,
+        actor: &Actor,
+        person: &PersonInfo,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditProviders)?;
         let mut stmt = self
             .conn
             .prepare(
@@ -728,12 +1752,14 @@ impl DB {
     ///
     /// # Arguments
     ///
+    /// * `actor` - Who is making the change.
     /// * `id` - The id of the member to remove.
     ///
     /// # Failure
     ///
     /// Will return `Err` if the member was not removed.
-    pub fn remove_member(&self, id: u32) -> Result<(), Error> {
+    pub fn remove_member(&self, actor: &Actor, id: u32) -> Result<(), Error> {
+        authorize(actor, Permission::EditMembers)?;
         let mut stmt = self
             .conn
             .prepare("DELETE FROM members WHERE id = ?")
@@ -745,40 +1771,279 @@ impl DB {
         Ok(())
     }
 
-    /// Removes a provider from the database.
+    /// Suspends a member so they are no longer valid, such as when their
+    /// fees are unpaid. The member and their records are kept.
     ///
     /// # Arguments
     ///
-    /// * `id` - The id of the provider to remove.
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the member to suspend.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if the provider was not removed.
-    pub fn remove_provider(&self, id: u32) -> Result<(), Error> {
-        let mut stmt = self
+    /// Will return `Err` if the member does not exist.
+    pub fn suspend_member(&self, actor: &Actor, id: u32) -> Result<(), Error> {
+        authorize(actor, Permission::EditMembers)?;
+        self.set_member_valid(id, false)
+    }
+
+    /// Reinstates a suspended member so they are valid again.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the member to reinstate.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the member does not exist.
+    pub fn reinstate_member(
+        &self,
+        actor: &Actor,
+        id: u32,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditMembers)?;
+        self.set_member_valid(id, true)
+    }
+
+    fn set_member_valid(&self, id: u32, valid: bool) -> Result<(), Error> {
+        let n = self
             .conn
-            .prepare("DELETE FROM providers WHERE id = ?")
+            .execute(
+                "UPDATE members SET is_valid = ?1 WHERE id = ?2",
+                rusqlite::params![valid, id],
+            )
             .map_err(Error::Sql)?;
-        let n = stmt.execute(rusqlite::params![id]).map_err(Error::Sql)?;
         if n == 0 {
             return Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows));
         }
         Ok(())
     }
 
-    /// Adds a consultation record to the database.
+    /// Removes a provider from the database.
     ///
     /// # Arguments
     ///
-    /// * `consul` - The consultation to add to the database.
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the provider to remove.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if the record was not added.
-    pub fn add_consultation_record(
-        &self,
-        consul: &Consultation,
+    /// Will return `Err` if the provider was not removed.
+    pub fn remove_provider(&self, actor: &Actor, id: u32) -> Result<(), Error> {
+        authorize(actor, Permission::EditProviders)?;
+        let mut stmt = self
+            .conn
+            .prepare("DELETE FROM providers WHERE id = ?")
+            .map_err(Error::Sql)?;
+        let n = stmt.execute(rusqlite::params![id]).map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows));
+        }
+        self.conn
+            .execute("DELETE FROM provider_pins WHERE provider_id = ?", [id])
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Writes a consistent copy of the whole database to a new file, which
+    /// can be opened like any other database.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `path` - The path of the backup, which must not already exist.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the file already exists or the backup was not
+    /// written.
+    pub fn backup(&self, actor: &Actor, path: &Path) -> Result<(), Error> {
+        authorize(actor, Permission::Backup)?;
+        if path.exists() {
+            return Err(Error::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            )));
+        }
+        self.conn
+            .execute("VACUUM INTO ?1", [path.to_string_lossy()])
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Changes the details of a member, keeping their id.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `person` - The member with their new details.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the member does not exist or was not changed.
+    pub fn update_member(
+        &self,
+        actor: &Actor,
+        person: &PersonInfo,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditMembers)?;
+        self.update_person("members", person)
+    }
+
+    /// Changes the details of a provider, keeping their id.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `person` - The provider with their new details.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the provider does not exist or was not changed.
+    pub fn update_provider(
+        &self,
+        actor: &Actor,
+        person: &PersonInfo,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditProviders)?;
+        self.update_person("providers", person)
+    }
+
+    fn update_person(
+        &self,
+        table: &str,
+        person: &PersonInfo,
+    ) -> Result<(), Error> {
+        let n = self
+            .conn
+            .execute(
+                &format!(
+                    "UPDATE {} SET
+                    name = ?1,
+                    address = ?2,
+                    city = ?3,
+                    state = ?4,
+                    zipcode = ?5,
+                    email = ?6
+                    WHERE id = ?7",
+                    table
+                ),
+                rusqlite::params![
+                    &person.name,
+                    &person.location.address,
+                    &person.location.city,
+                    &person.location.state,
+                    &person.location.zipcode,
+                    &person.email,
+                    &person.id,
+                ],
+            )
+            .map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+
+    /// Checks whether the database has no members, providers, services or
+    /// consultations, such as before it is populated with sample data.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error.
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        let count: u32 = self
+            .conn
+            .query_row(
+                "SELECT (SELECT count(*) FROM members)
+                + (SELECT count(*) FROM providers)
+                + (SELECT count(*) FROM provider_directory)
+                + (SELECT count(*) FROM consultations)",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(Error::Sql)?;
+        Ok(count == 0)
+    }
+
+    /// Gets every member, ordered by name.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the members could not be retrieved.
+    pub fn get_members(&self) -> Result<Vec<PersonInfo>, Error> {
+        self.get_people("members")
+    }
+
+    /// Gets every provider, ordered by name.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the providers could not be retrieved.
+    pub fn get_providers(&self) -> Result<Vec<PersonInfo>, Error> {
+        self.get_people("providers")
+    }
+
+    fn get_people(&self, table: &str) -> Result<Vec<PersonInfo>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT
+                id,
+                name,
+                address,
+                city,
+                state,
+                zipcode,
+                email
+                FROM {} ORDER BY name ASC, id ASC",
+                table
+            ))
+            .map_err(Error::Sql)?;
+        let people = stmt
+            .query_map([], |row| {
+                let id: u32 = row.get(0)?;
+                let name: String = row.get(1)?;
+                let address: String = row.get(2)?;
+                let city: String = row.get(3)?;
+                let state: String = row.get(4)?;
+                let zipcode: u32 = row.get(5)?;
+                let email: String = row.get(6)?;
+                LocationInfo::new(&address, &city, &state, zipcode)
+                    .and_then(|location| {
+                        PersonInfo::new(&name, id, &location, &email)
+                    })
+                    .map_err(|err| {
+                        invalid_text(0, format!("invalid person {id}: {err}"))
+                    })
+            })
+            .map_err(Error::Sql)?
+            .collect::<Result<Vec<PersonInfo>, _>>()
+            .map_err(Error::Sql)?;
+        Ok(people)
+    }
+
+    /// Adds a consultation record to the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `consul` - The consultation to add to the database.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the record was not added.
+    pub fn add_consultation_record(
+        &self,
+        actor: &Actor,
+        consul: &Consultation,
     ) -> Result<(), Error> {
+        authorize_for_provider(
+            actor,
+            Permission::RecordConsultations,
+            consul.provider_id,
+        )?;
         let mut stmt = self
             .conn
             .prepare(
@@ -804,182 +2069,2195 @@ impl DB {
         Ok(())
     }
 
-    /// Adds a service to the provider directory.
+    /// Adds a service to the provider directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the service.
+    /// * `name` - The name of the service.
+    /// * `fee` - The fee for the service.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the service was not added.
+    pub fn add_service(
+        &self,
+        actor: &Actor,
+        id: u32,
+        name: &str,
+        fee: f64,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditServices)?;
+        if name.chars().count() == 0 {
+            return Err(Error::EmptyInput);
+        }
+        let mut stmt = self
+            .conn
+            .prepare(
+                "INSERT INTO provider_directory (
+                service_id,
+                name,
+                fee
+            ) VALUES (?1, ?2, ?3)",
+            )
+            .map_err(Error::Sql)?;
+        stmt.execute(rusqlite::params![id, name, fee])
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Changes the name and fee of a service in the provider directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the service.
+    /// * `name` - The new name of the service.
+    /// * `fee` - The new fee for the service.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the service does not exist or was not changed.
+    pub fn update_service(
+        &self,
+        actor: &Actor,
+        id: u32,
+        name: &str,
+        fee: f64,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditServices)?;
+        if name.chars().count() == 0 {
+            return Err(Error::EmptyInput);
+        }
+        let n = self
+            .conn
+            .execute(
+                "UPDATE provider_directory SET name = ?1, fee = ?2
+                WHERE service_id = ?3",
+                rusqlite::params![name, fee, id],
+            )
+            .map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+
+    /// Removes a service from the provider directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the service to remove.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the service was not removed.
+    pub fn remove_service(&self, actor: &Actor, id: u32) -> Result<(), Error> {
+        authorize(actor, Permission::EditServices)?;
+        let n = self
+            .conn
+            .execute(
+                "DELETE FROM provider_directory WHERE service_id = ?",
+                [id],
+            )
+            .map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+
+    /// Gets the most recently recorded consultations, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The most consultations to get.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the consultations could not be retrieved.
+    pub fn get_recent_consultations(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<Consultation>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                current_date_time,
+                service_date,
+                provider_id,
+                member_id,
+                service_code,
+                comments
+                FROM consultations ORDER BY rowid DESC LIMIT ?",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([limit], |row| {
+                Ok(Consultation {
+                    curr_date: row.get(0)?,
+                    service_date: row.get(1)?,
+                    provider_id: row.get(2)?,
+                    member_id: row.get(3)?,
+                    service_code: row.get(4)?,
+                    comments: row
+                        .get::<_, Option<String>>(5)?
+                        .unwrap_or_default(),
+                })
+            })
+            .map_err(Error::Sql)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Error::Sql)
+    }
+
+    /// Gets the name corresponding to the specified service code id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the service to get the name of.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the name could not be retrieved.
+    pub fn get_service_name(&self, id: u32) -> Result<String, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM provider_directory WHERE service_id = ?")
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([id], |row| {
+                let name: String = row.get(0)?;
+                Ok(name)
+            })
+            .map_err(Error::Sql)?;
+        if let Some(name) = rows.flatten().next() {
+            return Ok(name);
+        }
+        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    /// Sets the bank account that a provider is paid to.
+    ///
+    /// Replaces any bank account the provider already had.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the provider.
+    /// * `bank` - The bank account of the provider.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the provider does not exist or the bank account
+    /// was not set.
+    pub fn set_provider_bank_info(
+        &self,
+        actor: &Actor,
+        id: u32,
+        bank: &BankInfo,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditProviders)?;
+        self.get_provider_info(id)?;
+        let mut stmt = self
+            .conn
+            .prepare(
+                "INSERT OR REPLACE INTO provider_bank_accounts (
+                provider_id,
+                routing_number,
+                account_number,
+                account_type
+            ) VALUES (?1, ?2, ?3, ?4)",
+            )
+            .map_err(Error::Sql)?;
+        stmt.execute(rusqlite::params![
+            id,
+            &bank.routing_number,
+            &bank.account_number,
+            bank.account_type.as_str(),
+        ])
+        .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Gets the bank account that a provider is paid to.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the provider.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the bank account could not be retrieved.
+    pub fn get_provider_bank_info(&self, id: u32) -> Result<BankInfo, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                routing_number,
+                account_number,
+                account_type
+                FROM provider_bank_accounts WHERE provider_id = ?",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([id], |row| {
+                let routing_number: String = row.get(0)?;
+                let account_number: String = row.get(1)?;
+                let account_type: String = row.get(2)?;
+                Ok((routing_number, account_number, account_type))
+            })
+            .map_err(Error::Sql)?;
+        if let Some((routing, account, account_type)) = rows.flatten().next() {
+            let account_type = if account_type == "savings" {
+                AccountType::Savings
+            } else {
+                AccountType::Checking
+            };
+            return Ok(BankInfo {
+                routing_number: routing,
+                account_number: account,
+                account_type,
+            });
+        }
+        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    /// Sends an email through the transport of the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - The email to send.
+    ///
+    /// # Success
+    ///
+    /// Will return where the email was delivered.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the email was not delivered.
+    fn send_email(&self, email: &Email) -> Result<String, Error> {
+        self.transport.send(email).map_err(Error::Io)
+    }
+
+    /// Adds an email to the outbox to be delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - The email to queue.
+    ///
+    /// # Success
+    ///
+    /// Will return the id of the email in the outbox.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the email was not queued.
+    fn queue_email(&self, email: &Email) -> Result<u32, Error> {
+        let now = Local::now()
+            .naive_local()
+            .format(RUN_DATE_TIME_FORMAT)
+            .to_string();
+        self.conn
+            .execute(
+                "INSERT INTO outbox (
+                kind,
+                recipient,
+                recipient_name,
+                sender,
+                subject,
+                body,
+                html,
+                status,
+                attempts,
+                next_attempt_at,
+                created_at,
+                updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?9, ?9)",
+                rusqlite::params![
+                    email.kind.as_str(),
+                    email.to,
+                    email.recipient_name,
+                    email.from,
+                    email.subject,
+                    email.body,
+                    email.html,
+                    OutboxStatus::Queued.as_str(),
+                    now,
+                ],
+            )
+            .map_err(Error::Sql)?;
+        let id = u32::try_from(self.conn.last_insert_rowid())
+            .map_err(|err| Error::Io(std::io::Error::other(err)))?;
+        for (position, attachment) in email.attachments.iter().enumerate() {
+            self.conn
+                .execute(
+                    "INSERT INTO outbox_attachments (
+                    outbox_id,
+                    position,
+                    file_name,
+                    content_type,
+                    data
+                ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![
+                        id,
+                        position,
+                        attachment.file_name,
+                        attachment.content_type,
+                        attachment.data,
+                    ],
+                )
+                .map_err(Error::Sql)?;
+        }
+        Ok(id)
+    }
+
+    /// Adds emails to the outbox to be delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `emails` - The emails to queue.
+    ///
+    /// # Success
+    ///
+    /// Will return the ids of the emails in the outbox.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if an email was not queued.
+    fn queue_emails(&self, emails: &[Email]) -> Result<Vec<u32>, Error> {
+        emails.iter().map(|email| self.queue_email(email)).collect()
+    }
+
+    /// Makes one attempt to deliver a queued email and records the result.
+    ///
+    /// An email that could not be delivered is retried later, waiting twice
+    /// as long after each attempt, until it has been tried
+    /// `MAX_DELIVERY_ATTEMPTS` times and is marked as failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the email in the outbox.
+    /// * `now` - The current time.
+    /// * `summary` - Where the delivery or failure is added.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the email could not be read or its status could
+    /// not be recorded.
+    fn deliver_queued(
+        &self,
+        id: u32,
+        now: NaiveDateTime,
+        summary: &mut DeliverySummary,
+    ) -> Result<(), Error> {
+        let message = self.get_outbox_message(id)?;
+        let attempts = message.attempts + 1;
+        let (status, next_attempt_at, delivered_to, last_error) =
+            match self.send_email(&message.email) {
+                Ok(file) => {
+                    summary.recipients.push(message.email.to.clone());
+                    summary.files.push(file.clone());
+                    (OutboxStatus::Sent, now, Some(file), None)
+                }
+                Err(err) => {
+                    let error = err.to_string();
+                    summary.failed.push(DeliveryFailure {
+                        recipient: message.email.to.clone(),
+                        error: error.clone(),
+                    });
+                    if attempts >= MAX_DELIVERY_ATTEMPTS {
+                        (OutboxStatus::Failed, now, None, Some(error))
+                    } else {
+                        let retry_at = now + retry_delay(attempts);
+                        (OutboxStatus::Queued, retry_at, None, Some(error))
+                    }
+                }
+            };
+        self.conn
+            .execute(
+                "UPDATE outbox SET
+                status = ?2,
+                attempts = ?3,
+                next_attempt_at = ?4,
+                delivered_to = ?5,
+                last_error = ?6,
+                updated_at = ?7
+                WHERE id = ?1",
+                rusqlite::params![
+                    id,
+                    status.as_str(),
+                    attempts,
+                    next_attempt_at.format(RUN_DATE_TIME_FORMAT).to_string(),
+                    delivered_to,
+                    last_error,
+                    now.format(RUN_DATE_TIME_FORMAT).to_string(),
+                ],
+            )
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Delivers every queued email in the outbox that is due to be tried.
+    ///
+    /// This is the delivery worker, and is run periodically to retry emails
+    /// that could not be delivered when they were first sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `now` - The current time.
+    ///
+    /// # Success
+    ///
+    /// Will return the emails that were delivered and those that failed
+    /// again.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the outbox could not be read or updated.
+    pub fn deliver_outbox(
+        &self,
+        actor: &Actor,
+        now: NaiveDateTime,
+    ) -> Result<DeliverySummary, Error> {
+        authorize(actor, Permission::SendReports)?;
+        let due = self.query_outbox(
+            "WHERE status = ?1 AND next_attempt_at <= ?2",
+            rusqlite::params![
+                OutboxStatus::Queued.as_str(),
+                now.format(RUN_DATE_TIME_FORMAT).to_string(),
+            ],
+        )?;
+        let mut summary = DeliverySummary::default();
+        for message in due {
+            self.deliver_queued(message.id, now, &mut summary)?;
+        }
+        Ok(summary)
+    }
+
+    /// Tries failed emails, and queued emails waiting for a retry, again
+    /// right away.
+    ///
+    /// Each email that is tried again gets a fresh set of attempts.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the email to send again, or `None` to send every
+    ///   email that has not been sent again.
+    ///
+    /// # Success
+    ///
+    /// Will return the emails that were delivered and those that failed
+    /// again.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if there are no matching unsent emails or the
+    /// outbox could not be updated.
+    pub fn resend_failed(
+        &self,
+        actor: &Actor,
+        id: Option<u32>,
+    ) -> Result<DeliverySummary, Error> {
+        authorize(actor, Permission::SendReports)?;
+        let ids: Vec<u32> = self
+            .get_outbox(None)?
+            .into_iter()
+            .filter(|message| message.status != OutboxStatus::Sent)
+            .map(|message| message.id)
+            .filter(|unsent| id.is_none_or(|id| id == *unsent))
+            .collect();
+        if ids.is_empty() {
+            return Err(Error::NoDataFound);
+        }
+        let now = Local::now().naive_local();
+        let mut summary = DeliverySummary::default();
+        for id in ids {
+            self.conn
+                .execute(
+                    "UPDATE outbox SET
+                    status = ?2,
+                    attempts = 0,
+                    next_attempt_at = ?3
+                    WHERE id = ?1",
+                    rusqlite::params![
+                        id,
+                        OutboxStatus::Queued.as_str(),
+                        now.format(RUN_DATE_TIME_FORMAT).to_string(),
+                    ],
+                )
+                .map_err(Error::Sql)?;
+            self.deliver_queued(id, now, &mut summary)?;
+        }
+        Ok(summary)
+    }
+
+    /// Gets the emails in the outbox.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - Only get emails with this status, or `None` for every
+    ///   email.
+    ///
+    /// # Success
+    ///
+    /// Will return the emails ordered from oldest to newest.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error.
+    pub fn get_outbox(
+        &self,
+        status: Option<OutboxStatus>,
+    ) -> Result<Vec<OutboxMessage>, Error> {
+        match status {
+            Some(status) => self.query_outbox(
+                "WHERE status = ?1",
+                rusqlite::params![status.as_str()],
+            ),
+            None => self.query_outbox("", []),
+        }
+    }
+
+    fn get_outbox_message(&self, id: u32) -> Result<OutboxMessage, Error> {
+        self.query_outbox("WHERE id = ?1", rusqlite::params![id])?
+            .pop()
+            .ok_or(Error::NoDataFound)
+    }
+
+    /// Reads emails from the outbox along with their attachments.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The `WHERE` clause choosing the emails, if any.
+    /// * `params` - The parameters of the `WHERE` clause.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error, or if an email in the outbox
+    /// could not be read.
+    fn query_outbox<P: rusqlite::Params>(
+        &self,
+        filter: &str,
+        params: P,
+    ) -> Result<Vec<OutboxMessage>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT
+                id,
+                kind,
+                recipient,
+                recipient_name,
+                sender,
+                subject,
+                body,
+                html,
+                status,
+                attempts,
+                last_error,
+                next_attempt_at,
+                delivered_to,
+                created_at
+                FROM outbox
+                {}
+                ORDER BY id",
+                filter
+            ))
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map(params, |row| {
+                let kind: String = row.get(1)?;
+                let status: String = row.get(8)?;
+                let next_attempt_at: String = row.get(11)?;
+                let created_at: String = row.get(13)?;
+                let email = Email {
+                    to: row.get(2)?,
+                    from: row.get(4)?,
+                    subject: row.get(5)?,
+                    body: row.get(6)?,
+                    html: row.get(7)?,
+                    attachments: Vec::new(),
+                    recipient_name: row.get(3)?,
+                    kind: EmailKind::parse(&kind).ok_or_else(|| {
+                        invalid_text(1, format!("unknown email kind: {kind}"))
+                    })?,
+                };
+                Ok(OutboxMessage {
+                    id: row.get(0)?,
+                    email,
+                    status: OutboxStatus::from_str(&status).ok_or_else(
+                        || invalid_text(8, format!("unknown status: {status}")),
+                    )?,
+                    attempts: row.get(9)?,
+                    last_error: row.get(10)?,
+                    next_attempt_at: NaiveDateTime::parse_from_str(
+                        &next_attempt_at,
+                        RUN_DATE_TIME_FORMAT,
+                    )
+                    .map_err(|err| invalid_text(11, err))?,
+                    delivered_to: row.get(12)?,
+                    created_at: NaiveDateTime::parse_from_str(
+                        &created_at,
+                        RUN_DATE_TIME_FORMAT,
+                    )
+                    .map_err(|err| invalid_text(13, err))?,
+                })
+            })
+            .map_err(Error::Sql)?;
+
+        let mut messages: Vec<OutboxMessage> =
+            rows.collect::<Result<_, _>>().map_err(Error::Sql)?;
+        for message in &mut messages {
+            message.email.attachments =
+                self.get_outbox_attachments(message.id)?;
+        }
+        Ok(messages)
+    }
+
+    fn get_outbox_attachments(
+        &self,
+        id: u32,
+    ) -> Result<Vec<Attachment>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT file_name, content_type, data
+                FROM outbox_attachments
+                WHERE outbox_id = ?1
+                ORDER BY position",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([id], |row| {
+                Ok(Attachment {
+                    file_name: row.get(0)?,
+                    content_type: row.get(1)?,
+                    data: row.get(2)?,
+                })
+            })
+            .map_err(Error::Sql)?;
+        rows.collect::<Result<_, _>>().map_err(Error::Sql)
+    }
+
+    /// Creates an email from ChocAn for a report.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the email is for.
+    /// * `to` - The email address of the recipient.
+    /// * `recipient_name` - The name of the recipient.
+    /// * `subject` - The subject line of the email.
+    /// * `bodies` - The plain text body and the HTML body, if any.
+    fn report_email(
+        &self,
+        kind: EmailKind,
+        to: &str,
+        recipient_name: &str,
+        subject: &str,
+        bodies: (String, Option<String>),
+    ) -> Email {
+        let (body, html) = bodies;
+        let mut email = Email::new(
+            kind,
+            to,
+            &self.config.sender,
+            subject,
+            &body,
+            recipient_name,
+        );
+        email.html = html;
+        email
+    }
+
+    /// Loads the plain text report templates.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a template could not be read or is invalid.
+    fn load_templates(&self) -> Result<Templates, Error> {
+        Templates::load(&self.config.templates_path).map_err(Error::Template)
+    }
+
+    /// Sets the format a type of report is rendered in.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `report_type` - The type of report.
+    /// * `recipient` - The email of the recipient the format is for, or
+    ///   `None` to set the format for every recipient without their own.
+    /// * `format` - The format to render the report in.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the format was not set.
+    pub fn set_report_format(
+        &self,
+        actor: &Actor,
+        report_type: ReportType,
+        recipient: Option<&str>,
+        format: Format,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::SendReports)?;
+        if let Some("") = recipient {
+            return Err(Error::EmptyInput);
+        }
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO report_formats (
+                report_type,
+                recipient,
+                format
+            ) VALUES (?1, ?2, ?3)",
+                rusqlite::params![
+                    report_type.as_str(),
+                    recipient.unwrap_or(""),
+                    format.as_str(),
+                ],
+            )
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Gets the format a type of report is rendered in for a recipient.
+    ///
+    /// The format set for the recipient is used first, then the format set
+    /// for the report type, and plain text otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `report_type` - The type of report.
+    /// * `recipient` - The email of the recipient.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the format could not be retrieved.
+    pub fn get_report_format(
+        &self,
+        report_type: ReportType,
+        recipient: &str,
+    ) -> Result<Format, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT format FROM report_formats
+                WHERE report_type = ?1 AND recipient IN (?2, '')
+                ORDER BY recipient DESC",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![report_type.as_str(), recipient],
+                |row| {
+                    let format: String = row.get(0)?;
+                    Ok(format)
+                },
+            )
+            .map_err(Error::Sql)?;
+        if let Some(format) = rows.flatten().next() {
+            return Ok(Format::parse(&format).unwrap_or_default());
+        }
+        Ok(Format::default())
+    }
+
+    /// Sets whether a type of report email carries a CSV attachment of its
+    /// consultation lines and totals.
+    ///
+    /// Only provider and manager report emails carry attachments.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `report_type` - The type of report.
+    /// * `recipient` - The email of the recipient the setting is for, or
+    ///   `None` to set it for every recipient without their own.
+    /// * `attach` - Whether to attach the CSV.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the setting was not stored.
+    pub fn set_csv_attachment(
+        &self,
+        actor: &Actor,
+        report_type: ReportType,
+        recipient: Option<&str>,
+        attach: bool,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::SendReports)?;
+        if let Some("") = recipient {
+            return Err(Error::EmptyInput);
+        }
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO report_csv_attachments (
+                report_type,
+                recipient,
+                attach
+            ) VALUES (?1, ?2, ?3)",
+                rusqlite::params![
+                    report_type.as_str(),
+                    recipient.unwrap_or(""),
+                    attach,
+                ],
+            )
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Gets whether a type of report email carries a CSV attachment for a
+    /// recipient.
+    ///
+    /// The setting for the recipient is used first, then the setting for the
+    /// report type, and no attachment otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `report_type` - The type of report.
+    /// * `recipient` - The email of the recipient.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the setting could not be retrieved.
+    pub fn get_csv_attachment(
+        &self,
+        report_type: ReportType,
+        recipient: &str,
+    ) -> Result<bool, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT attach FROM report_csv_attachments
+                WHERE report_type = ?1 AND recipient IN (?2, '')
+                ORDER BY recipient DESC",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![report_type.as_str(), recipient],
+                |row| {
+                    let attach: bool = row.get(0)?;
+                    Ok(attach)
+                },
+            )
+            .map_err(Error::Sql)?;
+        Ok(rows.flatten().next().unwrap_or(false))
+    }
+
+    /// Gets the fee corresponding to the specified service code id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the service to get the fee of.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the fee could not be retrieved.
+    pub fn get_service_fee(&self, id: u32) -> Result<f64, Error> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT fee FROM provider_directory WHERE service_id = ?")
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([id], |row| {
+                let fee: f64 = row.get(0)?;
+                Ok(fee)
+            })
+            .map_err(Error::Sql)?;
+        if let Some(fee) = rows.flatten().next() {
+            return Ok(fee);
+        }
+        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    /// Gets the data for a member in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the member to get the data for.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the data could not be retrieved.
+    pub fn get_member_info(&self, id: u32) -> Result<PersonInfo, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                name,
+                address,
+                city,
+                state,
+                zipcode,
+                email
+                FROM members WHERE id = ?",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([id], |row| {
+                let name: String = row.get(0)?;
+                let address: String = row.get(1)?;
+                let city: String = row.get(2)?;
+                let state: String = row.get(3)?;
+                let zipcode: u32 = row.get(4)?;
+                let email: String = row.get(5)?;
+                let location =
+                    LocationInfo::new(&address, &city, &state, zipcode)
+                        .unwrap();
+                Ok(PersonInfo::new(&name, id, &location, &email).unwrap())
+            })
+            .map_err(Error::Sql)?;
+        if let Some(person) = rows.flatten().next() {
+            return Ok(person);
+        }
+        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    /// Gets the data for a provider in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the provider to get the data for.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the data could not be retrieved.
+    pub fn get_provider_info(&self, id: u32) -> Result<PersonInfo, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                name,
+                address,
+                city,
+                state,
+                zipcode,
+                email
+                FROM providers WHERE id = ?",
+            )
+            .map_err(Error::Sql)?;
+        let rows = stmt
+            .query_map([id], |row| {
+                let name: String = row.get(0)?;
+                let address: String = row.get(1)?;
+                let city: String = row.get(2)?;
+                let state: String = row.get(3)?;
+                let zipcode: u32 = row.get(4)?;
+                let email: String = row.get(5)?;
+                let location =
+                    LocationInfo::new(&address, &city, &state, zipcode)
+                        .unwrap();
+                Ok(PersonInfo::new(&name, id, &location, &email).unwrap())
+            })
+            .map_err(Error::Sql)?;
+        if let Some(person) = rows.flatten().next() {
+            return Ok(person);
+        }
+        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    /// Records a run of the scheduled accounting procedure.
+    ///
+    /// Every attempt at a period is kept, so a retry of a failed run is
+    /// recorded as another run.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `run` - The run to record, of the `Accounting` report type.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the run was not recorded.
+    pub fn add_scheduled_run(
+        &self,
+        actor: &Actor,
+        run: &ReportRun,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::SendReports)?;
+        self.add_report_run(run)?;
+        Ok(())
+    }
+
+    /// Gets every recorded run of the scheduled accounting procedure.
+    ///
+    /// # Success
+    ///
+    /// Will return the runs ordered by when they were scheduled for, with
+    /// the attempts at a period from oldest to newest.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error.
+    pub fn get_scheduled_runs(&self) -> Result<Vec<ReportRun>, Error> {
+        let mut runs: Vec<ReportRun> = self
+            .get_report_runs()?
+            .into_iter()
+            .filter(|run| run.report_type == ReportType::Accounting)
+            .collect();
+        runs.sort_by_key(|run| run.scheduled_for);
+        Ok(runs)
+    }
+
+    /// Checks if the scheduled accounting procedure has already run
+    /// successfully for a period.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The period to check.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error.
+    pub fn is_period_scheduled_run(
+        &self,
+        period: &ReportPeriod,
+    ) -> Result<bool, Error> {
+        self.is_report_sent(ReportType::Accounting, period)
+    }
+
+    /// Sets the PIN a provider logs in with, which also unlocks them.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the provider.
+    /// * `pin` - The new PIN, which is stored only as a salted hash.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the PIN is not valid, the provider does not exist
+    /// or the PIN was not set.
+    pub fn set_provider_pin(
+        &self,
+        actor: &Actor,
+        id: u32,
+        pin: &str,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditProviders)?;
+        auth::check_pin(pin).map_err(Error::InvalidCredential)?;
+        self.get_provider_info(id)?;
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO provider_pins (
+                provider_id,
+                secret_hash,
+                failed_logins,
+                locked_until
+            ) VALUES (?1, ?2, 0, NULL)",
+                rusqlite::params![id, auth::hash_secret(pin)],
+            )
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Checks whether a provider has a PIN to log in with.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the provider.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error.
+    pub fn has_provider_pin(&self, id: u32) -> Result<bool, Error> {
+        self.conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM provider_pins
+                WHERE provider_id = ?)",
+                [id],
+                |row| row.get(0),
+            )
+            .map_err(Error::Sql)
+    }
+
+    /// Checks the PIN of a provider who is logging in.
+    ///
+    /// After too many wrong PINs in a row the provider is locked out for a
+    /// while, even if the right PIN is then entered.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the provider.
+    /// * `pin` - The PIN that was entered.
+    ///
+    /// # Success
+    ///
+    /// Will return the actor to make changes as, for this provider only.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` with `NoDataFound` if the provider has no PIN,
+    /// `LoginFailed` if the PIN is wrong, or `LockedOut` if the provider is
+    /// locked out.
+    pub fn login_provider(&self, id: u32, pin: &str) -> Result<Actor, Error> {
+        let pending = self.begin_provider_login(id)?;
+        let verified = pending.verify(pin);
+        self.finish_login(pending, verified)
+    }
+
+    /// Starts the login of a provider by getting the hash of their PIN, so
+    /// the PIN can be checked with [`PendingLogin::verify`] while nothing
+    /// else waits on the database. The login is then finished with
+    /// [`DB::finish_login`].
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the provider.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` with `LockedOut` if the provider is locked out.
+    pub fn begin_provider_login(&self, id: u32) -> Result<PendingLogin, Error> {
+        self.begin_login(LoginKey::Provider(id))
+    }
+
+    /// Finishes a login started with [`DB::begin_provider_login`], counting
+    /// a failed login and locking out after too many.
+    ///
+    /// # Arguments
+    ///
+    /// * `pending` - The login that was started.
+    /// * `verified` - Whether [`PendingLogin::verify`] accepted the secret.
+    ///
+    /// # Success
+    ///
+    /// Will return the actor to make changes as.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` with `NoDataFound` if there is no PIN or password
+    /// to log in with, `LoginFailed` if it was wrong, or `LockedOut` if the
+    /// account is locked out.
+    pub fn finish_login(
+        &self,
+        pending: PendingLogin,
+        verified: bool,
+    ) -> Result<Actor, Error> {
+        if pending.hash.is_none() {
+            return Err(Error::NoDataFound);
+        }
+        let (table, key_column) = pending.key.table();
+        let key = pending.key.value();
+        let (failed_logins, locked_until) = self.login_state(&pending.key)?;
+        // Another login may have locked the account out meanwhile.
+        let now = Local::now().naive_local();
+        if let Some(until) = locked_until
+            && until > now
+        {
+            return Err(Error::LockedOut(until));
+        }
+        let (failed_logins, locked_until) = if verified {
+            (0, None)
+        } else if failed_logins + 1 >= MAX_FAILED_LOGINS {
+            (0, Some(now + Duration::minutes(LOCKOUT_MINUTES)))
+        } else {
+            (failed_logins + 1, None)
+        };
+        self.conn
+            .execute(
+                &format!(
+                    "UPDATE {} SET failed_logins = ?1, locked_until = ?2
+                    WHERE {} = ?3",
+                    table, key_column
+                ),
+                rusqlite::params![
+                    failed_logins,
+                    locked_until.map(|until| until
+                        .format(RUN_DATE_TIME_FORMAT)
+                        .to_string()),
+                    key,
+                ],
+            )
+            .map_err(Error::Sql)?;
+        match locked_until {
+            Some(until) => return Err(Error::LockedOut(until)),
+            None if failed_logins > 0 => return Err(Error::LoginFailed),
+            None => (),
+        }
+        match pending.key {
+            LoginKey::Provider(id) => {
+                Ok(Actor::new(&id.to_string(), Role::Provider(id)))
+            }
+            LoginKey::Operator(username) => {
+                let role: String = self
+                    .conn
+                    .query_row(
+                        "SELECT role FROM operators WHERE username = ?",
+                        [&username],
+                        |row| row.get(0),
+                    )
+                    .map_err(Error::Sql)?;
+                let role =
+                    Role::parse(&role).map_err(Error::InvalidCredential)?;
+                Ok(Actor::new(&username, role))
+            }
+        }
+    }
+
+    /// Unlocks a provider who was locked out by failed logins.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `id` - The id of the provider.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the provider has no PIN.
+    pub fn unlock_provider(&self, actor: &Actor, id: u32) -> Result<(), Error> {
+        authorize(actor, Permission::EditProviders)?;
+        self.unlock("provider_pins", "provider_id", &id)
+    }
+
+    /// Adds an operator account that can log in to the manager terminal.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `username` - The name the operator logs in with.
+    /// * `password` - The password, which is stored only as a salted hash.
+    /// * `role` - What the operator may do, which must be an operator role.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the username, password or role is not valid, or
+    /// the account already exists.
+    pub fn add_operator(
+        &self,
+        actor: &Actor,
+        username: &str,
+        password: &str,
+        role: Role,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditOperators)?;
+        auth::check_username(username).map_err(Error::InvalidCredential)?;
+        auth::check_password(password).map_err(Error::InvalidCredential)?;
+        Role::parse(role.as_str()).map_err(Error::InvalidCredential)?;
+        self.conn
+            .execute(
+                "INSERT INTO operators (
+                username,
+                secret_hash,
+                role,
+                failed_logins,
+                locked_until,
+                created_at
+            ) VALUES (?1, ?2, ?3, 0, NULL, ?4)",
+                rusqlite::params![
+                    username,
+                    auth::hash_secret(password),
+                    role.as_str(),
+                    Local::now().format(RUN_DATE_TIME_FORMAT).to_string(),
+                ],
+            )
+            .map_err(Error::Sql)?;
+        Ok(())
+    }
+
+    /// Adds the first operator account, a manager, to a database that has
+    /// none. This is how a new database gets an operator who can then add
+    /// the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The name the operator logs in with.
+    /// * `password` - The password, which is stored only as a salted hash.
+    ///
+    /// # Success
+    ///
+    /// Will return the actor to make changes as, with the manager role.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` with `Unauthorized` if an operator account already
+    /// exists, or `InvalidCredential` if the username or password is not
+    /// valid.
+    pub fn add_first_operator(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<Actor, Error> {
+        auth::check_username(username).map_err(Error::InvalidCredential)?;
+        auth::check_password(password).map_err(Error::InvalidCredential)?;
+        let n = self
+            .conn
+            .execute(
+                "INSERT INTO operators (
+                username,
+                secret_hash,
+                role,
+                failed_logins,
+                locked_until,
+                created_at
+            ) SELECT ?1, ?2, ?3, 0, NULL, ?4
+            WHERE NOT EXISTS (SELECT 1 FROM operators)",
+                rusqlite::params![
+                    username,
+                    auth::hash_secret(password),
+                    Role::Manager.as_str(),
+                    Local::now().format(RUN_DATE_TIME_FORMAT).to_string(),
+                ],
+            )
+            .map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(Error::Unauthorized(
+                "an operator account already exists".to_string(),
+            ));
+        }
+        Ok(Actor::new(username, Role::Manager))
+    }
+
+    /// Changes the password of an operator account, which also unlocks it.
+    ///
+    /// Operators may always change their own password.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `username` - The name the operator logs in with.
+    /// * `password` - The new password.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the password is not valid or the account does
+    /// not exist.
+    pub fn set_operator_password(
+        &self,
+        actor: &Actor,
+        username: &str,
+        password: &str,
+    ) -> Result<(), Error> {
+        let is_self =
+            matches!(actor.role(), Role::Auditor | Role::Clerk | Role::Manager)
+                && actor.name() == username;
+        if !is_self {
+            authorize(actor, Permission::EditOperators)?;
+        }
+        auth::check_password(password).map_err(Error::InvalidCredential)?;
+        let n = self
+            .conn
+            .execute(
+                "UPDATE operators SET
+                secret_hash = ?1,
+                failed_logins = 0,
+                locked_until = NULL
+                WHERE username = ?2",
+                rusqlite::params![auth::hash_secret(password), username],
+            )
+            .map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+
+    /// Changes the role of an operator account.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `username` - The name the operator logs in with.
+    /// * `role` - What the operator may do, which must be an operator role.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the role is not valid, the account does not
+    /// exist, or it is the last manager and the role is not `manager`.
+    pub fn set_operator_role(
+        &self,
+        actor: &Actor,
+        username: &str,
+        role: Role,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditOperators)?;
+        Role::parse(role.as_str()).map_err(Error::InvalidCredential)?;
+        let sql = format!(
+            "UPDATE operators SET role = ?1
+            WHERE username = ?2 AND (?1 = 'manager' OR {})",
+            KEEPS_A_MANAGER
+        );
+        let n = self
+            .conn
+            .execute(&sql, rusqlite::params![role.as_str(), username])
+            .map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(self.last_manager_error(username)?);
+        }
+        Ok(())
+    }
+
+    /// Removes an operator account.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `username` - The name the operator logs in with.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the account does not exist or is the last
+    /// manager.
+    pub fn remove_operator(
+        &self,
+        actor: &Actor,
+        username: &str,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditOperators)?;
+        let sql = format!(
+            "DELETE FROM operators WHERE username = ?1 AND {}",
+            KEEPS_A_MANAGER
+        );
+        let n = self.conn.execute(&sql, [username]).map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(self.last_manager_error(username)?);
+        }
+        Ok(())
+    }
+
+    /// Says why an operator account was not changed by a statement that
+    /// keeps a manager: either it does not exist or it is the last manager.
+    fn last_manager_error(&self, username: &str) -> Result<Error, Error> {
+        let exists: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM operators WHERE username = ?",
+                [username],
+                |row| row.get(0),
+            )
+            .map_err(Error::Sql)?;
+        if exists {
+            Ok(Error::Unauthorized(format!(
+                "{} is the last manager and must stay one",
+                username
+            )))
+        } else {
+            Ok(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+        }
+    }
+
+    /// Gets every operator account, without their passwords.
+    ///
+    /// # Success
+    ///
+    /// Will return the accounts ordered by username.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` on database error, or if an account has a role or
+    /// date that is not valid.
+    pub fn get_operators(&self) -> Result<Vec<Operator>, Error> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT
+                username,
+                role,
+                failed_logins,
+                locked_until,
+                created_at
+                FROM operators ORDER BY username",
+            )
+            .map_err(Error::Sql)?;
+        let now = Local::now().naive_local();
+        let rows = stmt
+            .query_map([], |row| {
+                let username: String = row.get(0)?;
+                let role: String = row.get(1)?;
+                let locked_until: Option<String> = row.get(3)?;
+                let created_at: String = row.get(4)?;
+                let locked_until = match locked_until {
+                    Some(until) => {
+                        Some(parse_run_date_time(&until).ok_or_else(|| {
+                            invalid_text(3, format!("invalid date: {until}"))
+                        })?)
+                    }
+                    None => None,
+                };
+                Ok(Operator {
+                    username,
+                    role: Role::parse(&role)
+                        .map_err(|err| invalid_text(1, err))?,
+                    failed_logins: row.get(2)?,
+                    locked_until: locked_until.filter(|until| *until > now),
+                    created_at: parse_run_date_time(&created_at).ok_or_else(
+                        || {
+                            invalid_text(
+                                4,
+                                format!("invalid date: {created_at}"),
+                            )
+                        },
+                    )?,
+                })
+            })
+            .map_err(Error::Sql)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Error::Sql)
+    }
+
+    /// Checks the password of an operator who is logging in.
+    ///
+    /// After too many wrong passwords in a row the account is locked out for
+    /// a while, even if the right password is then entered.
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - The name the operator logs in with.
+    /// * `password` - The password that was entered.
+    ///
+    /// # Success
+    ///
+    /// Will return the actor to make changes as, with the operator's role.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` with `LoginFailed` if there is no such account or
+    /// the password is wrong, or `LockedOut` if the account is locked out.
+    pub fn login_operator(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<Actor, Error> {
+        let pending =
+            self.begin_login(LoginKey::Operator(username.to_string()))?;
+        let verified = pending.verify(password);
+        match self.finish_login(pending, verified) {
+            Err(Error::NoDataFound) => Err(Error::LoginFailed),
+            result => result,
+        }
+    }
+
+    /// Unlocks an operator account that was locked out by failed logins.
+    ///
+    /// # Arguments
+    ///
+    /// * `actor` - Who is making the change.
+    /// * `username` - The name the operator logs in with.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the account does not exist.
+    pub fn unlock_operator(
+        &self,
+        actor: &Actor,
+        username: &str,
+    ) -> Result<(), Error> {
+        authorize(actor, Permission::EditOperators)?;
+        self.unlock("operators", "username", &username)
+    }
+
+    /// Gets the stored hash of who is logging in, without checking it.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` with `LockedOut` if they are locked out.
+    fn begin_login(&self, key: LoginKey) -> Result<PendingLogin, Error> {
+        let (table, key_column) = key.table();
+        let hash = self.conn.query_row(
+            &format!(
+                "SELECT secret_hash FROM {} WHERE {} = ?",
+                table, key_column
+            ),
+            [key.value()],
+            |row| row.get(0),
+        );
+        let hash = match hash {
+            Ok(hash) => Some(hash),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(err) => return Err(Error::Sql(err)),
+        };
+        if hash.is_some()
+            && let (_, Some(until)) = self.login_state(&key)?
+            && until > Local::now().naive_local()
+        {
+            return Err(Error::LockedOut(until));
+        }
+        Ok(PendingLogin { key, hash })
+    }
+
+    /// Gets the failed logins in a row and any lockout of who is logging in.
+    fn login_state(
+        &self,
+        key: &LoginKey,
+    ) -> Result<(u32, Option<NaiveDateTime>), Error> {
+        let (table, key_column) = key.table();
+        let (failed_logins, locked_until) = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT failed_logins, locked_until FROM {} WHERE {} = ?",
+                    table, key_column
+                ),
+                [key.value()],
+                |row| {
+                    let failed_logins: u32 = row.get(0)?;
+                    let locked_until: Option<String> = row.get(1)?;
+                    Ok((failed_logins, locked_until))
+                },
+            )
+            .map_err(Error::Sql)?;
+        Ok((
+            failed_logins,
+            locked_until.as_deref().and_then(parse_run_date_time),
+        ))
+    }
+
+    /// Clears the failed logins and any lockout in a table of credentials.
+    fn unlock(
+        &self,
+        table: &str,
+        key_column: &str,
+        key: &dyn rusqlite::ToSql,
+    ) -> Result<(), Error> {
+        let n = self
+            .conn
+            .execute(
+                &format!(
+                    "UPDATE {} SET failed_logins = 0, locked_until = NULL
+                    WHERE {} = ?",
+                    table, key_column
+                ),
+                [key],
+            )
+            .map_err(Error::Sql)?;
+        if n == 0 {
+            return Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows));
+        }
+        Ok(())
+    }
+}
+
+/// Checks that an actor may make a change.
+///
+/// # Failure
+///
+/// Will return `Err` with `Unauthorized` if the actor's role does not have
+/// the permission.
+fn authorize(actor: &Actor, permission: Permission) -> Result<(), Error> {
+    if actor.allows(permission) {
+        Ok(())
+    } else {
+        Err(Error::Unauthorized(format!(
+            "{} may not {}",
+            actor, permission
+        )))
+    }
+}
+
+/// Checks that an actor may make a change for a provider. Providers may only
+/// make changes for themselves.
+///
+/// # Failure
+///
+/// Will return `Err` with `Unauthorized` if the actor's role does not have
+/// the permission, or the actor is another provider.
+fn authorize_for_provider(
+    actor: &Actor,
+    permission: Permission,
+    provider_id: u32,
+) -> Result<(), Error> {
+    authorize(actor, permission)?;
+    match actor.role() {
+        Role::Provider(id) if id != provider_id => {
+            Err(Error::Unauthorized(format!(
+                "{} may not {} for provider {}",
+                actor, permission, provider_id
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parses a date and time recorded in the database.
+fn parse_run_date_time(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, RUN_DATE_TIME_FORMAT).ok()
+}
+
+/// Creates a CSV attachment of a report for a period.
+///
+/// # Arguments
+///
+/// * `name` - The start of the file name, such as `provider_report`.
+/// * `period` - The period of the report.
+/// * `csv` - The report rendered as CSV.
+fn csv_attachment(
+    name: &str,
+    period: &ReportPeriod,
+    csv: String,
+) -> Attachment {
+    Attachment {
+        file_name: format!(
+            "{}_{}.csv",
+            name,
+            period.end().format(RUN_DATE_FORMAT)
+        ),
+        content_type: "text/csv; charset=utf-8".to_string(),
+        data: csv.into_bytes(),
+    }
+}
+
+/// The types of reports that are sent for a period.
+///
+/// `Accounting` is a whole run of the scheduled accounting procedure, which
+/// sends the other reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportType {
+    Member,
+    Provider,
+    Manager,
+    Eft,
+    Nacha,
+    Directory,
+    Accounting,
+}
+
+impl ReportType {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            ReportType::Member => "member",
+            ReportType::Provider => "provider",
+            ReportType::Manager => "manager",
+            ReportType::Eft => "eft",
+            ReportType::Nacha => "nacha",
+            ReportType::Directory => "directory",
+            ReportType::Accounting => "accounting",
+        }
+    }
+
+    pub(crate) fn from_str(text: &str) -> Option<Self> {
+        match text {
+            "member" => Some(ReportType::Member),
+            "provider" => Some(ReportType::Provider),
+            "manager" => Some(ReportType::Manager),
+            "eft" => Some(ReportType::Eft),
+            "nacha" => Some(ReportType::Nacha),
+            "directory" => Some(ReportType::Directory),
+            "accounting" => Some(ReportType::Accounting),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ReportType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The outcome of sending a report to every recipient.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeliverySummary {
+    /// The recipients that were delivered to.
+    pub recipients: Vec<String>,
+    /// Where the report was delivered, such as the files written.
+    pub files: Vec<String>,
+    /// The recipients that could not be delivered to.
+    pub failed: Vec<DeliveryFailure>,
+    /// The manifest written for the report run, if there was one.
+    pub manifest: Option<String>,
+    /// Why the manifest could not be written, if it was not.
+    pub manifest_error: Option<String>,
+}
+
+impl DeliverySummary {
+    /// Checks if the report was delivered to every recipient.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl std::fmt::Display for DeliverySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} recipient(s), {} file(s), {} failed",
+            self.recipients.len(),
+            self.files.len(),
+            self.failed.len()
+        )
+    }
+}
+
+/// The delivery status of an email in the outbox.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutboxStatus {
+    /// Waiting to be delivered or retried.
+    Queued,
+    /// Delivered.
+    Sent,
+    /// Could not be delivered after every attempt.
+    Failed,
+}
+
+impl OutboxStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            OutboxStatus::Queued => "queued",
+            OutboxStatus::Sent => "sent",
+            OutboxStatus::Failed => "failed",
+        }
+    }
+
+    fn from_str(text: &str) -> Option<Self> {
+        match text {
+            "queued" => Some(OutboxStatus::Queued),
+            "sent" => Some(OutboxStatus::Sent),
+            "failed" => Some(OutboxStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for OutboxStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An email in the outbox.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboxMessage {
+    /// The id of the email in the outbox.
+    pub id: u32,
+    /// The email to deliver.
+    pub email: Email,
+    /// Whether the email was delivered.
+    pub status: OutboxStatus,
+    /// How many times delivery was tried.
+    pub attempts: u32,
+    /// Why the latest attempt failed, if it did.
+    pub last_error: Option<String>,
+    /// When delivery is tried next, if the email is queued.
+    pub next_attempt_at: NaiveDateTime,
+    /// Where the email was delivered, if it was sent.
+    pub delivered_to: Option<String>,
+    /// When the email was queued.
+    pub created_at: NaiveDateTime,
+}
+
+/// Gets how long to wait before retrying an email, doubling each attempt.
+///
+/// # Arguments
+///
+/// * `attempts` - How many times delivery has been tried.
+fn retry_delay(attempts: u32) -> Duration {
+    Duration::seconds(RETRY_BASE_SECONDS << attempts.saturating_sub(1).min(16))
+}
+
+/// Creates the error for a text column that holds a value that is not
+/// valid.
+///
+/// # Arguments
+///
+/// * `column` - The index of the column.
+/// * `err` - What is wrong with the value.
+fn invalid_text(
+    column: usize,
+    err: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        column,
+        rusqlite::types::Type::Text,
+        err.into(),
+    )
+}
+
+/// The emails a report would send, built without sending them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportPreview {
+    /// The type of report.
+    pub report_type: ReportType,
+    /// The emails that would be sent.
+    pub emails: Vec<Email>,
+    /// Totals for the report, as labels and values (E.g. "Consultations").
+    pub totals: Vec<(String, String)>,
+}
+
+impl ReportPreview {
+    /// Writes every email as an `.eml` file under a scratch directory.
     ///
     /// # Arguments
     ///
-    /// * `id` - The id of the service.
-    /// * `name` - The name of the service.
-    /// * `fee` - The fee for the service.
+    /// * `dir` - The directory to write under, which is created if it does
+    ///   not exist.
+    ///
+    /// # Success
+    ///
+    /// Will return the paths of the files that were written.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if the service was not added.
-    pub fn add_service(
-        &self,
-        id: u32,
-        name: &str,
-        fee: f64,
-    ) -> Result<(), Error> {
-        if name.chars().count() == 0 {
-            return Err(Error::EmptyInput);
+    /// Will return `Err` for IO errors.
+    pub fn write(&self, dir: &Path) -> std::io::Result<Vec<String>> {
+        let transport = FileTransport::new(dir);
+        self.emails
+            .iter()
+            .map(|email| transport.send(email))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for ReportPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Preview of {} report: {} recipient(s)",
+            self.report_type,
+            self.emails.len()
+        )?;
+        for (label, value) in &self.totals {
+            write!(f, "\n{}: {}", label, value)?;
         }
-        let mut stmt = self
-            .conn
-            .prepare(
-                "INSERT INTO provider_directory (
-                service_id,
-                name,
-                fee
-            ) VALUES (?1, ?2, ?3)",
-            )
-            .map_err(Error::Sql)?;
-        stmt.execute(rusqlite::params![id, name, fee])
-            .map_err(Error::Sql)?;
         Ok(())
     }
+}
 
-    /// Gets the name corresponding to the specified service code id.
+/// A recipient a report could not be delivered to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryFailure {
+    /// The email of the recipient.
+    pub recipient: String,
+    /// Why the report could not be delivered.
+    pub error: String,
+}
+
+impl std::fmt::Display for DeliveryFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.recipient, self.error)
+    }
+}
+
+/// A recorded sending of a report for a period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportRun {
+    /// The id of the run in the database.
+    pub id: u32,
+    /// The type of report that was sent.
+    pub report_type: ReportType,
+    /// The period that was reported on.
+    pub period: ReportPeriod,
+    /// The email addresses the report was sent to.
+    pub recipients: Vec<String>,
+    /// The files that were written.
+    pub files: Vec<String>,
+    /// Whether every report was queued or written. Emails that could not
+    /// be delivered yet are retried from the outbox.
+    pub success: bool,
+    /// When the run happened.
+    pub created_at: NaiveDateTime,
+    /// When the run was scheduled for, if the scheduler ran it.
+    pub scheduled_for: Option<NaiveDateTime>,
+    /// A description of what happened during the run.
+    pub message: String,
+}
+
+/// Who is logging in, and the table of credentials they are kept in.
+#[derive(Debug, Clone, PartialEq)]
+enum LoginKey {
+    Provider(u32),
+    Operator(String),
+}
+
+impl LoginKey {
+    /// The table of credentials and the column that names who logs in.
+    fn table(&self) -> (&'static str, &'static str) {
+        match self {
+            LoginKey::Provider(_) => ("provider_pins", "provider_id"),
+            LoginKey::Operator(_) => ("operators", "username"),
+        }
+    }
+
+    fn value(&self) -> rusqlite::types::Value {
+        match self {
+            LoginKey::Provider(id) => rusqlite::types::Value::from(*id),
+            LoginKey::Operator(username) => {
+                rusqlite::types::Value::from(username.clone())
+            }
+        }
+    }
+}
+
+/// A login that has been started but whose PIN or password has not been
+/// checked yet. The check is slow, so it is made apart from the database.
+#[derive(Debug, Clone)]
+pub struct PendingLogin {
+    key: LoginKey,
+    hash: Option<String>,
+}
+
+impl PendingLogin {
+    /// Checks a PIN or password against the stored hash. When there is no
+    /// hash the secret is hashed anyway, so that takes just as long.
+    ///
+    /// # Success
+    ///
+    /// Will return `true` if the secret matches.
+    pub fn verify(&self, secret: &str) -> bool {
+        match &self.hash {
+            Some(hash) => auth::verify_secret(secret, hash),
+            None => {
+                auth::verify_missing(secret);
+                false
+            }
+        }
+    }
+}
+
+/// An operator account, without its password.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operator {
+    /// The name the operator logs in with.
+    pub username: String,
+    /// What the operator may do.
+    pub role: Role,
+    /// How many wrong passwords have been entered in a row.
+    pub failed_logins: u32,
+    /// When the account is locked out until, if it is locked out.
+    pub locked_until: Option<NaiveDateTime>,
+    /// When the account was added.
+    pub created_at: NaiveDateTime,
+}
+
+/// A period of days that reports are created for.
+///
+/// Both the start and end dates are included in the period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReportPeriod {
+    start: NaiveDate,
+    end: NaiveDate,
+}
+
+impl Default for ReportPeriod {
+    /// The last completed ChocAn week, which ended at the most recent
+    /// Friday midnight.
+    fn default() -> Self {
+        ReportPeriod::last_completed(Local::now().date_naive())
+    }
+}
+
+impl std::fmt::Display for ReportPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} to {}",
+            self.start.format(SERVICE_DATE_FORMAT),
+            self.end.format(SERVICE_DATE_FORMAT)
+        )
+    }
+}
+
+impl ReportPeriod {
+    /// Create a report period.
     ///
     /// # Arguments
     ///
-    /// * `id` - The id of the service to get the name of.
+    /// * `start` - The first day of the period.
+    /// * `end` - The last day of the period.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if the name could not be retrieved.
-    pub fn get_service_name(&self, id: u32) -> Result<String, Error> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT name FROM provider_directory WHERE service_id = ?")
-            .map_err(Error::Sql)?;
-        let rows = stmt
-            .query_map([id], |row| {
-                let name: String = row.get(0)?;
-                Ok(name)
-            })
-            .map_err(Error::Sql)?;
-        if let Some(name) = rows.flatten().next() {
-            return Ok(name);
+    /// Will return `Err` if the start is after the end.
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self, String> {
+        if start > end {
+            return Err(format!(
+                "start of period must not be after the end: {} > {}",
+                start.format(SERVICE_DATE_FORMAT),
+                end.format(SERVICE_DATE_FORMAT)
+            ));
         }
-        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+        Ok(ReportPeriod { start, end })
     }
 
-    /// Gets the fee corresponding to the specified service code id.
+    /// Create the ChocAn week ending on a Friday at midnight.
     ///
     /// # Arguments
     ///
-    /// * `id` - The id of the service to get the fee of.
+    /// * `friday` - The last day of the week.
     ///
     /// # Failure
     ///
-    /// Will return `Err` if the fee could not be retrieved.
-    pub fn get_service_fee(&self, id: u32) -> Result<f64, Error> {
-        let mut stmt = self
-            .conn
-            .prepare("SELECT fee FROM provider_directory WHERE service_id = ?")
-            .map_err(Error::Sql)?;
-        let rows = stmt
-            .query_map([id], |row| {
-                let fee: f64 = row.get(0)?;
-                Ok(fee)
-            })
-            .map_err(Error::Sql)?;
-        if let Some(fee) = rows.flatten().next() {
-            return Ok(fee);
+    /// Will return `Err` if the day is not a Friday.
+    pub fn week_ending(friday: NaiveDate) -> Result<Self, String> {
+        if friday.weekday() != Weekday::Fri {
+            return Err(format!(
+                "week must end on a Friday: {}",
+                friday.format(SERVICE_DATE_FORMAT)
+            ));
         }
-        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+        Ok(ReportPeriod {
+            start: friday - Duration::days(6),
+            end: friday,
+        })
     }
 
-    /// Gets the data for a member in the database.
+    /// Create the ChocAn week, Saturday through Friday, containing a date.
     ///
     /// # Arguments
     ///
-    /// * `id` - The id of the member to get the data for.
-    ///
-    /// # Failure
-    ///
-    /// Will return `Err` if the data could not be retrieved.
-    pub fn get_member_info(&self, id: u32) -> Result<PersonInfo, Error> {
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT
-                name,
-                address,
-                city,
-                state,
-                zipcode,
-                email
-                FROM members WHERE id = ?",
-            )
-            .map_err(Error::Sql)?;
-        let rows = stmt
-            .query_map([id], |row| {
-                let name: String = row.get(0)?;
-                let address: String = row.get(1)?;
-                let city: String = row.get(2)?;
-                let state: String = row.get(3)?;
-                let zipcode: u32 = row.get(4)?;
-                let email: String = row.get(5)?;
-                let location =
-                    LocationInfo::new(&address, &city, &state, zipcode)
-                        .unwrap();
-                Ok(PersonInfo::new(&name, id, &location, &email).unwrap())
-            })
-            .map_err(Error::Sql)?;
-        if let Some(person) = rows.flatten().next() {
-            return Ok(person);
+    /// * `date` - A day within the week.
+    pub fn week_of(date: NaiveDate) -> Self {
+        let days_until_friday = (Weekday::Fri.num_days_from_monday() + 7
+            - date.weekday().num_days_from_monday())
+            % 7;
+        let friday = date + Duration::days(days_until_friday.into());
+        ReportPeriod {
+            start: friday - Duration::days(6),
+            end: friday,
         }
-        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
     }
 
-    /// Gets the data for a provider in the database.
+    /// Create the last ChocAn week to be completed by a day.
+    ///
+    /// A week ends on Friday at midnight, so during a Friday the week
+    /// ending that day is not completed yet.
     ///
     /// # Arguments
     ///
-    /// * `id` - The id of the provider to get the data for.
+    /// * `today` - The day to look back from.
+    pub fn last_completed(today: NaiveDate) -> Self {
+        let days_back = match (today.weekday().num_days_from_monday() + 7
+            - Weekday::Fri.num_days_from_monday())
+            % 7
+        {
+            0 => 7,
+            n => n,
+        };
+        ReportPeriod::week_of(today - Duration::days(days_back.into()))
+    }
+
+    /// The first day of the period.
+    pub fn start(&self) -> NaiveDate {
+        self.start
+    }
+
+    /// The last day of the period.
+    pub fn end(&self) -> NaiveDate {
+        self.end
+    }
+
+    /// Checks if a date is within the period.
     ///
-    /// # Failure
+    /// # Arguments
     ///
-    /// Will return `Err` if the data could not be retrieved.
-    pub fn get_provider_info(&self, id: u32) -> Result<PersonInfo, Error> {
-        let mut stmt = self
-            .conn
-            .prepare(
-                "SELECT
-                name,
-                address,
-                city,
-                state,
-                zipcode,
-                email
-                FROM providers WHERE id = ?",
-            )
-            .map_err(Error::Sql)?;
-        let rows = stmt
-            .query_map([id], |row| {
-                let name: String = row.get(0)?;
-                let address: String = row.get(1)?;
-                let city: String = row.get(2)?;
-                let state: String = row.get(3)?;
-                let zipcode: u32 = row.get(4)?;
-                let email: String = row.get(5)?;
-                let location =
-                    LocationInfo::new(&address, &city, &state, zipcode)
-                        .unwrap();
-                Ok(PersonInfo::new(&name, id, &location, &email).unwrap())
-            })
-            .map_err(Error::Sql)?;
-        if let Some(person) = rows.flatten().next() {
-            return Ok(person);
+    /// * `date` - The date to check.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// Checks if a service date, formatted as "MM-DD-YYYY", is within the
+    /// period. Dates that can not be parsed are never within the period.
+    fn contains_service_date(&self, service_date: &str) -> bool {
+        match NaiveDate::parse_from_str(service_date, SERVICE_DATE_FORMAT) {
+            Ok(date) => self.contains(date),
+            Err(_) => false,
         }
-        Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
     }
 }
 
 /// Information on a person in the ChocAn database.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PersonInfo {
     id: u32,
     name: String,
@@ -1024,6 +4302,9 @@ impl PersonInfo {
                 return Err("Email does not have an '@' symbol".to_string());
             }
         }
+        if email.chars().any(|c| c.is_control()) {
+            return Err("Email must not contain control characters".to_string());
+        }
         Ok(PersonInfo {
             name: name.to_string(),
             id,
@@ -1031,6 +4312,26 @@ impl PersonInfo {
             email: email.to_string(),
         })
     }
+
+    /// The name of the person.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The unique id of the person.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The location of the person.
+    pub fn location(&self) -> &LocationInfo {
+        &self.location
+    }
+
+    /// The email of the person.
+    pub fn email(&self) -> &str {
+        &self.email
+    }
 }
 
 /// Information on location for a person in the ChocAn database.
@@ -1081,17 +4382,149 @@ impl LocationInfo {
         }
         if zipcode > MAX_ZIPCODE {
             return Err(format!(
-                "zipcode must be less than or equal to {}: {}",
-                MAX_ZIPCODE, zipcode
+                "zipcode must be less than or equal to {}: {}",
+                MAX_ZIPCODE, zipcode
+            ));
+        }
+        Ok(LocationInfo {
+            address: address.to_string(),
+            city: city.to_string(),
+            state: state.to_string(),
+            zipcode,
+        })
+    }
+
+    /// The street address.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// The city name.
+    pub fn city(&self) -> &str {
+        &self.city
+    }
+
+    /// The two character state name.
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// The zipcode number.
+    pub fn zipcode(&self) -> u32 {
+        self.zipcode
+    }
+}
+
+/// The type of a bank account.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountType {
+    Checking,
+    Savings,
+}
+
+impl AccountType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AccountType::Checking => "checking",
+            AccountType::Savings => "savings",
+        }
+    }
+}
+
+/// Bank account information used to pay a provider.
+///
+/// This is stored separately from the `PersonInfo` of the provider.
+#[derive(Debug, Clone)]
+pub struct BankInfo {
+    routing_number: String,
+    account_number: String,
+    account_type: AccountType,
+}
+
+impl BankInfo {
+    /// Create bank account information.
+    ///
+    /// # Arguments
+    ///
+    /// * `routing_number` - The 9 digit ABA routing number of the bank.
+    ///   The last digit must be a valid check digit.
+    /// * `account_number` - The account number. Constrained
+    ///   by `MAX_ACCOUNT_NUMBER_SIZE`.
+    /// * `account_type` - Whether the account is checking or savings.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a paramater is not valid.
+    pub fn new(
+        routing_number: &str,
+        account_number: &str,
+        account_type: AccountType,
+    ) -> Result<Self, String> {
+        check_routing_number(routing_number)?;
+        if account_number.is_empty()
+            || account_number.len() > MAX_ACCOUNT_NUMBER_SIZE
+            || !account_number.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            return Err(format!(
+                "account number must be 1 to {} letters or digits: {}",
+                MAX_ACCOUNT_NUMBER_SIZE, account_number
             ));
         }
-        Ok(LocationInfo {
-            address: address.to_string(),
-            city: city.to_string(),
-            state: state.to_string(),
-            zipcode,
+        Ok(BankInfo {
+            routing_number: routing_number.to_string(),
+            account_number: account_number.to_string(),
+            account_type,
         })
     }
+
+    /// The 9 digit routing number of the bank.
+    pub fn routing_number(&self) -> &str {
+        &self.routing_number
+    }
+
+    /// The account number at the bank.
+    pub fn account_number(&self) -> &str {
+        &self.account_number
+    }
+
+    /// Whether the account is checking or savings.
+    pub fn account_type(&self) -> AccountType {
+        self.account_type
+    }
+}
+
+/// Checks that a routing number is 9 digits with a valid ABA check digit.
+///
+/// # Arguments
+///
+/// * `routing_number` - The routing number to check.
+///
+/// # Failure
+///
+/// Will return `Err` if the routing number is not valid.
+pub(crate) fn check_routing_number(routing_number: &str) -> Result<(), String> {
+    if routing_number.len() != ROUTING_NUMBER_SIZE
+        || !routing_number.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(format!(
+            "routing number must be {} digits: {}",
+            ROUTING_NUMBER_SIZE, routing_number
+        ));
+    }
+    let digits: Vec<u32> = routing_number
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    let checksum = 3 * (digits[0] + digits[3] + digits[6])
+        + 7 * (digits[1] + digits[4] + digits[7])
+        + (digits[2] + digits[5] + digits[8]);
+    if !checksum.is_multiple_of(10) {
+        return Err(format!(
+            "routing number has an invalid check digit: {}",
+            routing_number
+        ));
+    }
+    Ok(())
 }
 
 /// A consultation record between a member and provider.
@@ -1154,13 +4587,15 @@ impl Consultation {
         service_code: u32,
         comments: &str,
     ) -> Result<Self, String> {
-        if curr_date.chars().count() != DATE_TIME_SIZE.try_into().unwrap() {
+        if curr_date.chars().count() != usize::try_from(DATE_TIME_SIZE).unwrap()
+        {
             return Err(format!(
                 "current date time must be equal to {} characters: {}",
                 DATE_TIME_SIZE, curr_date
             ));
         }
-        if service_date.chars().count() != SERVICE_DATE_SIZE.try_into().unwrap()
+        if service_date.chars().count()
+            != usize::try_from(SERVICE_DATE_SIZE).unwrap()
         {
             return Err(format!(
                 "service date must be equal to {} characters: {}",
@@ -1199,7 +4634,9 @@ impl Consultation {
                 MAX_SERVICE_CODE, service_code
             ));
         }
-        if comments.chars().count() == MAX_COMMENT_SIZE.try_into().unwrap() {
+        if comments.chars().count()
+            == usize::try_from(MAX_COMMENT_SIZE).unwrap()
+        {
             return Err(format!(
                 "comments must be less than or equal to {} characters: {}",
                 MAX_COMMENT_SIZE, comments
@@ -1214,9 +4651,40 @@ impl Consultation {
             comments: comments.to_string(),
         })
     }
+
+    /// The date and time the consultation was recorded.
+    pub fn curr_date(&self) -> &str {
+        &self.curr_date
+    }
+
+    /// The date the consultation occured.
+    pub fn service_date(&self) -> &str {
+        &self.service_date
+    }
+
+    /// The id of the provider.
+    pub fn provider_id(&self) -> u32 {
+        self.provider_id
+    }
+
+    /// The id of the member.
+    pub fn member_id(&self) -> u32 {
+        self.member_id
+    }
+
+    /// The code of the service issued.
+    pub fn service_code(&self) -> u32 {
+        self.service_code
+    }
+
+    /// Comments on the service.
+    pub fn comments(&self) -> &str {
+        &self.comments
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::let_and_return, clippy::single_match)]
 mod tests {
     use super::*;
 
@@ -1227,6 +4695,11 @@ mod tests {
         let _ = std::fs::remove_file(TEST_DB_PATH);
     }
 
+    /// The actor the tests make changes as, which may do anything.
+    fn actor() -> Actor {
+        Actor::system("test")
+    }
+
     fn get_a_person() -> PersonInfo {
         let location =
             LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
@@ -1248,6 +4721,12 @@ mod tests {
         person
     }
 
+    /// Gets a report period from a week ago until today.
+    fn get_a_recent_period() -> ReportPeriod {
+        let today = Local::now().date_naive();
+        ReportPeriod::new(today - Duration::days(7), today).unwrap()
+    }
+
     /// Creates a consultation with a date of yesterday.
     fn create_a_unique_consultation(
         member_id: u32,
@@ -1296,176 +4775,868 @@ mod tests {
             "This is a comment",
         ) {
             Ok(_) => (),
-            Err(err) => {
-                panic!("test_consultation_constructor() ERROR: {}", err)
-            }
-        }
-        match Consultation::new(
-            "01-13-2025:07:45:39",
-            "01_13-2025",
-            123456789,
-            123456789,
-            123456,
-            "This is a comment",
-        ) {
-            Ok(_) => panic!("Invalid format should give an error"),
-            Err(_) => (),
+            Err(err) => {
+                panic!("test_consultation_constructor() ERROR: {}", err)
+            }
+        }
+        match Consultation::new(
+            "01-13-2025:07:45:39",
+            "01_13-2025",
+            123456789,
+            123456789,
+            123456,
+            "This is a comment",
+        ) {
+            Ok(_) => panic!("Invalid format should give an error"),
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_person_info_email() {
+        let location =
+            LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+        for email in ["timmy.pdx.edu", "timmy@pdx.edu\r\nBcc: eve@evil.com"] {
+            match PersonInfo::new("Timmy Smith", 123456789, &location, email) {
+                Ok(_) => panic!("Invalid email should give an error"),
+                Err(_) => (),
+            }
+        }
+    }
+
+    #[test]
+    fn test_report_period_week_ending() {
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let period = ReportPeriod::week_ending(friday).unwrap();
+        assert_eq!(
+            period.start(),
+            NaiveDate::from_ymd_opt(2026, 10, 10).unwrap()
+        );
+        assert_eq!(period.end(), friday);
+        let thursday = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+        assert!(ReportPeriod::week_ending(thursday).is_err());
+    }
+
+    #[test]
+    fn test_report_period_week_of() {
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let expected = ReportPeriod::week_ending(friday).unwrap();
+        for day in 10..=16 {
+            let date = NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+            assert_eq!(ReportPeriod::week_of(date), expected);
+        }
+        let saturday = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert_ne!(ReportPeriod::week_of(saturday), expected);
+    }
+
+    #[test]
+    fn test_report_period_last_completed() {
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let expected = ReportPeriod::week_ending(friday).unwrap();
+        for day in 17..=23 {
+            let date = NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+            assert_eq!(ReportPeriod::last_completed(date), expected);
+        }
+        assert_ne!(ReportPeriod::last_completed(friday), expected);
+    }
+
+    #[test]
+    fn test_report_period_contains_service_date() {
+        let period = ReportPeriod::new(
+            NaiveDate::from_ymd_opt(2025, 12, 27).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 2).unwrap(),
+        )
+        .unwrap();
+        assert!(period.contains_service_date("12-27-2025"));
+        assert!(period.contains_service_date("01-02-2026"));
+        assert!(!period.contains_service_date("01-03-2026"));
+        assert!(!period.contains_service_date("12-28-2026"));
+        assert!(!period.contains_service_date("not a date"));
+    }
+
+    #[test]
+    fn test_send_member_reports_outside_period_error() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 99.99)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        let friday = NaiveDate::from_ymd_opt(2025, 10, 17).unwrap();
+        let period = ReportPeriod::week_ending(friday).unwrap();
+        match db.send_member_reports(&actor(), &period, false) {
+            Ok(_) => {
+                panic!("Expected error for no consultations in the period")
+            }
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_send_member_reports_with_populated_database_success() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 99.99)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName2", 2))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName3", 3))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName4", 4))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName2", 62))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(3, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(3, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(3, 61),
+        )
+        .unwrap();
+        match db.send_member_reports(&actor(), &get_a_recent_period(), false) {
+            Ok(_) => (),
+            Err(err) => panic!("send_member_reports() ERROR: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_send_member_reports_twice_already_sent_error() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 99.99)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        let period = get_a_recent_period();
+        db.send_member_reports(&actor(), &period, false).unwrap();
+        match db.send_member_reports(&actor(), &period, false) {
+            Err(Error::AlreadySent) => (),
+            _ => panic!("Expected error for reports already sent"),
+        }
+        db.send_member_reports(&actor(), &period, true).unwrap();
+        // A different type of report has not been sent yet.
+        db.send_provider_reports(&actor(), &period, false).unwrap();
+    }
+
+    #[test]
+    fn test_get_report_runs_records_recipients_and_files() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 99.99)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName2", 2))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        let period = get_a_recent_period();
+        db.send_member_reports(&actor(), &period, false).unwrap();
+
+        let runs = db.get_report_runs().unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].report_type, ReportType::Member);
+        assert_eq!(runs[0].period, period);
+        assert!(runs[0].success);
+        let mut recipients = runs[0].recipients.clone();
+        recipients.sort();
+        assert_eq!(
+            recipients,
+            vec!["MemberName1@pdx.edu", "MemberName2@pdx.edu"]
+        );
+        assert_eq!(runs[0].files.len(), 2);
+        assert!(db.is_report_sent(ReportType::Member, &period).unwrap());
+    }
+
+    #[test]
+    fn test_report_runs_not_recorded_without_activity() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let period = get_a_recent_period();
+        match db.send_member_reports(&actor(), &period, false) {
+            Ok(_) => panic!("Expected error for no activity"),
+            Err(_) => (),
+        }
+        assert!(db.get_report_runs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_report_runs_bad_row_error() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO report_runs (
+                    report_type, period_start, period_end, recipients,
+                    files, status, created_at
+                ) VALUES (
+                    'member', '10-10-2026', '2026-10-16', '', '', 'success',
+                    '2026-10-17 00:00:00'
+                )",
+                [],
+            )
+            .unwrap();
+        match db.get_report_runs() {
+            Ok(_) => panic!("A bad report run row should give an error"),
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_send_member_reports_with_empty_tables_error() {
+        remove_test_db();
+        let mut got_error = false;
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        match db.send_member_reports(&actor(), &get_a_recent_period(), false) {
+            Ok(_) => (),
+            Err(_) => got_error = true,
+        }
+        assert!(got_error);
+    }
+
+    #[test]
+    fn test_send_provider_reports_with_populated_database_success() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 99.99)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName2", 2))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName3", 3))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName4", 4))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName2", 62))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(3, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(3, 62),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(3, 62),
+        )
+        .unwrap();
+        match db.send_provider_reports(&actor(), &get_a_recent_period(), false)
+        {
+            Ok(_) => (),
+            Err(err) => panic!("send_provider_reports() ERROR: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_send_provider_reports_with_empty_tables_error() {
+        remove_test_db();
+        let mut got_error = false;
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        match db.send_provider_reports(&actor(), &get_a_recent_period(), false)
+        {
+            Ok(_) => (),
+            Err(_) => got_error = true,
+        }
+        assert!(got_error);
+    }
+
+    #[test]
+    fn test_send_manager_report_success() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+
+        db.add_service(&actor(), 123456, "ServiceName1", 10.99)
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 1),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 2),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 2),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 3),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 3),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(3, 3),
+        )
+        .unwrap();
+        match db.send_manager_report(&actor(), &get_a_recent_period(), false) {
+            Ok(_) => (),
+            Err(err) => panic!("send_manager_report() ERROR: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_send_manager_report_no_data_error() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let mut got_error = false;
+        match db.send_manager_report(&actor(), &get_a_recent_period(), false) {
+            Ok(_) => (),
+            Err(_) => got_error = true,
         }
+        assert!(got_error);
     }
 
     #[test]
-    fn test_send_member_reports_with_populated_database_success() {
+    fn test_send_eft_records_success() {
         remove_test_db();
         let db = DB::new(TEST_DB_PATH).unwrap();
-        db.add_service(123456, "ServiceName123456", 99.99).unwrap();
-        db.add_member(&create_a_unique_person("MemberName1", 1))
-            .unwrap();
-        db.add_member(&create_a_unique_person("MemberName2", 2))
-            .unwrap();
-        db.add_member(&create_a_unique_person("MemberName3", 3))
-            .unwrap();
-        db.add_member(&create_a_unique_person("MemberName4", 4))
-            .unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName1", 61))
-            .unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName2", 62))
-            .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(1, 61))
-            .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(2, 61))
-            .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(2, 61))
-            .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(3, 61))
+
+        db.add_service(&actor(), 123456, "ServiceName1", 10.99)
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(3, 61))
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 1))
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(3, 61))
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName2", 2))
             .unwrap();
-        match db.send_member_reports() {
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 1),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 2),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 2),
+        )
+        .unwrap();
+        match db.send_eft_records(&actor(), &get_a_recent_period(), false) {
             Ok(_) => (),
-            Err(err) => panic!("send_member_reports() ERROR: {}", err),
+            Err(err) => panic!("send_eft_records() ERROR: {}", err),
         }
     }
 
     #[test]
-    fn test_send_member_reports_with_empty_tables_error() {
+    fn test_send_eft_records_no_data_error() {
         remove_test_db();
-        let mut got_error = false;
         let db = DB::new(TEST_DB_PATH).unwrap();
-        db.add_member(&create_a_unique_person("MemberName1", 1))
-            .unwrap();
-        match db.send_member_reports() {
-            Ok(_) => (),
-            Err(_) => got_error = true,
+        if db
+            .send_eft_records(&actor(), &get_a_recent_period(), false)
+            .is_ok()
+        {
+            panic!("Expected error for no recent consultations")
         }
-        assert!(got_error);
     }
 
     #[test]
-    fn test_send_provider_reports_with_populated_database_success() {
+    fn test_get_provider_totals_matches_consultations() {
         remove_test_db();
         let db = DB::new(TEST_DB_PATH).unwrap();
-        db.add_service(123456, "ServiceName123456", 99.99).unwrap();
-        db.add_member(&create_a_unique_person("MemberName1", 1))
-            .unwrap();
-        db.add_member(&create_a_unique_person("MemberName2", 2))
-            .unwrap();
-        db.add_member(&create_a_unique_person("MemberName3", 3))
+
+        db.add_service(&actor(), 123456, "ServiceName1", 10.0)
             .unwrap();
-        db.add_member(&create_a_unique_person("MemberName4", 4))
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 2),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 1),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 2),
+        )
+        .unwrap();
+        let totals = db.get_provider_totals(&get_a_recent_period()).unwrap();
+        assert_eq!(totals, vec![(1, 1, 10.0), (2, 2, 20.0)]);
+    }
+
+    #[test]
+    fn test_bank_info_constructor() {
+        assert!(
+            BankInfo::new("091000019", "12345678", AccountType::Checking)
+                .is_ok()
+        );
+        assert!(
+            BankInfo::new("091000018", "12345678", AccountType::Checking)
+                .is_err()
+        );
+        assert!(
+            BankInfo::new("09100001", "12345678", AccountType::Checking)
+                .is_err()
+        );
+        assert!(BankInfo::new("091000019", "", AccountType::Savings).is_err());
+    }
+
+    #[test]
+    fn test_set_provider_bank_info_success() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 1))
             .unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName1", 61))
+        let bank = BankInfo::new("091000019", "12345678", AccountType::Savings)
             .unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName2", 62))
+        db.set_provider_bank_info(&actor(), 1, &bank).unwrap();
+        let bank =
+            BankInfo::new("091000019", "87654321", AccountType::Checking)
+                .unwrap();
+        db.set_provider_bank_info(&actor(), 1, &bank).unwrap();
+        let info = db.get_provider_bank_info(1).unwrap();
+        assert_eq!(info.account_number(), "87654321");
+        assert_eq!(info.account_type(), AccountType::Checking);
+    }
+
+    #[test]
+    fn test_set_provider_bank_info_no_provider_error() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let bank = BankInfo::new("091000019", "12345678", AccountType::Savings)
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(1, 61))
+        match db.set_provider_bank_info(&actor(), 1, &bank) {
+            Ok(_) => {
+                panic!("Bank info should not be set for a missing provider")
+            }
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_provider_pin_login_and_lockout() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        assert!(db.is_empty().unwrap());
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 1))
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(2, 61))
+        assert!(!db.is_empty().unwrap());
+        assert!(!db.has_provider_pin(1).unwrap());
+        assert!(matches!(
+            db.login_provider(1, "1234"),
+            Err(Error::NoDataFound)
+        ));
+        assert!(matches!(
+            db.set_provider_pin(&actor(), 1, "12"),
+            Err(Error::InvalidCredential(_))
+        ));
+        assert!(db.set_provider_pin(&actor(), 2, "1234").is_err());
+        db.set_provider_pin(&actor(), 1, "1234").unwrap();
+        assert!(db.has_provider_pin(1).unwrap());
+        db.login_provider(1, "1234").unwrap();
+
+        for _ in 1..MAX_FAILED_LOGINS {
+            assert!(matches!(
+                db.login_provider(1, "4321"),
+                Err(Error::LoginFailed)
+            ));
+        }
+        assert!(matches!(
+            db.login_provider(1, "4321"),
+            Err(Error::LockedOut(_))
+        ));
+        assert!(matches!(
+            db.login_provider(1, "1234"),
+            Err(Error::LockedOut(_))
+        ));
+        db.unlock_provider(&actor(), 1).unwrap();
+        db.login_provider(1, "1234").unwrap();
+
+        let pending = db.begin_provider_login(1).unwrap();
+        assert!(!pending.verify("4321"));
+        assert!(pending.verify("1234"));
+        assert_eq!(db.finish_login(pending, true).unwrap().name(), "1");
+        let pending = db.begin_provider_login(2).unwrap();
+        assert!(!pending.verify("1234"));
+        assert!(matches!(
+            db.finish_login(pending, false),
+            Err(Error::NoDataFound)
+        ));
+
+        db.remove_provider(&actor(), 1).unwrap();
+        assert!(matches!(
+            db.login_provider(1, "1234"),
+            Err(Error::NoDataFound)
+        ));
+    }
+
+    #[test]
+    fn test_operators() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let manager = Role::Manager;
+        assert!(db.get_operators().unwrap().is_empty());
+        assert!(
+            db.add_operator(&actor(), "ann smith", "password1", manager)
+                .is_err()
+        );
+        assert!(db.add_operator(&actor(), "ann", "short", manager).is_err());
+        assert!(matches!(
+            db.add_operator(&actor(), "ann", "password1", Role::System),
+            Err(Error::InvalidCredential(_))
+        ));
+        db.add_operator(&actor(), "ann", "password1", manager)
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(2, 61))
+        assert!(
+            db.add_operator(&actor(), "ann", "password2", manager)
+                .is_err()
+        );
+        assert!(matches!(
+            db.login_operator("bob", "password1"),
+            Err(Error::LoginFailed)
+        ));
+        let ann = db.login_operator("ann", "password1").unwrap();
+        assert_eq!(ann.name(), "ann");
+        assert_eq!(ann.role(), Role::Manager);
+
+        for _ in 0..MAX_FAILED_LOGINS {
+            let _ = db.login_operator("ann", "wrong password");
+        }
+        let operators = db.get_operators().unwrap();
+        assert_eq!(operators[0].username, "ann");
+        assert_eq!(operators[0].role, Role::Manager);
+        assert!(operators[0].locked_until.is_some());
+        db.set_operator_password(&actor(), "ann", "password2")
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(3, 61))
+        db.login_operator("ann", "password2").unwrap();
+        assert!(
+            db.set_operator_password(&actor(), "bob", "password2")
+                .is_err()
+        );
+
+        db.add_operator(&actor(), "bob", "password1", manager)
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(3, 62))
+        db.remove_operator(&actor(), "ann").unwrap();
+        assert!(db.remove_operator(&actor(), "ann").is_err());
+        assert!(matches!(
+            db.login_operator("ann", "password2"),
+            Err(Error::LoginFailed)
+        ));
+    }
+
+    #[test]
+    fn test_last_manager_is_kept() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let ann = db.add_first_operator("ann", "password1").unwrap();
+        db.add_operator(&ann, "bob", "password1", Role::Clerk)
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(3, 62))
+        assert!(matches!(
+            db.remove_operator(&ann, "ann"),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            db.set_operator_role(&ann, "ann", Role::Auditor),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            db.set_operator_role(&ann, "cal", Role::Manager),
+            Err(Error::Sql(rusqlite::Error::QueryReturnedNoRows))
+        ));
+        assert!(matches!(
+            db.set_operator_role(&ann, "bob", Role::System),
+            Err(Error::InvalidCredential(_))
+        ));
+        db.set_operator_role(&ann, "ann", Role::Manager).unwrap();
+
+        db.set_operator_role(&ann, "bob", Role::Manager).unwrap();
+        db.set_operator_role(&ann, "ann", Role::Auditor).unwrap();
+        let operators = db.get_operators().unwrap();
+        assert_eq!(operators[0].role, Role::Auditor);
+        assert_eq!(operators[1].role, Role::Manager);
+        let bob = db.login_operator("bob", "password1").unwrap();
+        assert!(matches!(
+            db.remove_operator(&bob, "bob"),
+            Err(Error::Unauthorized(_))
+        ));
+        db.remove_operator(&bob, "ann").unwrap();
+        assert_eq!(db.get_operators().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_get_operators_bad_role_error() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_first_operator("ann", "password1").unwrap();
+        db.conn
+            .execute_batch(
+                "PRAGMA ignore_check_constraints = ON;
+                UPDATE operators SET role = 'boss' WHERE username = 'ann';",
+            )
             .unwrap();
-        match db.send_provider_reports() {
-            Ok(_) => (),
-            Err(err) => panic!("send_provider_reports() ERROR: {}", err),
+        match db.get_operators() {
+            Ok(_) => panic!("A bad operator role should give an error"),
+            Err(_) => (),
         }
     }
 
     #[test]
-    fn test_send_provider_reports_with_empty_tables_error() {
+    fn test_add_first_operator() {
         remove_test_db();
-        let mut got_error = false;
         let db = DB::new(TEST_DB_PATH).unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName1", 61))
+        assert!(matches!(
+            db.add_first_operator("ann", "short"),
+            Err(Error::InvalidCredential(_))
+        ));
+        let ann = db.add_first_operator("ann", "password1").unwrap();
+        assert_eq!(ann.role(), Role::Manager);
+        db.add_member(&ann, &create_a_unique_person("MemberName", 1))
             .unwrap();
-        match db.send_provider_reports() {
-            Ok(_) => (),
-            Err(_) => got_error = true,
-        }
-        assert!(got_error);
+        assert!(matches!(
+            db.add_first_operator("bob", "password1"),
+            Err(Error::Unauthorized(_))
+        ));
+        assert_eq!(db.get_operators().unwrap().len(), 1);
+        db.login_operator("ann", "password1").unwrap();
     }
 
     #[test]
-    fn test_send_manager_report_success() {
+    fn test_roles_are_checked() {
         remove_test_db();
         let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_operator(&actor(), "ann", "password1", Role::Clerk)
+            .unwrap();
+        db.add_operator(&actor(), "cal", "password1", Role::Auditor)
+            .unwrap();
+        let clerk = db.login_operator("ann", "password1").unwrap();
+        let auditor = db.login_operator("cal", "password1").unwrap();
 
-        db.add_service(123456, "ServiceName1", 10.99).unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(1, 1))
+        db.add_member(&clerk, &create_a_unique_person("MemberName1", 1))
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(1, 2))
+        db.suspend_member(&clerk, 1).unwrap();
+        assert!(matches!(
+            db.add_service(&clerk, 123456, "ServiceName1", 10.99),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            db.send_manager_report(&clerk, &get_a_recent_period(), false),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            db.remove_member(&auditor, 1),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(
+            db.add_operator(&clerk, "bob", "password1", Role::Manager)
+                .is_err()
+        );
+        assert!(db.get_members().is_ok());
+
+        // Anyone may change their own password, but not someone else's.
+        db.set_operator_password(&auditor, "cal", "password2")
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(2, 2))
+        assert!(matches!(
+            db.set_operator_password(&auditor, "ann", "password2"),
+            Err(Error::Unauthorized(_))
+        ));
+        let err = db.unlock_operator(&auditor, "ann").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unauthorized error: auditor cal may not edit operator accounts"
+        );
+    }
+
+    #[test]
+    fn test_providers_act_only_for_themselves() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName1", 10.99)
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(1, 3))
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(2, 3))
+        for id in [61, 62] {
+            let name = format!("ProviderName{}", id);
+            db.add_provider(&actor(), &create_a_unique_person(&name, id))
+                .unwrap();
+            db.set_provider_pin(&actor(), id, "1234").unwrap();
+        }
+        let provider = db.login_provider(61, "1234").unwrap();
+        assert_eq!(provider.role(), Role::Provider(61));
+
+        db.add_consultation_record(
+            &provider,
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        assert!(matches!(
+            db.add_consultation_record(
+                &provider,
+                &create_a_unique_consultation(1, 62)
+            ),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            db.send_provider_directory(&provider, 62),
+            Err(Error::Unauthorized(_))
+        ));
+        assert!(matches!(
+            db.add_member(&provider, &create_a_unique_person("MemberName2", 2)),
+            Err(Error::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn test_send_nacha_file_success() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName1", 10.99)
             .unwrap();
-        db.add_consultation_record(&create_a_unique_consultation(3, 3))
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 1))
             .unwrap();
-        match db.send_manager_report() {
+        let bank =
+            BankInfo::new("091000019", "12345678", AccountType::Checking)
+                .unwrap();
+        db.set_provider_bank_info(&actor(), 1, &bank).unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 1),
+        )
+        .unwrap();
+        match db.send_nacha_file(
+            &actor(),
+            &get_a_recent_period(),
+            &Originator::chocan(),
+            false,
+        ) {
             Ok(_) => (),
-            Err(err) => panic!("send_manager_report() ERROR: {}", err),
+            Err(err) => panic!("send_nacha_file() ERROR: {}", err),
         }
     }
 
     #[test]
-    fn test_send_manager_report_no_data_error() {
+    fn test_send_nacha_file_no_bank_info_error() {
         remove_test_db();
         let db = DB::new(TEST_DB_PATH).unwrap();
-        let mut got_error = false;
-        match db.send_manager_report() {
-            Ok(_) => (),
-            Err(_) => got_error = true,
+        db.add_service(&actor(), 123456, "ServiceName1", 10.99)
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 1))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 1),
+        )
+        .unwrap();
+        match db.send_nacha_file(
+            &actor(),
+            &get_a_recent_period(),
+            &Originator::chocan(),
+            false,
+        ) {
+            Err(Error::NoBankInfo(1)) => (),
+            _ => panic!("Expected error for missing bank info"),
         }
-        assert!(got_error);
     }
 
     #[test]
     fn test_send_provider_directory_success() {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
-        db.add_service(111112, "ServiceName2", 20.99).unwrap();
-        db.add_service(111111, "ServiceName1", 10.99).unwrap();
-        db.add_service(111113, "ServiceName3", 30.99).unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName1", 1))
+        db.add_service(&actor(), 111112, "ServiceName2", 20.99)
+            .unwrap();
+        db.add_service(&actor(), 111111, "ServiceName1", 10.99)
+            .unwrap();
+        db.add_service(&actor(), 111113, "ServiceName3", 30.99)
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 1))
             .unwrap();
-        db.send_provider_directory(1).unwrap();
+        db.send_provider_directory(&actor(), 1).unwrap();
     }
 
     #[test]
     fn test_send_provider_directory_no_data_error() {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName1", 1))
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 1))
             .unwrap();
-        match db.send_provider_directory(1) {
+        match db.send_provider_directory(&actor(), 1) {
             Ok(_) => panic!("Expected error for no data in provider directory"),
             Err(_) => (),
         }
@@ -1476,12 +5647,15 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_member(&create_a_unique_person("MemberName", 1))
+        db.add_member(&actor(), &create_a_unique_person("MemberName", 1))
             .unwrap();
-        db.add_member(&create_a_unique_person("MemberName", 2))
-            .unwrap();
-        db.add_member(&create_a_unique_person("MemberName", 123456789))
+        db.add_member(&actor(), &create_a_unique_person("MemberName", 2))
             .unwrap();
+        db.add_member(
+            &actor(),
+            &create_a_unique_person("MemberName", 123456789),
+        )
+        .unwrap();
         match db.is_valid_member_id(1) {
             Ok(valid) => {
                 if !valid {
@@ -1521,12 +5695,15 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_provider(&create_a_unique_person("ProviderName", 1))
-            .unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName", 2))
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName", 1))
             .unwrap();
-        db.add_provider(&create_a_unique_person("ProviderName", 123456789))
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName", 2))
             .unwrap();
+        db.add_provider(
+            &actor(),
+            &create_a_unique_person("ProviderName", 123456789),
+        )
+        .unwrap();
         match db.is_valid_provider_id(1) {
             Ok(valid) => {
                 if !valid {
@@ -1566,9 +5743,9 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_service(1, "Therapy1", 99.99).unwrap();
-        db.add_service(2, "Therapy2", 99.99).unwrap();
-        db.add_service(123456, "Therapy3", 99.99).unwrap();
+        db.add_service(&actor(), 1, "Therapy1", 99.99).unwrap();
+        db.add_service(&actor(), 2, "Therapy2", 99.99).unwrap();
+        db.add_service(&actor(), 123456, "Therapy3", 99.99).unwrap();
         match db.is_valid_service_id(1) {
             Ok(valid) => {
                 if !valid {
@@ -1609,7 +5786,7 @@ mod tests {
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
         let person: PersonInfo = get_a_person();
 
-        match db.add_member(&person) {
+        match db.add_member(&actor(), &person) {
             Ok(_) => (),
             Err(err) => {
                 panic!("add_member() ERROR: {}", err);
@@ -1623,13 +5800,13 @@ mod tests {
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
         let person: PersonInfo = get_a_person();
 
-        match db.add_member(&person) {
+        match db.add_member(&actor(), &person) {
             Ok(_) => (),
             Err(err) => {
                 panic!("add_member() ERROR: {}", err);
             }
         }
-        match db.add_member(&person) {
+        match db.add_member(&actor(), &person) {
             Ok(_) => panic!("Member should already exist and not be added."),
             Err(_) => (),
         }
@@ -1641,56 +5818,201 @@ mod tests {
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
         let person: PersonInfo = get_a_person();
 
-        match db.add_provider(&person) {
+        match db.add_provider(&actor(), &person) {
+            Ok(_) => (),
+            Err(err) => {
+                panic!("add_provider() ERROR: {}", err);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_provider_already_exists_err() {
+        remove_test_db();
+        let db: DB = DB::new(TEST_DB_PATH).unwrap();
+        let person: PersonInfo = get_a_person();
+
+        match db.add_provider(&actor(), &person) {
             Ok(_) => (),
             Err(err) => {
                 panic!("add_provider() ERROR: {}", err);
             }
         }
+        match db.add_provider(&actor(), &person) {
+            Ok(_) => panic!("Provider should already exist and not be added."),
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_remove_member_exists_success() {
+        remove_test_db();
+        let db: DB = DB::new(TEST_DB_PATH).unwrap();
+
+        db.add_member(
+            &actor(),
+            &create_a_unique_person("MemberName", 123456789),
+        )
+        .unwrap();
+        match db.remove_member(&actor(), 123456789) {
+            Ok(_) => (),
+            Err(err) => panic!("remove_member() ERROR: {}", err),
+        }
+    }
+
+    #[test]
+    fn test_remove_member_doesnt_exist_error() {
+        remove_test_db();
+        let db: DB = DB::new(TEST_DB_PATH).unwrap();
+
+        match db.remove_member(&actor(), 123456789) {
+            Ok(_) => {
+                panic!("Member should not exist and not be able to be removed.")
+            }
+
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_suspend_and_reinstate_member() {
+        remove_test_db();
+        let db: DB = DB::new(TEST_DB_PATH).unwrap();
+
+        db.add_member(
+            &actor(),
+            &create_a_unique_person("MemberName", 123456789),
+        )
+        .unwrap();
+        db.suspend_member(&actor(), 123456789).unwrap();
+        assert!(!db.is_valid_member_id(123456789).unwrap());
+        assert_eq!(db.get_member_info(123456789).unwrap().id(), 123456789);
+        db.reinstate_member(&actor(), 123456789).unwrap();
+        assert!(db.is_valid_member_id(123456789).unwrap());
+        match db.suspend_member(&actor(), 987654321) {
+            Ok(_) => panic!(
+                "Member should not exist and not be able to be suspended."
+            ),
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_backup_copies_database() {
+        remove_test_db();
+        let backup = Path::new("./test_backup.db3");
+        let _ = std::fs::remove_file(backup);
+        let db: DB = DB::new(TEST_DB_PATH).unwrap();
+        db.add_member(
+            &actor(),
+            &create_a_unique_person("MemberName", 123456789),
+        )
+        .unwrap();
+
+        db.backup(&actor(), backup).unwrap();
+        let copy = DB::new(backup.to_str().unwrap()).unwrap();
+        assert!(copy.is_valid_member_id(123456789).unwrap());
+        match db.backup(&actor(), backup) {
+            Ok(_) => panic!("Backup should not replace an existing file."),
+            Err(_) => (),
+        }
+        let _ = std::fs::remove_file(backup);
+    }
+
+    #[test]
+    fn test_update_and_list_people() {
+        remove_test_db();
+        let db: DB = DB::new(TEST_DB_PATH).unwrap();
+
+        db.add_member(&actor(), &create_a_unique_person("Zed", 2))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("Amy", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("Pat", 3))
+            .unwrap();
+        let names: Vec<String> = db
+            .get_members()
+            .unwrap()
+            .iter()
+            .map(|person| person.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["Amy", "Zed"]);
+
+        db.update_member(&actor(), &create_a_unique_person("Zoe", 2))
+            .unwrap();
+        assert_eq!(db.get_member_info(2).unwrap().name(), "Zoe");
+        assert_eq!(db.get_member_info(2).unwrap().email(), "Zoe@pdx.edu");
+        db.update_provider(&actor(), &create_a_unique_person("Sam", 3))
+            .unwrap();
+        assert_eq!(db.get_providers().unwrap()[0].name(), "Sam");
+        if db
+            .update_member(&actor(), &create_a_unique_person("Nobody", 9))
+            .is_ok()
+        {
+            panic!("Member should not exist and not be able to be updated.")
+        }
     }
 
     #[test]
-    fn test_add_provider_already_exists_err() {
+    fn test_list_people_bad_row_error() {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
-        let person: PersonInfo = get_a_person();
 
-        match db.add_provider(&person) {
-            Ok(_) => (),
-            Err(err) => {
-                panic!("add_provider() ERROR: {}", err);
-            }
-        }
-        match db.add_provider(&person) {
-            Ok(_) => panic!("Provider should already exist and not be added."),
+        db.add_member(&actor(), &create_a_unique_person("Amy", 1))
+            .unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO members
+                (id, name, address, city, state, zipcode, email, is_valid)
+                VALUES (2, 'Bad', 'Street', 'City', 'OR', 97201,
+                'no-at-sign', 1)",
+                [],
+            )
+            .unwrap();
+        match db.get_members() {
+            Ok(_) => panic!("A bad member row should give an error"),
             Err(_) => (),
         }
     }
 
     #[test]
-    fn test_remove_member_exists_success() {
+    fn test_update_and_remove_service() {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_member(&create_a_unique_person("MemberName", 123456789))
+        db.add_service(&actor(), 123456, "ServiceName", 10.25)
             .unwrap();
-        match db.remove_member(123456789) {
-            Ok(_) => (),
-            Err(err) => panic!("remove_member() ERROR: {}", err),
+        db.update_service(&actor(), 123456, "NewName", 12.5)
+            .unwrap();
+        assert_eq!(db.get_service_name(123456).unwrap(), "NewName");
+        assert_eq!(db.get_service_fee(123456).unwrap(), 12.5);
+        db.remove_service(&actor(), 123456).unwrap();
+        assert!(!db.is_valid_service_id(123456).unwrap());
+        match db.update_service(&actor(), 123456, "NewName", 12.5) {
+            Ok(_) => panic!(
+                "Service should not exist and not be able to be updated."
+            ),
+            Err(_) => (),
         }
     }
 
     #[test]
-    fn test_remove_member_doesnt_exist_error() {
+    fn test_get_recent_consultations() {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        match db.remove_member(123456789) {
-            Ok(_) => {
-                panic!("Member should not exist and not be able to be removed.")
-            }
-            Err(_) => (),
+        for member in 1..=3 {
+            db.add_consultation_record(
+                &actor(),
+                &create_a_unique_consultation(member, 61),
+            )
+            .unwrap();
         }
+        let recent = db.get_recent_consultations(2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].member_id(), 3);
+        assert_eq!(recent[1].member_id(), 2);
+        assert_eq!(recent[0].provider_id(), 61);
     }
 
     #[test]
@@ -1698,9 +6020,12 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_provider(&create_a_unique_person("ProviderName", 123456789))
-            .unwrap();
-        match db.remove_provider(123456789) {
+        db.add_provider(
+            &actor(),
+            &create_a_unique_person("ProviderName", 123456789),
+        )
+        .unwrap();
+        match db.remove_provider(&actor(), 123456789) {
             Ok(_) => (),
             Err(err) => panic!("remove_provider() ERROR: {}", err),
         }
@@ -1711,12 +6036,11 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        match db.remove_provider(123456789) {
-            Ok(_) => {
-                panic!(
-                    "Provider should not exist and not be able to be removed."
-                )
-            }
+        match db.remove_provider(&actor(), 123456789) {
+            Ok(_) => panic!(
+                "Provider should not exist and not be able to be removed."
+            ),
+
             Err(_) => (),
         }
     }
@@ -1727,7 +6051,7 @@ mod tests {
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
         let consul: Consultation = get_a_consultation();
 
-        match db.add_consultation_record(&consul) {
+        match db.add_consultation_record(&actor(), &consul) {
             Ok(_) => (),
             Err(err) => {
                 panic!("add_consultation_record() ERROR: {}", err);
@@ -1740,7 +6064,7 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_service(123456, "Service1", 99.99).unwrap();
+        db.add_service(&actor(), 123456, "Service1", 99.99).unwrap();
     }
 
     #[test]
@@ -1748,8 +6072,8 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_service(123456, "Service1", 99.99).unwrap();
-        match db.add_service(123456, "Serv", 99.99) {
+        db.add_service(&actor(), 123456, "Service1", 99.99).unwrap();
+        match db.add_service(&actor(), 123456, "Serv", 99.99) {
             Ok(_) => panic!("Error expected for duplicate ID."),
             Err(_) => (),
         }
@@ -1760,8 +6084,9 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        match db.add_service(222222, "", 99.99) {
+        match db.add_service(&actor(), 222222, "", 99.99) {
             Ok(_) => panic!("Error expected for empty name."),
+
             Err(_) => (),
         }
     }
@@ -1771,7 +6096,7 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        db.add_service(123456, "Service1", 99.99).unwrap();
+        db.add_service(&actor(), 123456, "Service1", 99.99).unwrap();
         let name = db.get_service_name(123456).unwrap();
         if name != "Service1" {
             panic!("Name should match for retrieved name.");
@@ -1785,6 +6110,7 @@ mod tests {
 
         match db.get_service_name(123456) {
             Ok(_) => panic!("Error expected on empty database"),
+
             Err(_) => (),
         }
     }
@@ -1794,7 +6120,10 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        match db.add_member(&create_a_unique_person("PersonName", 123456789)) {
+        match db.add_member(
+            &actor(),
+            &create_a_unique_person("PersonName", 123456789),
+        ) {
             Ok(_) => (),
             Err(err) => panic!("add_member() ERROR: {}", err),
         }
@@ -1816,6 +6145,7 @@ mod tests {
             Ok(_) => panic!(
                 "Member info should give error when member does not exist"
             ),
+
             Err(_) => (),
         }
     }
@@ -1825,8 +6155,10 @@ mod tests {
         remove_test_db();
         let db: DB = DB::new(TEST_DB_PATH).unwrap();
 
-        match db.add_provider(&create_a_unique_person("PersonName", 123456789))
-        {
+        match db.add_provider(
+            &actor(),
+            &create_a_unique_person("PersonName", 123456789),
+        ) {
             Ok(_) => (),
             Err(err) => panic!("add_provider() ERROR: {}", err),
         }
@@ -1848,7 +6180,617 @@ mod tests {
             Ok(_) => panic!(
                 "Provider info should give error when member does not exist"
             ),
+
+            Err(_) => (),
+        }
+    }
+
+    #[test]
+    fn test_build_member_reports_groups_by_member() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 99.99)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName2", 2))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        let reports = db.build_member_reports(&get_a_recent_period()).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].member.id(), 1);
+        assert_eq!(reports[0].consultations.len(), 1);
+        assert_eq!(reports[1].member.id(), 2);
+        assert_eq!(reports[1].consultations.len(), 2);
+        assert_eq!(reports[1].consultations[0].provider_name, "ProviderName1");
+        assert_eq!(
+            reports[1].consultations[0].service_name,
+            "ServiceName123456"
+        );
+    }
+
+    #[test]
+    fn test_build_provider_and_manager_reports_totals() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName2", 62))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 62),
+        )
+        .unwrap();
+        let period = get_a_recent_period();
+
+        let reports = db.build_provider_reports(&period).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].provider.id(), 61);
+        assert_eq!(reports[0].total_consultations, 2);
+        assert_eq!(reports[0].total_fee, 20.5);
+        assert_eq!(reports[0].consultations[0].member_name, "MemberName1");
+
+        let manager = db.build_manager_report(&period).unwrap();
+        assert_eq!(manager.total_providers, 2);
+        assert_eq!(manager.total_consultations, 3);
+        assert_eq!(manager.total_fees, 30.75);
+        assert_eq!(manager.providers[1].provider_id, 62);
+    }
+
+    #[test]
+    fn test_build_provider_directory_sorted_by_name() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        match db.build_provider_directory() {
+            Ok(_) => panic!("Expected error for an empty provider directory"),
+            Err(_) => (),
+        }
+        db.add_service(&actor(), 222222, "Zumba", 20.0).unwrap();
+        db.add_service(&actor(), 111111, "Aerobics", 10.0).unwrap();
+        let directory = db.build_provider_directory().unwrap();
+        assert_eq!(directory.services.len(), 2);
+        assert_eq!(directory.services[0].name, "Aerobics");
+        assert_eq!(directory.services[0].service_id, 111111);
+        assert_eq!(directory.services[1].name, "Zumba");
+    }
+
+    #[test]
+    fn test_get_report_format_prefers_recipient() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let member = "MemberName1@pdx.edu";
+        assert_eq!(
+            db.get_report_format(ReportType::Member, member).unwrap(),
+            Format::Text
+        );
+        db.set_report_format(&actor(), ReportType::Member, None, Format::Html)
+            .unwrap();
+        assert_eq!(
+            db.get_report_format(ReportType::Member, member).unwrap(),
+            Format::Html
+        );
+        db.set_report_format(
+            &actor(),
+            ReportType::Member,
+            Some(member),
+            Format::Json,
+        )
+        .unwrap();
+        assert_eq!(
+            db.get_report_format(ReportType::Member, member).unwrap(),
+            Format::Json
+        );
+        assert_eq!(
+            db.get_report_format(ReportType::Member, "other@pdx.edu")
+                .unwrap(),
+            Format::Html
+        );
+        assert_eq!(
+            db.get_report_format(ReportType::Manager, member).unwrap(),
+            Format::Text
+        );
+        if db
+            .set_report_format(
+                &actor(),
+                ReportType::Member,
+                Some(""),
+                Format::Csv,
+            )
+            .is_ok()
+        {
+            panic!("Expected error for an empty recipient")
+        }
+    }
+
+    #[test]
+    fn test_send_manager_report_uses_report_format() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.set_report_format(&actor(), ReportType::Manager, None, Format::Csv)
+            .unwrap();
+        db.send_manager_report(&actor(), &get_a_recent_period(), false)
+            .unwrap();
+        let run = db.get_report_runs().unwrap().pop().unwrap();
+        let contents = std::fs::read_to_string(&run.files[0]).unwrap();
+        assert!(contents.contains("Provider number,Consultations,Fees\r\n"));
+        assert!(contents.contains("61,1,10.25\r\n"));
+    }
+
+    #[test]
+    fn test_get_csv_attachment_prefers_recipient() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let provider = "ProviderName1@pdx.edu";
+        assert!(
+            !db.get_csv_attachment(ReportType::Provider, provider)
+                .unwrap()
+        );
+        db.set_csv_attachment(&actor(), ReportType::Provider, None, true)
+            .unwrap();
+        assert!(
+            db.get_csv_attachment(ReportType::Provider, provider)
+                .unwrap()
+        );
+        db.set_csv_attachment(
+            &actor(),
+            ReportType::Provider,
+            Some(provider),
+            false,
+        )
+        .unwrap();
+        assert!(
+            !db.get_csv_attachment(ReportType::Provider, provider)
+                .unwrap()
+        );
+        assert!(
+            db.get_csv_attachment(ReportType::Provider, "other@pdx.edu")
+                .unwrap()
+        );
+        assert!(
+            !db.get_csv_attachment(ReportType::Manager, provider)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_send_provider_reports_with_csv_attachment() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName2", 62))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 62),
+        )
+        .unwrap();
+        db.set_csv_attachment(
+            &actor(),
+            ReportType::Provider,
+            Some("ProviderName1@pdx.edu"),
+            true,
+        )
+        .unwrap();
+        let period = get_a_recent_period();
+        db.send_provider_reports(&actor(), &period, false).unwrap();
+        let run = db.get_report_runs().unwrap().pop().unwrap();
+        let with_csv = std::fs::read_to_string(&run.files[0]).unwrap();
+        let without_csv = std::fs::read_to_string(&run.files[1]).unwrap();
+        let file_name = format!(
+            "filename=\"provider_report_{}.csv\"",
+            period.end().format(RUN_DATE_FORMAT)
+        );
+        assert!(with_csv.contains("Content-Type: multipart/mixed"));
+        assert!(with_csv.contains(&file_name));
+        assert!(with_csv.contains("Content-Type: text/csv; charset=utf-8"));
+        assert!(!without_csv.contains("multipart/mixed"));
+    }
+
+    /// A transport that fails for one recipient and writes files for the rest.
+    #[derive(Debug)]
+    struct FailingTransport {
+        fail_to: String,
+    }
+
+    impl EmailTransport for FailingTransport {
+        fn send(&self, email: &Email) -> std::io::Result<String> {
+            if email.to == self.fail_to {
+                return Err(std::io::Error::other("mailbox unavailable"));
+            }
+            FileTransport::default().send(email)
+        }
+    }
+
+    #[test]
+    fn test_send_member_reports_partial_failure() {
+        remove_test_db();
+        let transport = FailingTransport {
+            fail_to: String::from("MemberName2@pdx.edu"),
+        };
+        let db = DB::with_transport(TEST_DB_PATH, Box::new(transport)).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName2", 2))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        let summary = db
+            .send_member_reports(&actor(), &get_a_recent_period(), false)
+            .unwrap();
+        assert!(!summary.is_complete());
+        assert_eq!(summary.recipients, vec!["MemberName1@pdx.edu"]);
+        assert_eq!(summary.files.len(), 1);
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].recipient, "MemberName2@pdx.edu");
+        assert!(summary.failed[0].error.contains("mailbox unavailable"));
+        // Every report was queued, so the period is not sent again.
+        let run = db.get_report_runs().unwrap().pop().unwrap();
+        assert!(run.success);
+        assert_eq!(run.recipients, summary.recipients);
+        match db.send_member_reports(&actor(), &get_a_recent_period(), false) {
+            Err(Error::AlreadySent) => (),
+            _ => panic!("Queued reports should not be sent again"),
+        }
+        assert_eq!(db.get_outbox(None).unwrap().len(), 2);
+        // Emails waiting for a retry can be re-sent right away.
+        let summary = db.resend_failed(&actor(), None).unwrap();
+        assert_eq!(summary.failed.len(), 1);
+    }
+
+    #[test]
+    fn test_outbox_bad_row_error() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.conn
+            .execute(
+                "INSERT INTO outbox (
+                kind,
+                recipient,
+                recipient_name,
+                sender,
+                subject,
+                body,
+                status,
+                attempts,
+                next_attempt_at,
+                created_at,
+                updated_at
+            ) VALUES ('unknown', 'a@pdx.edu', 'A', 'b@pdx.edu', 'S', 'B',
+                'queued', 0, 'never', 'never', 'never')",
+                [],
+            )
+            .unwrap();
+        match db.get_outbox(None) {
+            Ok(_) => panic!("A bad outbox row should give an error"),
             Err(_) => (),
         }
     }
+
+    #[test]
+    fn test_outbox_retries_then_fails_then_resends() {
+        remove_test_db();
+        let transport = FailingTransport {
+            fail_to: String::from("MemberName2@pdx.edu"),
+        };
+        let db = DB::with_transport(TEST_DB_PATH, Box::new(transport)).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName2", 2))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        db.send_member_reports(&actor(), &get_a_recent_period(), false)
+            .unwrap();
+
+        let sent = db.get_outbox(Some(OutboxStatus::Sent)).unwrap();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].delivered_to.is_some());
+        let queued = db.get_outbox(Some(OutboxStatus::Queued)).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].email.to, "MemberName2@pdx.edu");
+        assert_eq!(queued[0].email.kind, EmailKind::MemberReport);
+        assert_eq!(queued[0].attempts, 1);
+        assert!(queued[0].last_error.is_some());
+        assert!(queued[0].next_attempt_at > queued[0].created_at);
+
+        let summary =
+            db.deliver_outbox(&actor(), queued[0].created_at).unwrap();
+        assert_eq!(summary, DeliverySummary::default());
+        for attempt in 1..MAX_DELIVERY_ATTEMPTS {
+            let later = queued[0].created_at + Duration::days(attempt.into());
+            let summary = db.deliver_outbox(&actor(), later).unwrap();
+            assert_eq!(summary.failed.len(), 1);
+        }
+        let failed = db.get_outbox(Some(OutboxStatus::Failed)).unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].attempts, MAX_DELIVERY_ATTEMPTS);
+        assert!(
+            db.get_outbox(Some(OutboxStatus::Queued))
+                .unwrap()
+                .is_empty()
+        );
+        drop(db);
+
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let summary = db.resend_failed(&actor(), Some(failed[0].id)).unwrap();
+        assert!(summary.is_complete());
+        assert_eq!(summary.recipients, vec!["MemberName2@pdx.edu"]);
+        assert_eq!(db.get_outbox(Some(OutboxStatus::Sent)).unwrap().len(), 2);
+        match db.resend_failed(&actor(), None) {
+            Err(Error::NoDataFound) => (),
+            _ => panic!("Nothing should be left to re-send"),
+        }
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(1), Duration::seconds(RETRY_BASE_SECONDS));
+        assert_eq!(retry_delay(2), Duration::seconds(RETRY_BASE_SECONDS * 2));
+        assert_eq!(retry_delay(4), Duration::seconds(RETRY_BASE_SECONDS * 8));
+    }
+
+    #[test]
+    fn test_with_config_sends_to_every_manager() {
+        remove_test_db();
+        let output = "./test_config_output";
+        let _ = std::fs::remove_dir_all(output);
+        let config = Config {
+            output_path: std::path::PathBuf::from(output),
+            sender: "reports@chocan.com".to_string(),
+            managers: Config::parse_managers(
+                "Ann <ann@pdx.edu>, Bob <bob@pdx.edu>",
+            )
+            .unwrap(),
+            ..Config::default()
+        };
+        let transport = Box::new(FileTransport::new(output));
+        let db = DB::with_config(TEST_DB_PATH, transport, config).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        let period = get_a_recent_period();
+
+        let summary = db.send_manager_report(&actor(), &period, false).unwrap();
+        assert_eq!(summary.recipients, vec!["ann@pdx.edu", "bob@pdx.edu"]);
+        for file in &summary.files {
+            assert!(file.starts_with("./test_config_output/manager/"));
+            let contents = std::fs::read_to_string(file).unwrap();
+            assert!(contents.contains("From: reports@chocan.com\r\n"));
+        }
+        let summary = db.send_eft_records(&actor(), &period, false).unwrap();
+        assert!(summary.files[0].starts_with("./test_config_output/eft/"));
+        let provider = db.get_provider_info(61).unwrap();
+        assert_eq!(summary.recipients, vec![provider.email]);
+        let run = db.get_report_runs().unwrap().pop().unwrap();
+        assert_eq!(run.recipients, summary.recipients);
+        let manifest: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(summary.manifest.unwrap()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest["entries"][0]["recipient"], run.recipients[0]);
+        assert_eq!(manifest["entries"][0]["file"], summary.files[0]);
+        let _ = std::fs::remove_dir_all(output);
+    }
+
+    #[test]
+    fn test_manifest_error_still_records_run() {
+        remove_test_db();
+        let output = "./test_manifest_error_output";
+        let _ = std::fs::remove_dir_all(output);
+        std::fs::create_dir_all(output).unwrap();
+        // A file where the manifests directory should be.
+        std::fs::write(format!("{}/manifests", output), "").unwrap();
+        let config = Config {
+            output_path: std::path::PathBuf::from(output),
+            ..Config::default()
+        };
+        let transport = Box::new(FileTransport::new(output));
+        let db = DB::with_config(TEST_DB_PATH, transport, config).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        let period = get_a_recent_period();
+
+        let summary = db.send_member_reports(&actor(), &period, false).unwrap();
+        assert!(summary.is_complete());
+        assert!(summary.manifest.is_none());
+        assert!(summary.manifest_error.is_some());
+        let run = db.get_report_runs().unwrap().pop().unwrap();
+        assert!(run.success);
+        assert_eq!(run.recipients, vec!["MemberName1@pdx.edu"]);
+        assert_eq!(run.files, summary.files);
+        match db.send_member_reports(&actor(), &period, false) {
+            Err(Error::AlreadySent) => (),
+            _ => panic!("A delivered run should not be sent again"),
+        }
+        let _ = std::fs::remove_dir_all(output);
+    }
+
+    #[test]
+    fn test_send_member_reports_same_name_writes_manifest() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("Same/Name", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("Same/Name", 2))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        let summary = db
+            .send_member_reports(&actor(), &get_a_recent_period(), false)
+            .unwrap();
+        assert_eq!(summary.files.len(), 2);
+        assert_ne!(summary.files[0], summary.files[1]);
+        for file in &summary.files {
+            assert!(file.starts_with("./emails/member/Same_Name_"));
+        }
+
+        let manifest = summary.manifest.unwrap();
+        assert!(manifest.starts_with("./emails/manifests/member_"));
+        let text = std::fs::read_to_string(&manifest).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let entries = json["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        for (entry, file) in entries.iter().zip(&summary.files) {
+            assert_eq!(entry["file"], file.as_str());
+            assert_eq!(entry["recipient"], "Same/Name@pdx.edu");
+            assert_eq!(entry["report_type"], "member");
+            assert_eq!(entry["sha256"], file_checksum(file).unwrap().as_str());
+        }
+    }
+
+    #[test]
+    fn test_preview_reports_send_nothing() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        db.add_service(&actor(), 123456, "ServiceName123456", 10.25)
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName1", 1))
+            .unwrap();
+        db.add_member(&actor(), &create_a_unique_person("MemberName2", 2))
+            .unwrap();
+        db.add_provider(&actor(), &create_a_unique_person("ProviderName1", 61))
+            .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(1, 61),
+        )
+        .unwrap();
+        db.add_consultation_record(
+            &actor(),
+            &create_a_unique_consultation(2, 61),
+        )
+        .unwrap();
+        let period = get_a_recent_period();
+
+        let preview = db.preview_member_reports(&period).unwrap();
+        assert_eq!(preview.emails.len(), 2);
+        assert_eq!(preview.emails[0].to, "MemberName1@pdx.edu");
+        let preview = db.preview_provider_reports(&period).unwrap();
+        assert_eq!(preview.emails.len(), 1);
+        assert!(preview.to_string().contains("Total fees: $20.50"));
+        let preview = db.preview_manager_report(&period).unwrap();
+        assert_eq!(
+            preview.to_string(),
+            "Preview of manager report: 1 recipient(s)\n\
+             Providers: 1\nConsultations: 2\nTotal fees: $20.50"
+        );
+        let preview = db.preview_provider_directory(61).unwrap();
+        assert_eq!(preview.totals, vec![("Services".into(), "1".into())]);
+
+        let dir = "./test_preview";
+        let _ = std::fs::remove_dir_all(dir);
+        let files = preview.write(Path::new(dir)).unwrap();
+        assert!(files[0].starts_with("./test_preview/provider/"));
+        let _ = std::fs::remove_dir_all(dir);
+
+        assert!(db.get_report_runs().unwrap().is_empty());
+        assert!(db.get_outbox(None).unwrap().is_empty());
+        assert!(!db.is_report_sent(ReportType::Member, &period).unwrap());
+        db.send_member_reports(&actor(), &period, false).unwrap();
+    }
 }
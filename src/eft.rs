@@ -0,0 +1,122 @@
+/* File: eft.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for writing electronic funds transfer (EFT) records as files.
+use crate::esend::create_unique_file;
+use std::io::prelude::*;
+use std::path::Path;
+
+/// An electronic funds transfer to pay a provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EftRecord {
+    pub provider_name: String,
+    pub provider_id: u32,
+    pub amount: f64,
+}
+
+/// Writes out an EFT file containing a record for every provider.
+///
+/// The file ends with a summary of the total number of providers and the
+/// total amount to be transferred.
+///
+/// # Arguments
+///
+/// * `records` - The EFT records to write.
+/// * `dir` - The directory to write the file in, which is created if it does
+///   not exist.
+///
+/// # Success
+///
+/// Will return the path of the file that was written.
+///
+/// # Failure
+///
+/// Will return `Err` for IO errors.
+pub fn write_eft_file(
+    records: &[EftRecord],
+    dir: &Path,
+) -> std::io::Result<String> {
+    let timestamp =
+        chrono::Local::now().format("%m-%d-%Y_%H:%M:%S").to_string();
+    let (mut file, file_name) =
+        create_unique_file(dir, &format!("eft_{}", timestamp), "txt")?;
+
+    let mut total_amount: f64 = 0.0;
+    for record in records {
+        total_amount += record.amount;
+        writeln!(
+            file,
+            "Provider name: {}, Provider number: {}, Amount: {:.2}",
+            record.provider_name, record.provider_id, record.amount
+        )?;
+    }
+    writeln!(file, "----------------------------------------")?;
+    writeln!(file, "Total providers: {}", records.len())?;
+    writeln!(file, "Total amount: {:.2}", total_amount)?;
+    Ok(file_name)
+}
+
+/// Writes out a NACHA ACH file.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of the ACH file.
+/// * `dir` - The directory to write the file in, which is created if it does
+///   not exist.
+///
+/// # Success
+///
+/// Will return the path of the file that was written.
+///
+/// # Failure
+///
+/// Will return `Err` for IO errors.
+pub fn write_nacha_file(contents: &str, dir: &Path) -> std::io::Result<String> {
+    let timestamp =
+        chrono::Local::now().format("%m-%d-%Y_%H:%M:%S").to_string();
+    let (mut file, file_name) =
+        create_unique_file(dir, &format!("nacha_{}", timestamp), "ach")?;
+    file.write_all(contents.as_bytes())?;
+    Ok(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_eft_file() {
+        let records = vec![
+            EftRecord {
+                provider_name: "ProviderName1".to_string(),
+                provider_id: 1,
+                amount: 10.50,
+            },
+            EftRecord {
+                provider_name: "ProviderName2".to_string(),
+                provider_id: 2,
+                amount: 20.25,
+            },
+        ];
+        let path = write_eft_file(&records, Path::new("./test_eft")).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains(
+            "Provider name: ProviderName1, Provider number: 1, Amount: 10.50"
+        ));
+        assert!(contents.contains("Total providers: 2"));
+        assert!(contents.contains("Total amount: 30.75"));
+        let _ = std::fs::remove_dir_all("./test_eft");
+    }
+}
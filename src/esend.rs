@@ -13,26 +13,24 @@
  * Instructor: Christopher Gilmore
  */
 
-//! Module for sending emails by writing them as files.
-use std::fs::{File, create_dir_all};
+//! Module for sending emails.
+//!
+//! Emails are sent through an `EmailTransport`. The `FileTransport` writes
+//! each email as a file under an output directory (`./emails` by default),
+//! while the other transports deliver to a Maildir, an mbox file or an SMTP
+//! server. Every transport sends the same RFC 5322 message built by the
+//! `mime` module.
+use crate::mime::{Attachment, Message};
+use std::fs::{File, OpenOptions, create_dir_all, rename};
 use std::io::prelude::*;
+use std::io::{BufReader, Error, ErrorKind};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
-/// The various paths in the file system where emails shall be written.
-#[non_exhaustive]
-struct EmailPath;
-impl EmailPath {
-    pub const MEMBER: &str = "./emails/member";
-    pub const PROVIDER: &str = "./emails/provider";
-    pub const MANAGER: &str = "./emails/manager";
-}
-
-// creates a directory and doesn't error if
-// the directory already exists
-fn ensure_email_dir() {
-    let _ = create_dir_all(EmailPath::MEMBER);
-    let _ = create_dir_all(EmailPath::PROVIDER);
-    let _ = create_dir_all(EmailPath::MANAGER);
-}
+/// The default directory that emails and other files are written under.
+pub const DEFAULT_OUTPUT_PATH: &str = "./emails";
 
 /// Sends an email for a provider report.
 ///
@@ -44,6 +42,10 @@ fn ensure_email_dir() {
 /// * `body` - The entire body of the email.
 /// * `recipient_name` - The name of the recipient.
 ///
+/// # Success
+///
+/// Will return the path of the file that was written.
+///
 /// # Failure
 ///
 /// Will return `Err` for IO errors.
@@ -53,16 +55,15 @@ pub fn send_provider_report(
     subject: &str,
     body: &str,
     recipient_name: &str,
-) -> std::io::Result<()> {
-    _ = send_email(
+) -> std::io::Result<String> {
+    send_email(
         to,
         from,
         subject,
         body,
         recipient_name,
-        EmailPath::PROVIDER,
-    );
-    Ok(())
+        EmailKind::ProviderReport,
+    )
 }
 
 /// Sends an email for a member report.
@@ -75,6 +76,10 @@ pub fn send_provider_report(
 /// * `body` - The entire body of the email.
 /// * `recipient_name` - The name of the recipient.
 ///
+/// # Success
+///
+/// Will return the path of the file that was written.
+///
 /// # Failure
 ///
 /// Will return `Err` for IO errors.
@@ -84,9 +89,15 @@ pub fn send_member_report(
     subject: &str,
     body: &str,
     recipient_name: &str,
-) -> std::io::Result<()> {
-    _ = send_email(to, from, subject, body, recipient_name, EmailPath::MEMBER);
-    Ok(())
+) -> std::io::Result<String> {
+    send_email(
+        to,
+        from,
+        subject,
+        body,
+        recipient_name,
+        EmailKind::MemberReport,
+    )
 }
 
 /// Sends an email for a manager report.
@@ -99,6 +110,10 @@ pub fn send_member_report(
 /// * `body` - The entire body of the email.
 /// * `recipient_name` - The name of the recipient.
 ///
+/// # Success
+///
+/// Will return the path of the file that was written.
+///
 /// # Failure
 ///
 /// Will return `Err` for IO errors.
@@ -108,9 +123,15 @@ pub fn send_manager_report(
     subject: &str,
     body: &str,
     recipient_name: &str,
-) -> std::io::Result<()> {
-    _ = send_email(to, from, subject, body, recipient_name, EmailPath::MANAGER);
-    Ok(())
+) -> std::io::Result<String> {
+    send_email(
+        to,
+        from,
+        subject,
+        body,
+        recipient_name,
+        EmailKind::ManagerReport,
+    )
 }
 
 /// Sends an email for a provider directory.
@@ -123,6 +144,10 @@ pub fn send_manager_report(
 /// * `body` - The entire body of the email.
 /// * `recipient_name` - The name of the recipient.
 ///
+/// # Success
+///
+/// Will return the path of the file that was written.
+///
 /// # Failure
 ///
 /// Will return `Err` for IO errors.
@@ -132,12 +157,18 @@ pub fn send_provider_directory(
     subject: &str,
     body: &str,
     recipient_name: &str,
-) -> std::io::Result<()> {
-    send_email(to, from, subject, body, recipient_name, EmailPath::PROVIDER)?;
-    Ok(())
+) -> std::io::Result<String> {
+    send_email(
+        to,
+        from,
+        subject,
+        body,
+        recipient_name,
+        EmailKind::ProviderDirectory,
+    )
 }
 
-/// Writes out an email to the specified path.
+/// Writes out an email under the default output directory.
 ///
 /// # Arguments
 ///
@@ -146,7 +177,11 @@ pub fn send_provider_directory(
 /// * `subject` - The subject line of the email.
 /// * `body` - The entire body of the email.
 /// * `recipient_name` - The name of the recipient.
-/// * `path` - The path to send the file, not including the file name.
+/// * `kind` - What the email is for, which decides its directory.
+///
+/// # Success
+///
+/// Will return the path of the file that was written.
 ///
 /// # Failure
 ///
@@ -157,25 +192,571 @@ fn send_email(
     subject: &str,
     body: &str,
     recipient_name: &str,
-    path: &str,
-) -> std::io::Result<()> {
-    ensure_email_dir();
+    kind: EmailKind,
+) -> std::io::Result<String> {
+    let message = Message {
+        from,
+        to,
+        to_name: recipient_name,
+        subject,
+        text: body,
+        html: None,
+        attachments: &[],
+    };
+    let dir = FileTransport::default().dir(kind);
+    write_email_file(&message.format(), recipient_name, &dir)
+}
 
+/// Writes out a formatted message as an `.eml` file.
+///
+/// # Arguments
+///
+/// * `message` - The RFC 5322 message.
+/// * `recipient_name` - The name of the recipient.
+/// * `dir` - The directory to write the file in, which is created if it
+///   does not exist.
+///
+/// # Success
+///
+/// Will return the path of the file that was written.
+///
+/// # Failure
+///
+/// Will return `Err` for IO errors.
+fn write_email_file(
+    message: &str,
+    recipient_name: &str,
+    dir: &Path,
+) -> std::io::Result<String> {
     let timestamp =
         chrono::Local::now().format("%m-%d-%Y_%H:%M:%S").to_string();
-    let file_name = format!("{}/{}_{}.txt", path, recipient_name, timestamp);
-    let mut file = File::create(&file_name)?;
+    let stem = format!("{}_{}", sanitize_file_name(recipient_name), timestamp);
+    let (mut file, file_name) = create_unique_file(dir, &stem, "eml")?;
+    file.write_all(message.as_bytes())?;
+    Ok(file_name)
+}
+
+/// Makes text safe to use as part of a file name.
+///
+/// Anything other than ASCII letters, digits, `-`, `_` and `.` is replaced
+/// with `_`, and leading dots are removed so the file is never hidden.
+///
+/// # Arguments
+///
+/// * `name` - The text to use in a file name, such as a recipient name.
+pub fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect();
+    match sanitized.trim_start_matches('.') {
+        "" => "unnamed".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
 
-    writeln!(file, "To: {}", to)?;
-    writeln!(file, "From: {}", from)?;
-    writeln!(file, "Subject: {}", subject)?;
-    writeln!(
-        file,
-        "Date: {}\n",
-        chrono::Local::now().format("%Y-%m-%d %H-%M-%S")
-    )?;
-    writeln!(file, "{}\n", body)?;
-    Ok(())
+/// Creates a new file in a directory without ever replacing another file.
+///
+/// The file is named `{stem}.{extension}`, or `{stem}_{n}.{extension}` with
+/// the lowest `n` from 2 up that is free if that name is taken.
+///
+/// # Arguments
+///
+/// * `dir` - The directory to create the file in, which is created if it
+///   does not exist.
+/// * `stem` - The file name without its extension, which must already be
+///   safe to use in a file name.
+/// * `extension` - The extension of the file name (E.g. `eml`).
+///
+/// # Success
+///
+/// Will return the created file and its path.
+///
+/// # Failure
+///
+/// Will return `Err` for IO errors.
+pub fn create_unique_file(
+    dir: &Path,
+    stem: &str,
+    extension: &str,
+) -> std::io::Result<(File, String)> {
+    create_dir_all(dir)?;
+    let mut count = 1;
+    loop {
+        let file_name = match count {
+            1 => format!("{}.{}", stem, extension),
+            n => format!("{}_{}.{}", stem, n, extension),
+        };
+        let path = dir.join(file_name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, path.display().to_string())),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => count += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// The kinds of emails ChocAn sends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmailKind {
+    MemberReport,
+    ProviderReport,
+    ManagerReport,
+    ProviderDirectory,
+}
+
+impl EmailKind {
+    /// The directory, under the output directory, where emails of this
+    /// kind are written.
+    fn dir_name(&self) -> &'static str {
+        match self {
+            EmailKind::MemberReport => "member",
+            EmailKind::ProviderReport => "provider",
+            EmailKind::ManagerReport => "manager",
+            EmailKind::ProviderDirectory => "provider",
+        }
+    }
+
+    /// The name of this kind of email, as stored in the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailKind::MemberReport => "member_report",
+            EmailKind::ProviderReport => "provider_report",
+            EmailKind::ManagerReport => "manager_report",
+            EmailKind::ProviderDirectory => "provider_directory",
+        }
+    }
+
+    /// Gets the kind of email from its name.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The name of the kind of email (E.g. `member_report`).
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "member_report" => Some(EmailKind::MemberReport),
+            "provider_report" => Some(EmailKind::ProviderReport),
+            "manager_report" => Some(EmailKind::ManagerReport),
+            "provider_directory" => Some(EmailKind::ProviderDirectory),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EmailKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An email to be sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Email {
+    /// The email address of the recipient.
+    pub to: String,
+    /// The email address of the sender (ChocAn).
+    pub from: String,
+    /// The subject line of the email.
+    pub subject: String,
+    /// The entire plain text body of the email.
+    pub body: String,
+    /// An HTML version of the body, sent along with the plain text.
+    pub html: Option<String>,
+    /// Files attached to the email.
+    pub attachments: Vec<Attachment>,
+    /// The name of the recipient.
+    pub recipient_name: String,
+    /// What the email is for.
+    pub kind: EmailKind,
+}
+
+impl Email {
+    /// Creates a plain text email without attachments.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the email is for.
+    /// * `to` - The email address of the recipient.
+    /// * `from` - The email address of the sender (ChocAn).
+    /// * `subject` - The subject line of the email.
+    /// * `body` - The entire plain text body of the email.
+    /// * `recipient_name` - The name of the recipient.
+    pub fn new(
+        kind: EmailKind,
+        to: &str,
+        from: &str,
+        subject: &str,
+        body: &str,
+        recipient_name: &str,
+    ) -> Self {
+        Email {
+            to: to.to_string(),
+            from: from.to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            html: None,
+            attachments: Vec::new(),
+            recipient_name: recipient_name.to_string(),
+            kind,
+        }
+    }
+
+    /// The email as an RFC 5322 message with CRLF line endings.
+    pub fn to_message(&self) -> String {
+        Message {
+            from: &self.from,
+            to: &self.to,
+            to_name: &self.recipient_name,
+            subject: &self.subject,
+            text: &self.body,
+            html: self.html.as_deref(),
+            attachments: &self.attachments,
+        }
+        .format()
+    }
+}
+
+/// A way of delivering emails.
+pub trait EmailTransport: std::fmt::Debug + Send {
+    /// Sends an email.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - The email to send.
+    ///
+    /// # Success
+    ///
+    /// Will return where the email was delivered, such as a file path.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the email was not delivered.
+    fn send(&self, email: &Email) -> std::io::Result<String>;
+
+    /// Checks if every email is delivered to its own file, so that where it
+    /// was delivered can be checksummed.
+    fn writes_files(&self) -> bool {
+        false
+    }
+}
+
+/// Parses a transport from a description.
+///
+/// The description is one of `file`, `maildir:PATH`, `mbox:PATH` or
+/// `smtp://HOST[:PORT]`.
+///
+/// # Arguments
+///
+/// * `spec` - The description of the transport.
+///
+/// # Failure
+///
+/// Will return `Err` if the description is not understood.
+pub fn parse_transport(spec: &str) -> Result<Box<dyn EmailTransport>, String> {
+    let spec = spec.trim();
+    if spec == "file" {
+        return Ok(Box::new(FileTransport::default()));
+    }
+    if let Some(path) = spec.strip_prefix("file:") {
+        return Ok(Box::new(FileTransport::new(path)));
+    }
+    if let Some(path) = spec.strip_prefix("maildir:") {
+        return Ok(Box::new(MaildirTransport::new(path)));
+    }
+    if let Some(path) = spec.strip_prefix("mbox:") {
+        return Ok(Box::new(MboxTransport::new(path)));
+    }
+    if let Some(address) = spec.strip_prefix("smtp://") {
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => match port.parse() {
+                Ok(port) => (host, port),
+                Err(_) => return Err(format!("Invalid SMTP port: {port}")),
+            },
+            None => (address, SmtpTransport::DEFAULT_PORT),
+        };
+        if host.is_empty() {
+            return Err("SMTP host is empty".to_string());
+        }
+        return Ok(Box::new(SmtpTransport::new(host, port)));
+    }
+    Err(format!(
+        "Unknown email transport {spec}, expected file, maildir:PATH, \
+         file:PATH, mbox:PATH or smtp://HOST[:PORT]"
+    ))
+}
+
+/// Writes emails as `.eml` files under an output directory, one directory
+/// per kind.
+#[derive(Debug, Clone)]
+pub struct FileTransport {
+    root: PathBuf,
+}
+
+impl Default for FileTransport {
+    /// Writes under `DEFAULT_OUTPUT_PATH`.
+    fn default() -> Self {
+        FileTransport::new(DEFAULT_OUTPUT_PATH)
+    }
+}
+
+impl FileTransport {
+    /// Create a transport that writes emails under a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The output directory, which is created if it does not
+    ///   exist.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileTransport { root: root.into() }
+    }
+
+    /// The directory where emails of a kind are written.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the emails are for.
+    pub fn dir(&self, kind: EmailKind) -> PathBuf {
+        self.root.join(kind.dir_name())
+    }
+}
+
+impl EmailTransport for FileTransport {
+    fn send(&self, email: &Email) -> std::io::Result<String> {
+        write_email_file(
+            &email.to_message(),
+            &email.recipient_name,
+            &self.dir(email.kind),
+        )
+    }
+
+    fn writes_files(&self) -> bool {
+        true
+    }
+}
+
+/// Counts the emails delivered by this process for unique Maildir names.
+static MAILDIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Delivers emails into a Maildir.
+///
+/// Each email is written to `tmp` and then moved to `new`, so a mail reader
+/// never sees a partly written email.
+#[derive(Debug, Clone)]
+pub struct MaildirTransport {
+    path: PathBuf,
+}
+
+impl MaildirTransport {
+    /// Creates a transport for the Maildir at a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The Maildir, which is created if it does not exist.
+    pub fn new(path: &str) -> Self {
+        MaildirTransport {
+            path: PathBuf::from(path),
+        }
+    }
+}
+
+impl EmailTransport for MaildirTransport {
+    fn send(&self, email: &Email) -> std::io::Result<String> {
+        for dir in ["tmp", "new", "cur"] {
+            create_dir_all(self.path.join(dir))?;
+        }
+        let now = chrono::Local::now();
+        let name = format!(
+            "{}.M{}P{}Q{}.chocanon",
+            now.timestamp(),
+            now.timestamp_subsec_micros(),
+            std::process::id(),
+            MAILDIR_COUNTER.fetch_add(1, Ordering::Relaxed),
+        );
+        let tmp = self.path.join("tmp").join(&name);
+        let new = self.path.join("new").join(&name);
+        // Maildirs store messages with the line endings of the system.
+        let message = email.to_message().replace("\r\n", "\n");
+        let mut file = File::create(&tmp)?;
+        file.write_all(message.as_bytes())?;
+        file.sync_all()?;
+        rename(&tmp, &new)?;
+        Ok(new.display().to_string())
+    }
+
+    fn writes_files(&self) -> bool {
+        true
+    }
+}
+
+/// Appends emails to an mbox file.
+///
+/// Lines of the body starting with `From ` are quoted with `>` as in the
+/// mboxrd format.
+#[derive(Debug, Clone)]
+pub struct MboxTransport {
+    path: PathBuf,
+}
+
+impl MboxTransport {
+    /// Creates a transport for the mbox file at a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The mbox file, which is created if it does not exist.
+    pub fn new(path: &str) -> Self {
+        MboxTransport {
+            path: PathBuf::from(path),
+        }
+    }
+}
+
+impl EmailTransport for MboxTransport {
+    fn send(&self, email: &Email) -> std::io::Result<String> {
+        if let Some(dir) = self.path.parent() {
+            create_dir_all(dir)?;
+        }
+        let mut text = format!(
+            "From {} {}\n",
+            email.from,
+            chrono::Local::now().format("%a %b %e %H:%M:%S %Y")
+        );
+        for line in email.to_message().lines() {
+            if line.trim_start_matches('>').starts_with("From ") {
+                text.push('>');
+            }
+            text.push_str(line);
+            text.push('\n');
+        }
+        text.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(text.as_bytes())?;
+        Ok(self.path.display().to_string())
+    }
+}
+
+/// Sends emails to an SMTP server without encryption or authentication,
+/// such as a local mail relay.
+#[derive(Debug, Clone)]
+pub struct SmtpTransport {
+    host: String,
+    port: u16,
+}
+
+impl SmtpTransport {
+    /// The port SMTP servers usually accept mail on.
+    pub const DEFAULT_PORT: u16 = 25;
+
+    /// How long to wait for the server before giving up.
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Creates a transport for the SMTP server at a host and port.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host name or address of the server.
+    /// * `port` - The port of the server.
+    pub fn new(host: &str, port: u16) -> Self {
+        SmtpTransport {
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    /// Sends a command and checks the reply code of the server.
+    fn command(
+        reader: &mut BufReader<TcpStream>,
+        writer: &mut TcpStream,
+        command: &str,
+        expected: &[u16],
+    ) -> std::io::Result<()> {
+        writer.write_all(command.as_bytes())?;
+        writer.write_all(b"\r\n")?;
+        Self::reply(reader, command, expected)
+    }
+
+    /// Reads a possibly multiline reply and checks its code.
+    fn reply(
+        reader: &mut BufReader<TcpStream>,
+        command: &str,
+        expected: &[u16],
+    ) -> std::io::Result<()> {
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "SMTP server closed the connection",
+                ));
+            }
+            let code: Option<u16> = line.get(..3).and_then(|c| c.parse().ok());
+            let Some(code) = code else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid SMTP reply: {}", line.trim_end()),
+                ));
+            };
+            // A dash after the code means more lines follow.
+            if line.as_bytes().get(3) == Some(&b'-') {
+                continue;
+            }
+            if expected.contains(&code) {
+                return Ok(());
+            }
+            let command = command.split(':').next().unwrap_or(command);
+            return Err(Error::other(format!(
+                "SMTP server replied {} to {}",
+                line.trim_end(),
+                command
+            )));
+        }
+    }
+}
+
+impl EmailTransport for SmtpTransport {
+    fn send(&self, email: &Email) -> std::io::Result<String> {
+        let stream = TcpStream::connect((self.host.as_str(), self.port))?;
+        stream.set_read_timeout(Some(Self::TIMEOUT))?;
+        stream.set_write_timeout(Some(Self::TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+
+        Self::reply(&mut reader, "connect", &[220])?;
+        Self::command(&mut reader, &mut writer, "EHLO chocanon", &[250])?;
+        Self::command(
+            &mut reader,
+            &mut writer,
+            &format!("MAIL FROM:<{}>", email.from),
+            &[250],
+        )?;
+        Self::command(
+            &mut reader,
+            &mut writer,
+            &format!("RCPT TO:<{}>", email.to),
+            &[250, 251],
+        )?;
+        Self::command(&mut reader, &mut writer, "DATA", &[354])?;
+
+        let mut data = String::new();
+        for line in email.to_message().lines() {
+            // Lines starting with a dot are doubled so they do not end the
+            // message early.
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        writer.write_all(data.as_bytes())?;
+        Self::command(&mut reader, &mut writer, ".", &[250])?;
+        Self::command(&mut reader, &mut writer, "QUIT", &[221])?;
+        Ok(format!("smtp://{}:{}/{}", self.host, self.port, email.to))
+    }
 }
 
 #[cfg(test)]
@@ -190,19 +771,27 @@ mod tests {
             "Regarding Follow up meeting",
             "This is te body of the email.",
             "Name",
-            EmailPath::MEMBER,
+            EmailKind::MemberReport,
         ) {
             Ok(_) => (),
             Err(err) => panic!("ERROR {}", err),
         }
     }
 
+    #[test]
+    fn test_write_email_file_error() {
+        // A file where the directory should be cannot be written under.
+        let result =
+            write_email_file("message", "Name", Path::new("./Cargo.toml"));
+        assert!(result.is_err(), "write errors must reach the caller");
+    }
+
     #[test]
     fn test_send_member_report() {
         let _ =
             send_member_report("user", "chocanon", "matter", "testst", "God");
 
-        let dir = std::path::Path::new(EmailPath::MEMBER);
+        let dir = FileTransport::default().dir(EmailKind::MemberReport);
         assert!(
             dir.read_dir().unwrap().next().is_some(),
             "send member report did not create a file"
@@ -215,7 +804,7 @@ mod tests {
             "user1", "Chocanon", "consults", "do this", "You",
         );
 
-        let dir = std::path::Path::new(EmailPath::PROVIDER);
+        let dir = FileTransport::default().dir(EmailKind::ProviderReport);
         assert!(
             dir.read_dir().unwrap().next().is_some(),
             "send provider report did not create a file"
@@ -232,7 +821,7 @@ mod tests {
             "You",
         );
 
-        let dir = std::path::Path::new(EmailPath::MANAGER);
+        let dir = FileTransport::default().dir(EmailKind::ManagerReport);
         assert!(
             dir.read_dir().unwrap().next().is_some(),
             "send manager report did not create a file"
@@ -245,10 +834,171 @@ mod tests {
             "user1", "Chocanon", "consults", "do this", "You",
         );
 
-        let dir = std::path::Path::new(EmailPath::PROVIDER);
+        let dir = FileTransport::default().dir(EmailKind::ProviderReport);
         assert!(
             dir.read_dir().unwrap().next().is_some(),
             "send provider directory did not create a file"
         );
     }
+
+    fn get_an_email() -> Email {
+        Email {
+            to: "member@pdx.edu".to_string(),
+            from: "testing@chocan.com".to_string(),
+            subject: "Member Report for Name".to_string(),
+            body: "From the desk of ChocAn\n.\nBye".to_string(),
+            html: None,
+            attachments: Vec::new(),
+            recipient_name: "Name".to_string(),
+            kind: EmailKind::MemberReport,
+        }
+    }
+
+    #[test]
+    fn test_file_transport() {
+        let path = FileTransport::default().send(&get_an_email()).unwrap();
+        assert!(path.starts_with("./emails/member/"));
+        assert!(path.ends_with(".eml"));
+        let contents = std::fs::read_to_string(path).unwrap();
+        assert!(contents.contains("\r\nTo: \"Name\" <member@pdx.edu>\r\n"));
+    }
+
+    #[test]
+    fn test_file_transport_output_path() {
+        let dir = "./test_output/nested";
+        let _ = std::fs::remove_dir_all("./test_output");
+        let transport = FileTransport::new(dir);
+        let path = transport.send(&get_an_email()).unwrap();
+        assert!(path.starts_with("./test_output/nested/member/"));
+        assert!(std::path::Path::new(&path).is_file());
+        let _ = std::fs::remove_dir_all("./test_output");
+    }
+
+    #[test]
+    fn test_maildir_transport() {
+        let dir = "./test_maildir";
+        let _ = std::fs::remove_dir_all(dir);
+        let transport = MaildirTransport::new(dir);
+        let first = transport.send(&get_an_email()).unwrap();
+        let second = transport.send(&get_an_email()).unwrap();
+        assert_ne!(first, second);
+        assert!(first.starts_with("./test_maildir/new/"));
+        let contents = std::fs::read_to_string(&first).unwrap();
+        assert!(contents.contains("Subject: Member Report for Name\n"));
+        let tmp = std::path::Path::new(dir).join("tmp");
+        assert!(tmp.read_dir().unwrap().next().is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_mbox_transport_quotes_from_lines() {
+        let path = "./test_mbox/chocanon.mbox";
+        let _ = std::fs::remove_dir_all("./test_mbox");
+        let transport = MboxTransport::new(path);
+        transport.send(&get_an_email()).unwrap();
+        transport.send(&get_an_email()).unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        let _ = std::fs::remove_dir_all("./test_mbox");
+        assert!(contents.starts_with("From testing@chocan.com "));
+        assert_eq!(contents.matches("\nFrom testing@chocan.com ").count(), 1);
+        assert!(contents.contains("\n>From the desk of ChocAn\n"));
+    }
+
+    /// Starts a stand-in SMTP server that accepts one email and returns the
+    /// commands and data it received.
+    fn start_smtp_stand_in() -> (u16, std::thread::JoinHandle<Vec<String>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut received = Vec::new();
+            writer.write_all(b"220 stand-in ready\r\n").unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end_matches("\r\n").to_string();
+                received.push(line.clone());
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-stand-in\r\n250 8BITMIME\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                writer.write_all(reply).unwrap();
+            }
+            received
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn test_smtp_transport() {
+        let (port, handle) = start_smtp_stand_in();
+        let transport = SmtpTransport::new("127.0.0.1", port);
+        let sent = transport.send(&get_an_email()).unwrap();
+        assert_eq!(sent, format!("smtp://127.0.0.1:{}/member@pdx.edu", port));
+        let received = handle.join().unwrap();
+        assert_eq!(received[0], "EHLO chocanon");
+        assert_eq!(received[1], "MAIL FROM:<testing@chocan.com>");
+        assert_eq!(received[2], "RCPT TO:<member@pdx.edu>");
+        assert!(
+            received.contains(&"To: \"Name\" <member@pdx.edu>".to_string())
+        );
+        // The lone dot in the body is doubled.
+        assert!(received.contains(&"..".to_string()));
+        assert_eq!(received.last().unwrap(), "QUIT");
+    }
+
+    #[test]
+    fn test_parse_transport() {
+        assert!(parse_transport("file").is_ok());
+        assert!(parse_transport("file:/tmp/chocanon").is_ok());
+        assert!(parse_transport("maildir:./mail").is_ok());
+        assert!(parse_transport("mbox:./chocanon.mbox").is_ok());
+        assert!(parse_transport("smtp://localhost").is_ok());
+        assert!(parse_transport("smtp://localhost:2525").is_ok());
+        for spec in ["smtp://localhost:port", "smtp://", "pigeon"] {
+            if parse_transport(spec).is_ok() {
+                panic!("Expected error for transport: {}", spec)
+            }
+        }
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("Ann Lee"), "Ann_Lee");
+        assert_eq!(sanitize_file_name("../a/b"), "_a_b");
+        assert_eq!(sanitize_file_name(".hidden"), "hidden");
+        assert_eq!(sanitize_file_name("Jos\u{e9}"), "Jos_");
+        assert_eq!(sanitize_file_name(""), "unnamed");
+    }
+
+    #[test]
+    fn test_write_email_file_never_replaces() {
+        let dir = Path::new("./test_unique_files");
+        let _ = std::fs::remove_dir_all(dir);
+        let first = write_email_file("first", "A/B", dir).unwrap();
+        let second = write_email_file("second", "A/B", dir).unwrap();
+        assert_ne!(first, second);
+        assert!(first.starts_with("./test_unique_files/A_B_"));
+        assert_eq!(std::fs::read_to_string(&first).unwrap(), "first");
+        assert_eq!(std::fs::read_to_string(&second).unwrap(), "second");
+        let _ = std::fs::remove_dir_all(dir);
+    }
 }
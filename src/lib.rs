@@ -16,10 +16,47 @@
 //! Chocaholics Anonymous project.
 //#![warn(clippy::pedantic)]
 
+pub mod access;
+
+pub mod auth;
+
+pub mod cli;
+
+pub mod client;
+
+pub mod config;
+
 pub mod db;
 
+pub mod eft;
+
 pub mod esend;
 
+#[cfg(feature = "http")]
+pub mod http;
+
+pub mod manifest;
+
+pub mod mime;
+
 pub mod mterm;
 
+pub mod nacha;
+
+pub mod ops;
+
+pub mod prompt;
+
 pub mod pterm;
+
+pub mod render;
+
+pub mod report;
+
+pub mod sched;
+
+pub mod server;
+
+pub mod template;
+
+pub mod tui;
@@ -15,9 +15,16 @@
 
 //! Chocaholics Anonymous project.
 
+use chocanon::access::{Actor, Role};
+use chocanon::config::{Config, OPTIONS_USAGE};
 use chocanon::db::{Consultation, DB, LocationInfo, PersonInfo};
-use chocanon::{mterm, pterm};
-use std::io::{self, Write};
+#[cfg(feature = "http")]
+use chocanon::http::HttpServer;
+use chocanon::sched::Scheduler;
+use chocanon::server::Server;
+use chocanon::template::Templates;
+use chocanon::{cli, mterm, prompt, pterm, tui};
+use std::io::{self, BufRead, Write};
 
 #[derive(PartialEq, Debug)]
 enum MenuOption {
@@ -25,89 +32,322 @@ enum MenuOption {
     ProviderTerminal,
     ManagerTerminal,
     PopulateDatabase,
+    FullScreenManagerTerminal,
 }
 
-const DB_PATH: &str = "./chocanon.db3";
+const USAGE: &str = "Usage: chocanon [OPTIONS] [--scheduler]
+       chocanon [OPTIONS] --serve
+       chocanon [OPTIONS] --http
+       chocanon [OPTIONS] --check-templates [DIR]
+       chocanon [OPTIONS] COMMAND";
 
 fn main() {
-    let db = match DB::new(DB_PATH) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (config, args) = match Config::load(&args) {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            eprintln!("{}\nOPTIONS: {}", USAGE, OPTIONS_USAGE);
+            std::process::exit(cli::EXIT_USAGE);
+        }
+    };
+    let transport = match config.email_transport() {
+        Ok(transport) => transport,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(2);
+        }
+    };
+    let db = match DB::with_config(&config.db_path, transport, config.clone()) {
         Ok(db) => db,
         Err(err) => panic!("Error: {}", err),
     };
+    if args.first().is_some_and(|arg| arg == "--check-templates") {
+        check_templates(&config, &args[1..]);
+        return;
+    }
+    if cli::is_command(&args) {
+        run_command(&db, &config, &args);
+        return;
+    }
+    if matches!(args.as_slice(), [flag] if flag == "--serve") {
+        run_server(db, &config);
+        return;
+    }
+    if matches!(args.as_slice(), [flag] if flag == "--http") {
+        run_http(db, &config);
+        return;
+    }
+    if !args.is_empty() {
+        run_scheduler(&db, &config, &args);
+        return;
+    }
+    prompt::reads_stdin();
+    let mut input = io::stdin().lock();
+    let result = run_menu(&db, &config, &mut input, &mut io::stdout());
+    if let Err(err) = prompt::end_on_eof(result) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Runs the start menu and the terminals chosen from it until quit.
+///
+/// # Arguments
+///
+/// * `db` - The database the terminals interact with.
+/// * `config` - The configuration.
+/// * `input` - Where the user's input is read from.
+/// * `out` - Where the menu and terminals are written to.
+///
+/// # Failure
+///
+/// Will return `Err` if the input ends before quitting, or the output could
+/// not be written.
+fn run_menu(
+    db: &DB,
+    config: &Config,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
     loop {
-        print_menu();
-        let option = get_valid_user_input();
+        print_menu(out)?;
+        let option = get_valid_user_input(input, out)?;
         match option {
             MenuOption::Quit => {
-                println!("Quitting");
-                break;
+                writeln!(out, "Quitting")?;
+                return Ok(());
             }
             MenuOption::ProviderTerminal => {
-                println!("Chose provider terminal");
-                pterm::run(&db);
+                writeln!(out, "Chose provider terminal")?;
+                pterm::run(db, input, out)?;
             }
             MenuOption::ManagerTerminal => {
-                println!("Chose manager terminal");
-                mterm::run_man_term(&db);
+                writeln!(out, "Chose manager terminal")?;
+                mterm::run_man_term(db, config, input, out)?;
             }
             MenuOption::PopulateDatabase => {
-                println!("Populating database");
-                populate_database(&db);
+                writeln!(out, "Populating database")?;
+                if let Some(actor) = mterm::login(db, input, out)? {
+                    populate_database(db, &actor, out)?;
+                }
+            }
+            MenuOption::FullScreenManagerTerminal => {
+                writeln!(out, "Chose full-screen manager terminal")?;
+                if let Some(actor) = mterm::login(db, input, out)? {
+                    tui::run(db, &actor)?;
+                }
+            }
+        }
+    }
+}
+
+/// Runs a non-interactive command, printing its result as JSON and exiting
+/// with its exit code.
+///
+/// # Arguments
+///
+/// * `db` - The database to run the command on.
+/// * `config` - The configuration.
+/// * `args` - The command and its arguments.
+fn run_command(db: &DB, config: &Config, args: &[String]) {
+    let password = std::env::var(cli::PASSWORD_VAR).ok();
+    match cli::run(
+        db,
+        config,
+        args,
+        password.as_deref(),
+        &mut io::stdin().lock(),
+    ) {
+        Ok(output) => println!("{}", output),
+        Err(err) => {
+            eprintln!("{}", err.to_json());
+            if let cli::CliError::Usage(_) = err {
+                eprintln!("COMMANDS: {}", cli::COMMANDS_USAGE);
             }
+            std::process::exit(err.exit_code());
+        }
+    }
+}
+
+/// Runs the weekly accounting procedure on a schedule until killed.
+///
+/// # Arguments
+///
+/// * `db` - The database to run the accounting procedure on.
+/// * `config` - The configuration, which has the schedule.
+/// * `args` - The command line arguments, not including the program name or
+///   configuration options.
+fn run_scheduler(db: &DB, config: &Config, args: &[String]) {
+    if !matches!(args, [flag] if flag == "--scheduler") {
+        eprintln!("{}\nOPTIONS: {}", USAGE, OPTIONS_USAGE);
+        std::process::exit(2);
+    }
+    if let Err(err) = Scheduler::new(db, config.schedule).run() {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Answers networked provider terminals until killed.
+///
+/// # Arguments
+///
+/// * `db` - The database the terminals interact with.
+/// * `config` - The configuration, which has the address to listen on.
+fn run_server(db: DB, config: &Config) {
+    let server = match Server::bind(&config.listen, db) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("Error listening on {}: {}", config.listen, err);
+            std::process::exit(1);
+        }
+    };
+    println!("Provider server listening on {}", config.listen);
+    if let Err(err) = server.run() {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Answers the HTTP JSON API until killed.
+///
+/// # Arguments
+///
+/// * `db` - The database the API interacts with.
+/// * `config` - The configuration, which has the address to listen on.
+#[cfg(feature = "http")]
+fn run_http(db: DB, config: &Config) {
+    let server = match HttpServer::bind(&config.http_listen, db, config.clone())
+    {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("Error listening on {}: {}", config.http_listen, err);
+            std::process::exit(1);
+        }
+    };
+    println!("HTTP API listening on {}", config.http_listen);
+    if let Err(err) = server.run() {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Exits with an error, as the HTTP JSON API was not built.
+#[cfg(not(feature = "http"))]
+fn run_http(_db: DB, _config: &Config) {
+    eprintln!(
+        "Error: built without the HTTP API, rebuild with --features http"
+    );
+    std::process::exit(2);
+}
+
+/// Checks that the report templates are valid and exits with an error if
+/// they are not.
+///
+/// # Arguments
+///
+/// * `config` - The configuration, which has the template directory.
+/// * `args` - The arguments after `--check-templates`.
+fn check_templates(config: &Config, args: &[String]) {
+    let dir = match args {
+        [] => config.templates_path.as_str(),
+        [dir] => dir.as_str(),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    match Templates::load(dir) {
+        Ok(_) => println!("Templates in {} are valid", dir),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
         }
     }
 }
 
-fn print_menu() {
-    print!(
+fn print_menu(out: &mut impl Write) -> io::Result<()> {
+    write!(
+        out,
         "\n---ChocAn Start Menu---
 Enter a number corresponding to the option
 ( 0 ) Quit
 ( 1 ) Provider Terminal
 ( 2 ) Manager Terminal
 ( 3 ) Populate database with some values
+( 4 ) Manager Terminal (full screen)
 Choice: "
-    );
-    io::stdout().flush().expect("Failed to flush stdout");
+    )?;
+    out.flush()
 }
 
-fn get_valid_user_input() -> MenuOption {
-    let mut valid_input = false;
-    let mut number: u32 = 0;
-
-    while !valid_input {
-        let mut input = String::new();
-        std::io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
-        let input = input.trim();
-
-        match input.parse::<u32>() {
-            Ok(n) => {
-                valid_input = true;
-                number = n;
-            }
-            Err(_) => println!("Invalid input. Try again"),
-        }
-    }
+fn get_valid_user_input(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<MenuOption> {
+    let number = prompt::ask("", input, out, |line| {
+        line.parse::<u32>().map_err(|_| "Invalid input".to_string())
+    })?;
+    let Some(number) = number else {
+        return Ok(MenuOption::Quit);
+    };
 
     if number == 0 {
-        return MenuOption::Quit;
+        return Ok(MenuOption::Quit);
     }
     if number == 1 {
-        return MenuOption::ProviderTerminal;
+        return Ok(MenuOption::ProviderTerminal);
     }
     if number == 2 {
-        return MenuOption::ManagerTerminal;
+        return Ok(MenuOption::ManagerTerminal);
     }
     if number == 3 {
-        MenuOption::PopulateDatabase
+        return Ok(MenuOption::PopulateDatabase);
+    }
+    if number == 4 {
+        Ok(MenuOption::FullScreenManagerTerminal)
     } else {
-        MenuOption::Quit
+        Ok(MenuOption::Quit)
     }
 }
 
-fn populate_database(db: &DB) {
+/// Fills an empty database with sample data, made as a logged in manager.
+/// A database that already has data is left as is, and a provider's PIN is
+/// never replaced.
+///
+/// # Arguments
+///
+/// * `db` - The database to fill.
+/// * `actor` - The operator who chose to fill it, who must be a manager.
+/// * `out` - Where the result is written to.
+///
+/// # Failure
+///
+/// Will return `Err` if the output could not be written.
+fn populate_database(
+    db: &DB,
+    actor: &Actor,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    if actor.role() != Role::Manager {
+        writeln!(out, "Only a manager can populate the database.")?;
+        return Ok(());
+    }
+    match db.is_empty() {
+        Ok(true) => (),
+        Ok(false) => {
+            writeln!(
+                out,
+                "The database already has data, so it was left as is."
+            )?;
+            return Ok(());
+        }
+        Err(err) => {
+            writeln!(out, "Error checking the database: {}", err)?;
+            return Ok(());
+        }
+    }
     fn create_a_unique_person(name: &str, id: u32) -> PersonInfo {
         let location =
             LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
@@ -137,31 +377,99 @@ fn populate_database(db: &DB) {
         .unwrap()
     }
 
-    let _ = db.add_service(123456, "ServiceName123456", 99.99);
-    let _ = db.add_service(1, "ServiceName1", 1.99);
-    let _ = db.add_service(2, "ServiceName2", 2.99);
-    let _ = db.add_service(3, "ServiceName3", 3.99);
-    let _ = db.add_service(4, "ServiceName4", 4.99);
-    let _ = db.add_service(5, "ServiceName5", 5.99);
-    let _ = db.add_member(&create_a_unique_person("MemberName1", 1));
-    let _ = db.add_member(&create_a_unique_person("MemberName2", 2));
-    let _ = db.add_member(&create_a_unique_person("MemberName3", 3));
-    let _ = db.add_member(&create_a_unique_person("MemberName4", 4));
-    let _ = db.add_provider(&create_a_unique_person("ProviderName1", 1));
-    let _ = db.add_provider(&create_a_unique_person("ProviderName2", 2));
-    let _ = db.add_provider(&create_a_unique_person("ProviderName3", 3));
-    let _ = db.add_provider(&create_a_unique_person("ProviderName4", 4));
-    let _ = db.add_provider(&create_a_unique_person("ProviderName5", 5));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(1, 1));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(2, 1));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(2, 1));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(3, 1));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(3, 1));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(3, 1));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(1, 2));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(2, 2));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(2, 2));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(3, 2));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(3, 2));
-    let _ = db.add_consultation_record(&create_a_unique_consultation(3, 2));
+    let _ = db.add_service(actor, 123456, "ServiceName123456", 99.99);
+    let _ = db.add_service(actor, 1, "ServiceName1", 1.99);
+    let _ = db.add_service(actor, 2, "ServiceName2", 2.99);
+    let _ = db.add_service(actor, 3, "ServiceName3", 3.99);
+    let _ = db.add_service(actor, 4, "ServiceName4", 4.99);
+    let _ = db.add_service(actor, 5, "ServiceName5", 5.99);
+    let _ = db.add_member(actor, &create_a_unique_person("MemberName1", 1));
+    let _ = db.add_member(actor, &create_a_unique_person("MemberName2", 2));
+    let _ = db.add_member(actor, &create_a_unique_person("MemberName3", 3));
+    let _ = db.add_member(actor, &create_a_unique_person("MemberName4", 4));
+    let _ = db.add_provider(actor, &create_a_unique_person("ProviderName1", 1));
+    let _ = db.add_provider(actor, &create_a_unique_person("ProviderName2", 2));
+    let _ = db.add_provider(actor, &create_a_unique_person("ProviderName3", 3));
+    let _ = db.add_provider(actor, &create_a_unique_person("ProviderName4", 4));
+    let _ = db.add_provider(actor, &create_a_unique_person("ProviderName5", 5));
+    for id in 1..=5 {
+        if db.has_provider_pin(id).is_ok_and(|has_pin| !has_pin) {
+            let _ = db.set_provider_pin(actor, id, "1234");
+        }
+    }
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(1, 1));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(2, 1));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(2, 1));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(3, 1));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(3, 1));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(3, 1));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(1, 2));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(2, 2));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(2, 2));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(3, 2));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(3, 2));
+    let _ =
+        db.add_consultation_record(actor, &create_a_unique_consultation(3, 2));
+    writeln!(out, "The sample providers log in with the PIN 1234.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_populate_database_only_when_empty() {
+        let path = "./test_populate.db3";
+        let _ = std::fs::remove_file(path);
+        let db = DB::new(path).unwrap();
+        let actor = db.add_first_operator("manager", "password1").unwrap();
+        let mut out = Vec::new();
+        populate_database(&db, &actor, &mut out).unwrap();
+        db.login_provider(1, "1234").unwrap();
+
+        db.set_provider_pin(&actor, 1, "5678").unwrap();
+        let mut out = Vec::new();
+        populate_database(&db, &actor, &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains("already has data"));
+        db.login_provider(1, "5678").unwrap();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_get_valid_user_input_asks_again() {
+        let mut input = io::Cursor::new("abc\n2\n");
+        let mut out = Vec::new();
+        let option = get_valid_user_input(&mut input, &mut out).unwrap();
+        assert_eq!(option, MenuOption::ManagerTerminal);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "Invalid input. Try again, or enter back to cancel.\n"
+        );
+    }
+
+    #[test]
+    fn test_get_valid_user_input_back_quits() {
+        let mut input = io::Cursor::new("back\n");
+        let option = get_valid_user_input(&mut input, &mut Vec::new()).unwrap();
+        assert_eq!(option, MenuOption::Quit);
+    }
+
+    #[test]
+    fn test_get_valid_user_input_ends() {
+        let mut input = io::Cursor::new("");
+        let err =
+            get_valid_user_input(&mut input, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
 }
@@ -0,0 +1,201 @@
+/* File: manifest.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the manifest written for every report run.
+//!
+//! A manifest is a JSON index of everything a run delivered: each file, who
+//! it was for, the type of report and a SHA-256 checksum of the file, along
+//! with the recipients that could not be delivered to. For example:
+//!
+//! ```json
+//! {
+//!   "report_type": "member",
+//!   "period_start": "2026-10-10",
+//!   "period_end": "2026-10-16",
+//!   "created_at": "2026-10-17 00:00:00",
+//!   "entries": [
+//!     {
+//!       "file": "./emails/member/Ann_10-17-2026_00:00:00.eml",
+//!       "recipient": "ann@pdx.edu",
+//!       "report_type": "member",
+//!       "sha256": "9f86d081884c7d65..."
+//!     }
+//!   ],
+//!   "failed": []
+//! }
+//! ```
+//!
+//! The checksum is `null` when the report was not delivered to a file of
+//! its own, such as when it was sent over SMTP.
+use crate::esend::create_unique_file;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::Path;
+
+/// Something delivered by a report run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    /// Where the report was delivered, such as a file path.
+    pub file: String,
+    /// The email address of the recipient, if it was sent to one.
+    pub recipient: Option<String>,
+    /// The type of report that was delivered.
+    pub report_type: String,
+    /// The SHA-256 checksum of the file as hex, if it is a file of its own.
+    pub sha256: Option<String>,
+}
+
+/// An index of everything a report run delivered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    /// The type of report that was sent.
+    pub report_type: String,
+    /// The first day of the period that was reported on, as YYYY-MM-DD.
+    pub period_start: String,
+    /// The last day of the period that was reported on, as YYYY-MM-DD.
+    pub period_end: String,
+    /// When the run happened.
+    pub created_at: String,
+    /// What was delivered.
+    pub entries: Vec<ManifestEntry>,
+    /// The recipients that could not be delivered to and why.
+    pub failed: Vec<(String, String)>,
+}
+
+impl Manifest {
+    /// Converts the manifest to JSON.
+    pub fn to_json(&self) -> Value {
+        let entries: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "file": entry.file,
+                    "recipient": entry.recipient,
+                    "report_type": entry.report_type,
+                    "sha256": entry.sha256,
+                })
+            })
+            .collect();
+        let failed: Vec<Value> = self
+            .failed
+            .iter()
+            .map(|(recipient, error)| {
+                json!({ "recipient": recipient, "error": error })
+            })
+            .collect();
+        json!({
+            "report_type": self.report_type,
+            "period_start": self.period_start,
+            "period_end": self.period_end,
+            "created_at": self.created_at,
+            "entries": entries,
+            "failed": failed,
+        })
+    }
+
+    /// Writes the manifest as a new JSON file in a directory.
+    ///
+    /// The file is named after the report type and the end of the period,
+    /// and never replaces an earlier manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to write in, which is created if it does not
+    ///   exist.
+    ///
+    /// # Success
+    ///
+    /// Will return the path of the file that was written.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` for IO errors.
+    pub fn write(&self, dir: &Path) -> std::io::Result<String> {
+        let stem = format!("{}_{}", self.report_type, self.period_end);
+        let (mut file, path) = create_unique_file(dir, &stem, "json")?;
+        let text = serde_json::to_string_pretty(&self.to_json())
+            .map_err(std::io::Error::other)?;
+        writeln!(file, "{}", text)?;
+        Ok(path)
+    }
+}
+
+/// Gets the SHA-256 checksum of a file as hex.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Failure
+///
+/// Will return `Err` if the file could not be read.
+pub fn file_checksum(path: &str) -> std::io::Result<String> {
+    let contents = std::fs::read(path)?;
+    Ok(format!("{:x}", Sha256::digest(contents)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_a_manifest() -> Manifest {
+        Manifest {
+            report_type: "member".to_string(),
+            period_start: "2026-10-10".to_string(),
+            period_end: "2026-10-16".to_string(),
+            created_at: "2026-10-17 00:00:00".to_string(),
+            entries: vec![ManifestEntry {
+                file: "./emails/member/Ann.eml".to_string(),
+                recipient: Some("ann@pdx.edu".to_string()),
+                report_type: "member".to_string(),
+                sha256: None,
+            }],
+            failed: vec![("bob@pdx.edu".to_string(), "refused".to_string())],
+        }
+    }
+
+    #[test]
+    fn test_file_checksum() {
+        let dir = Path::new("./test_manifest_checksum");
+        let _ = std::fs::remove_dir_all(dir);
+        let (mut file, path) = create_unique_file(dir, "test", "txt").unwrap();
+        file.write_all(b"test").unwrap();
+        assert_eq!(
+            file_checksum(&path).unwrap(),
+            "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_write_manifest_never_replaces() {
+        let dir = Path::new("./test_manifest");
+        let _ = std::fs::remove_dir_all(dir);
+        let manifest = get_a_manifest();
+        let first = manifest.write(dir).unwrap();
+        let second = manifest.write(dir).unwrap();
+        assert!(first.ends_with("member_2026-10-16.json"));
+        assert!(second.ends_with("member_2026-10-16_2.json"));
+        let text = std::fs::read_to_string(&first).unwrap();
+        let json: Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json, manifest.to_json());
+        assert_eq!(json["entries"][0]["recipient"], "ann@pdx.edu");
+        assert_eq!(json["entries"][0]["sha256"], Value::Null);
+        assert_eq!(json["failed"][0]["error"], "refused");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
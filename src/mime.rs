@@ -0,0 +1,433 @@
+/* File: mime.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for formatting emails as RFC 5322 messages with MIME bodies.
+//!
+//! Messages use CRLF line endings and only 7 bit characters so they can be
+//! opened by any mail client or relayed unchanged. Text is encoded as
+//! quoted-printable, attachments as base64, and headers containing
+//! non-ASCII characters as RFC 2047 encoded words.
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The longest line allowed by RFC 5322, not including the CRLF.
+const MAX_LINE_LENGTH: usize = 78;
+/// The longest encoded line for quoted-printable and base64 bodies.
+const MAX_ENCODED_LENGTH: usize = 76;
+/// The most bytes of a header put in a single encoded word.
+const MAX_ENCODED_WORD_BYTES: usize = 42;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Counts the messages formatted by this process for unique identifiers.
+static MESSAGE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A file attached to an email.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    /// The name the file is saved as by the recipient.
+    pub file_name: String,
+    /// The MIME type of the file, such as `text/csv`.
+    pub content_type: String,
+    /// The contents of the file.
+    pub data: Vec<u8>,
+}
+
+/// The parts of an email to be formatted as a message.
+#[derive(Debug)]
+pub struct Message<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+    pub to_name: &'a str,
+    pub subject: &'a str,
+    pub text: &'a str,
+    pub html: Option<&'a str>,
+    pub attachments: &'a [Attachment],
+}
+
+impl Message<'_> {
+    /// Formats the message with a `Date` of now and a new `Message-ID`.
+    pub fn format(&self) -> String {
+        let date = chrono::Local::now();
+        let mut message = String::new();
+        message.push_str(&header("Date", &date.to_rfc2822()));
+        message.push_str(&header("From", &clean_address(self.from)));
+        message.push_str(&header("To", &mailbox(self.to_name, self.to)));
+        message.push_str(&header("Subject", &encode_header(self.subject)));
+        message.push_str(&header("Message-ID", &message_id(self.from, &date)));
+        message.push_str(&header("MIME-Version", "1.0"));
+
+        let counter = MESSAGE_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let mut body = text_part("text/plain", self.text);
+        if let Some(html) = self.html {
+            body = multipart(
+                "alternative",
+                &boundary("alt", counter),
+                &[body, text_part("text/html", html)],
+            );
+        }
+        if !self.attachments.is_empty() {
+            let mut parts = vec![body];
+            parts.extend(self.attachments.iter().map(attachment_part));
+            body = multipart("mixed", &boundary("mixed", counter), &parts);
+        }
+        message.push_str(&body);
+        message
+    }
+}
+
+/// Formats a header field, folding it at spaces to keep lines short.
+fn header(name: &str, value: &str) -> String {
+    let mut text = format!("{}:", name);
+    let mut line_length = text.len();
+    for (i, word) in value.split(' ').enumerate() {
+        if i > 0 && line_length + 1 + word.len() > MAX_LINE_LENGTH {
+            text.push_str("\r\n");
+            line_length = 0;
+        }
+        text.push(' ');
+        text.push_str(word);
+        line_length += 1 + word.len();
+    }
+    text.push_str("\r\n");
+    text
+}
+
+/// Formats an address with a display name, such as `"Name" <a@b.com>`.
+///
+/// A name with control characters is encoded so that a line break in it
+/// can never start another header.
+fn mailbox(name: &str, address: &str) -> String {
+    let address = clean_address(address);
+    if name.is_empty() {
+        return format!("<{}>", address);
+    }
+    if name.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("\"{}\" <{}>", name, address)
+    } else {
+        format!("{} <{}>", encode_header(name), address)
+    }
+}
+
+/// Removes control characters from an address so that it can never end the
+/// header it is in. Addresses are checked when they are entered, so this
+/// only guards against ones that were not.
+fn clean_address(address: &str) -> String {
+    address.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Creates a unique message identifier in the domain of the sender.
+fn message_id(from: &str, date: &chrono::DateTime<chrono::Local>) -> String {
+    let domain = match from.rsplit_once('@') {
+        Some((_, domain)) if !domain.is_empty() => domain,
+        _ => "chocan.com",
+    };
+    format!(
+        "<{}.{}.{}.{}@{}>",
+        date.format("%Y%m%d%H%M%S"),
+        date.timestamp_subsec_micros(),
+        std::process::id(),
+        MESSAGE_COUNTER.fetch_add(1, Ordering::Relaxed),
+        domain.trim_end_matches('>')
+    )
+}
+
+/// Creates a multipart boundary, which can never appear in quoted-printable
+/// or base64 text because of the `=_`.
+fn boundary(kind: &str, counter: usize) -> String {
+    format!("=_chocanon_{}_{}_{}", kind, std::process::id(), counter)
+}
+
+/// Formats a text body part encoded as quoted-printable.
+fn text_part(content_type: &str, text: &str) -> String {
+    format!(
+        "Content-Type: {}; charset=utf-8\r\n\
+         Content-Transfer-Encoding: quoted-printable\r\n\r\n{}",
+        content_type,
+        encode_quoted_printable(text)
+    )
+}
+
+/// Formats an attachment body part encoded as base64.
+fn attachment_part(attachment: &Attachment) -> String {
+    let file_name: String = attachment
+        .file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!(
+        "Content-Type: {}; name=\"{}\"\r\n\
+         Content-Disposition: attachment; filename=\"{}\"\r\n\
+         Content-Transfer-Encoding: base64\r\n\r\n{}",
+        attachment.content_type,
+        file_name,
+        file_name,
+        encode_base64_lines(&attachment.data)
+    )
+}
+
+/// Formats a multipart body from parts that each end with a line break.
+fn multipart(subtype: &str, boundary: &str, parts: &[String]) -> String {
+    let mut text = format!(
+        "Content-Type: multipart/{}; boundary=\"{}\"\r\n\r\n",
+        subtype, boundary
+    );
+    for part in parts {
+        text.push_str(&format!("--{}\r\n", boundary));
+        text.push_str(part);
+        if !part.ends_with("\r\n") {
+            text.push_str("\r\n");
+        }
+    }
+    text.push_str(&format!("--{}--\r\n", boundary));
+    text
+}
+
+/// Encodes a header value as RFC 2047 encoded words if it is not plain
+/// ASCII.
+///
+/// # Arguments
+///
+/// * `value` - The unencoded header value.
+pub fn encode_header(value: &str) -> String {
+    if value.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        return value.to_string();
+    }
+    let mut words = Vec::new();
+    let mut chunk = String::new();
+    for c in value.chars() {
+        if chunk.len() + c.len_utf8() > MAX_ENCODED_WORD_BYTES {
+            words.push(chunk);
+            chunk = String::new();
+        }
+        chunk.push(c);
+    }
+    words.push(chunk);
+    words
+        .iter()
+        .map(|word| format!("=?UTF-8?B?{}?=", encode_base64(word.as_bytes())))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Encodes text as quoted-printable with CRLF line breaks.
+///
+/// # Arguments
+///
+/// * `text` - The text to encode, with LF or CRLF line breaks.
+pub fn encode_quoted_printable(text: &str) -> String {
+    let mut encoded = String::new();
+    let lines: Vec<&str> = text.split('\n').collect();
+    for (i, line) in lines.iter().enumerate() {
+        let bytes = line.strip_suffix('\r').unwrap_or(line).as_bytes();
+        let mut length = 0;
+        for (j, &byte) in bytes.iter().enumerate() {
+            let is_last = j + 1 == bytes.len();
+            let literal = byte == b'\t'
+                || byte == b' '
+                || (33..=126).contains(&byte) && byte != b'=';
+            // Trailing white space would be removed by mail servers.
+            let piece =
+                if literal && !(is_last && (byte == b' ' || byte == b'\t')) {
+                    (byte as char).to_string()
+                } else {
+                    format!("={:02X}", byte)
+                };
+            // A soft line break keeps lines short, leaving room for the `=`.
+            let room = if is_last { 0 } else { 1 };
+            if length + piece.len() + room > MAX_ENCODED_LENGTH {
+                encoded.push_str("=\r\n");
+                length = 0;
+            }
+            encoded.push_str(&piece);
+            length += piece.len();
+        }
+        if i + 1 < lines.len() {
+            encoded.push_str("\r\n");
+        }
+    }
+    encoded
+}
+
+/// Encodes bytes as base64.
+///
+/// # Arguments
+///
+/// * `data` - The bytes to encode.
+pub fn encode_base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n =
+            (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (n >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Encodes bytes as base64 split into CRLF terminated lines.
+fn encode_base64_lines(data: &[u8]) -> String {
+    let encoded = encode_base64(data);
+    let mut lines = String::new();
+    for line in encoded.as_bytes().chunks(MAX_ENCODED_LENGTH) {
+        // Base64 is always ASCII.
+        lines.push_str(std::str::from_utf8(line).unwrap_or_default());
+        lines.push_str("\r\n");
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_a_message<'a>(
+        html: Option<&'a str>,
+        attachments: &'a [Attachment],
+    ) -> Message<'a> {
+        Message {
+            from: "testing@chocan.com",
+            to: "member@pdx.edu",
+            to_name: "José Núñez",
+            subject: "Member Report for José Núñez",
+            text: "Member name: José Núñez\nFee: 10.50\n",
+            html,
+            attachments,
+        }
+    }
+
+    fn assert_short_ascii_lines(message: &str) {
+        assert!(message.is_ascii());
+        for line in message.split("\r\n") {
+            assert!(!line.contains('\n'), "bare line feed in: {}", line);
+            assert!(line.len() <= MAX_LINE_LENGTH, "too long: {}", line);
+        }
+    }
+
+    #[test]
+    fn test_encode_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64("é".as_bytes()), "w6k=");
+    }
+
+    #[test]
+    fn test_encode_quoted_printable() {
+        assert_eq!(
+            encode_quoted_printable("a=b café \nend\t\n"),
+            "a=3Db caf=C3=A9=20\r\nend=09\r\n"
+        );
+        let long = "x".repeat(100);
+        let encoded = encode_quoted_printable(&long);
+        assert_eq!(
+            encoded,
+            format!("{}=\r\n{}", "x".repeat(75), "x".repeat(25))
+        );
+    }
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("Member Report"), "Member Report");
+        assert_eq!(encode_header("José"), "=?UTF-8?B?Sm9zw6k=?=");
+        let long = encode_header(&"é".repeat(40));
+        assert_eq!(long.matches("=?UTF-8?B?").count(), 2);
+        for word in long.split(' ') {
+            assert!("Subject: ".len() + word.len() <= MAX_LINE_LENGTH);
+        }
+    }
+
+    #[test]
+    fn test_mailbox_cannot_add_headers() {
+        assert_eq!(
+            mailbox("Ann \"A\"", "ann@pdx.edu"),
+            "\"Ann \\\"A\\\"\" <ann@pdx.edu>"
+        );
+        let name = mailbox("Ann\r\nBcc: eve@evil.com", "ann@pdx.edu");
+        assert!(name.starts_with("=?UTF-8?B?"));
+        assert!(!name.contains('\n'));
+        let address = mailbox("Ann", "ann@pdx.edu\r\nBcc: eve@evil.com");
+        assert_eq!(address, "\"Ann\" <ann@pdx.eduBcc: eve@evil.com>");
+    }
+
+    #[test]
+    fn test_plain_message_headers() {
+        let message = get_a_message(None, &[]).format();
+        assert_short_ascii_lines(&message);
+        assert!(message.starts_with("Date: "));
+        assert!(message.contains(
+            "\r\nTo: =?UTF-8?B?Sm9zw6kgTsO6w7Fleg==?= <member@pdx.edu>\r\n"
+        ));
+        assert!(message.contains("\r\nMessage-ID: <"));
+        assert!(message.contains("@chocan.com>\r\n"));
+        assert!(message.contains("\r\nMIME-Version: 1.0\r\n"));
+        assert!(message.contains(
+            "Content-Type: text/plain; charset=utf-8\r\n\
+             Content-Transfer-Encoding: quoted-printable\r\n\r\n\
+             Member name: Jos=C3=A9 N=C3=BA=C3=B1ez\r\nFee: 10.50\r\n"
+        ));
+        let date = message.lines().next().unwrap().trim_start_matches("Date: ");
+        assert!(chrono::DateTime::parse_from_rfc2822(date).is_ok());
+    }
+
+    #[test]
+    fn test_multipart_message() {
+        let attachments = [Attachment {
+            file_name: "report.csv".to_string(),
+            content_type: "text/csv".to_string(),
+            data: b"Fee\r\n10.50\r\n".to_vec(),
+        }];
+        let message =
+            get_a_message(Some("<p>Jos\u{e9}</p>\n"), &attachments).format();
+        assert_short_ascii_lines(&message);
+        assert!(message.contains("Content-Type: multipart/mixed; boundary="));
+        assert!(
+            message.contains("Content-Type: multipart/alternative; boundary=")
+        );
+        assert!(message.contains("Content-Type: text/html; charset=utf-8"));
+        assert!(message.contains("<p>Jos=C3=A9</p>"));
+        assert!(message.contains(
+            "Content-Disposition: attachment; filename=\"report.csv\"\r\n\
+             Content-Transfer-Encoding: base64\r\n\r\nRmVlDQoxMC41MA0K\r\n"
+        ));
+        // Every opened boundary is closed.
+        for line in message.lines() {
+            if let Some(b) = line.strip_prefix("Content-Type: multipart/") {
+                let boundary = b.split('"').nth(1).unwrap();
+                assert!(message.contains(&format!("--{}--\r\n", boundary)));
+            }
+        }
+    }
+}
@@ -14,181 +14,964 @@
  */
 
 //! Module for the manager terminal.
-use crate::db::{DB, LocationInfo, PersonInfo};
-use std::io::{self, Write};
+use crate::access::{Actor, Role};
+use crate::auth;
+use crate::config::Config;
+use crate::db::{
+    AccountType, BankInfo, DB, DeliverySummary, Error, LocationInfo,
+    OutboxStatus, PersonInfo, ReportPeriod, ReportType,
+};
+use crate::prompt;
+use crate::render::Format;
+use std::io::{self, BufRead, Write};
 
 //driver function that initializes the manager terminal
+//an operator must log in before any screen is shown
+//every change is made as the operator, so their role decides what they can do
 //param DB - database to be passed to the manager terminal
-pub fn run_man_term(db: &DB) {
+//param config - configuration with the NACHA originator
+//param input - where the user's input is read from
+//param out - where the terminal is written to
+//ends cleanly when the input ends
+//returns an error if the output cannot be written
+pub fn run_man_term(
+    db: &DB,
+    config: &Config,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    prompt::end_on_eof(run_logged_in(db, config, input, out))
+}
+
+//logs an operator in, then runs the screens
+fn run_logged_in(
+    db: &DB,
+    config: &Config,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(actor) = login(db, input, out)? else {
+        return Ok(());
+    };
+    run_screens(db, &actor, config, input, out)
+}
+
+//asks for an operator's username and password until they log in
+//param DB - database with the operator accounts
+//returns the operator to make changes as, or None if cancelled or there
+//are no accounts
+//returns an error if the input ends
+pub fn login(
+    db: &DB,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<Actor>> {
+    match db.get_operators() {
+        Ok(operators) if operators.is_empty() => {
+            writeln!(out, "No operator accounts exist.")?;
+            writeln!(out, "Add one with: chocanon operator add USERNAME")?;
+            return Ok(None);
+        }
+        Ok(_) => (),
+        Err(e) => {
+            writeln!(out, "Error checking operator accounts: {e}")?;
+            return Ok(None);
+        }
+    }
+    writeln!(out, "----MANAGER LOGIN----")?;
+    loop {
+        let Some(username) = prompt::text("Username: ", input, out)? else {
+            return Ok(None);
+        };
+        let Some(password) = prompt::secret("Password: ", input, out)? else {
+            return Ok(None);
+        };
+        match db.login_operator(&username, &password) {
+            Ok(actor) => {
+                writeln!(out, "Logged in as {username} ({}).", actor.role())?;
+                return Ok(Some(actor));
+            }
+            Err(Error::LoginFailed) => {
+                writeln!(out, "Invalid username or password.")?
+            }
+            Err(Error::LockedOut(until)) => writeln!(
+                out,
+                "Too many invalid passwords, locked out until {}.",
+                until.format("%m-%d-%Y %H:%M:%S")
+            )?,
+            Err(e) => writeln!(out, "Error logging in: {e}")?,
+        }
+    }
+}
+
+//shows the menu and runs the chosen screens until quit
+//param actor - the logged in operator
+fn run_screens(
+    db: &DB,
+    actor: &Actor,
+    config: &Config,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
     let mut quit = false;
     while !quit {
-        let choice = display_options();
+        let choice = display_options(input, out)?;
 
         match choice.as_str() {
             "0" => quit = true,
-            "1" => add_person_ui(db),
-            "2" => remove_person(db),
-            "3" => match db.send_member_reports() {
-                Ok(()) => println!("Member reports sent."),
-                Err(e) => eprintln!("Error sending member reports: {e}"),
-            },
-            "4" => match db.send_provider_reports() {
-                Ok(()) => println!("Provider reports sent."),
-                Err(e) => eprintln!("Error sending provider reports: {e}"),
-            },
-            "5" => match db.send_manager_report() {
-                Ok(()) => println!("Manager report sent."),
-                Err(e) => eprintln!("Error sending manager report: {e}"),
-            },
-            "6" => add_service(db),
-            _ => println!("Invalid input."),
+            "1" => add_person_ui(db, actor, input, out)?,
+            "2" => remove_person(db, actor, input, out)?,
+            "3" => send_report_ui(
+                "Member reports",
+                |period, force| db.send_member_reports(actor, period, force),
+                input,
+                out,
+            )?,
+            "4" => send_provider_reports_ui(db, actor, input, out)?,
+            "5" => send_report_ui(
+                "Manager report",
+                |period, force| db.send_manager_report(actor, period, force),
+                input,
+                out,
+            )?,
+            "6" => add_service(db, actor, input, out)?,
+            "7" => send_report_ui(
+                "EFT records",
+                |period, force| db.send_eft_records(actor, period, force),
+                input,
+                out,
+            )?,
+            "8" => set_bank_info_ui(db, actor, input, out)?,
+            "9" => send_report_ui(
+                "NACHA ACH file",
+                |period, force| {
+                    db.send_nacha_file(actor, period, &config.originator, force)
+                },
+                input,
+                out,
+            )?,
+            "10" => list_report_runs(db, out)?,
+            "11" => set_report_format_ui(db, actor, input, out)?,
+            "12" => set_csv_attachment_ui(db, actor, input, out)?,
+            "13" => list_outbox(db, input, out)?,
+            "14" => resend_failed_ui(db, actor, input, out)?,
+            "15" => preview_report_ui(db, input, out)?,
+            "16" => manage_credentials_ui(db, actor, input, out)?,
+            _ => writeln!(out, "Invalid input.")?,
         }
     }
+    Ok(())
 }
 
 //displays options for the manager terminal
 //returns string
-fn display_options() -> String {
-    println!("----MANAGER TERMINAL----");
-    println!("0. Quit");
-    println!("1. Add new person");
-    println!("2. Remove person");
-    println!("3. Send out member reports");
-    println!("4. Send out provider reports");
-    println!("5. Request manager report");
-    println!("6. Add a service");
-    read_choice()
-}
-
-//reads the choice of the user as string
-fn read_choice() -> String {
-    use std::io::{self, Write};
-
-    print!("Select an option: ");
-    io::stdout().flush().unwrap();
-
-    let mut buf = String::new();
-    io::stdin().read_line(&mut buf).expect("Invalid input");
-    buf.trim().to_string()
+fn display_options(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<String> {
+    writeln!(out, "----MANAGER TERMINAL----")?;
+    writeln!(out, "0. Quit")?;
+    writeln!(out, "1. Add new person")?;
+    writeln!(out, "2. Remove person")?;
+    writeln!(out, "3. Send out member reports")?;
+    writeln!(out, "4. Send out provider reports and EFT records")?;
+    writeln!(out, "5. Request manager report")?;
+    writeln!(out, "6. Add a service")?;
+    writeln!(out, "7. Write EFT records for providers")?;
+    writeln!(out, "8. Set provider bank account")?;
+    writeln!(out, "9. Write NACHA ACH file for providers")?;
+    writeln!(out, "10. List past report runs")?;
+    writeln!(out, "11. Set report format")?;
+    writeln!(out, "12. Set CSV attachment for reports")?;
+    writeln!(out, "13. View email outbox")?;
+    writeln!(out, "14. Re-send unsent emails")?;
+    writeln!(out, "15. Preview reports without sending")?;
+    writeln!(out, "16. Manage PINs and operator accounts")?;
+    writeln!(out, "Enter {} at any prompt to return here.", prompt::BACK)?;
+    prompt::line("Select an option: ", input, out)
 }
 
 //adds member to the database
 //param DB - database to add the member too
-fn add_person_ui(db: &DB) {
-    println!("----Add New Person----");
-    let name = read_line("Name: ");
-    let id_str = read_line("9 digit ID: ");
-    let address = read_line("Street address: ");
-    let city = read_line("City name: ");
-    let state = read_line("State (2 letters): ");
-    let zip_str = read_line("5 digit zip: ");
-    let email = read_line("Email: ");
-
-    let id: u32 = match id_str.parse() {
-        Ok(v) => v,
-        Err(_) => {
-            eprintln!("Invalid id, enter valid 9 digit id number");
-            return;
-        }
+fn add_person_ui(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "----Add New Person----")?;
+    let Some(name) = prompt::text("Name: ", input, out)? else {
+        return Ok(());
+    };
+    let Some(id) = prompt::id("9 digit ID: ", input, out)? else {
+        return Ok(());
+    };
+    let Some(address) = prompt::text("Street address: ", input, out)? else {
+        return Ok(());
+    };
+    let Some(city) = prompt::text("City name: ", input, out)? else {
+        return Ok(());
+    };
+    //set state input to 2 uppercase chars
+    let Some(state) =
+        prompt::ask("State (2 letters): ", input, out, |state| {
+            if state.chars().count() == 2 {
+                Ok(state.to_uppercase())
+            } else {
+                Err("State must be 2 characters".to_string())
+            }
+        })?
+    else {
+        return Ok(());
+    };
+    let Some(zipcode) = prompt::id("5 digit zip: ", input, out)? else {
+        return Ok(());
+    };
+    let Some(email) = prompt::text("Email: ", input, out)? else {
+        return Ok(());
     };
 
-    let zipcode: u32 = match zip_str.parse() {
-        Ok(v) => v,
-        Err(_) => {
-            eprintln!("Invalid zip, enter a valid 5 digit number");
-            return;
+    let location = match LocationInfo::new(&address, &city, &state, zipcode) {
+        Ok(loc) => loc,
+        Err(msg) => {
+            writeln!(out, "Location error {msg}")?;
+            return Ok(());
         }
     };
 
-    //set state input to an array of 2 uppercase chars
-    if state.chars().count() != 2 {
-        eprintln!("State must be 2 characters.");
-        return;
-    }
-    let state_upper = state.trim().to_uppercase();
-
-    let location =
-        match LocationInfo::new(&address, &city, &state_upper, zipcode) {
-            Ok(loc) => loc,
-            Err(msg) => {
-                eprintln!("Location error {msg}");
-                return;
-            }
-        };
-
     let person = match PersonInfo::new(&name, id, &location, &email) {
         Ok(p) => p,
         Err(msg) => {
-            eprintln!("Person error {msg}");
-            return;
+            writeln!(out, "Person error {msg}")?;
+            return Ok(());
         }
     };
 
     //determine if user is adding member or provider
-    let person_type = read_line("Is this person a provider? (y/n): ");
-    if person_type.to_lowercase().starts_with('y') {
-        match db.add_provider(&person) {
-            Ok(_) => println!("Provider was successfully added."),
-            Err(e) => eprintln!("Error adding provider: {e}"),
+    let Some(is_provider) =
+        prompt::yes_no("Is this person a provider? (y/n): ", input, out)?
+    else {
+        return Ok(());
+    };
+    if is_provider {
+        match db.add_provider(actor, &person) {
+            Ok(_) => writeln!(out, "Provider was successfully added.")?,
+            Err(e) => writeln!(out, "Error adding provider: {e}")?,
         }
     } else {
-        match db.add_member(&person) {
-            Ok(_) => println!("Member was successfully added."),
-            Err(e) => eprintln!("Error adding member: {e}"),
+        match db.add_member(actor, &person) {
+            Ok(_) => writeln!(out, "Member was successfully added.")?,
+            Err(e) => writeln!(out, "Error adding member: {e}")?,
         }
     }
+    Ok(())
 }
 //removes member based off of the member id
-fn remove_person(db: &DB) {
-    let person_type = read_line("Is this person a provider? (y/n)");
-    if person_type.to_lowercase().starts_with('y') {
-        let id_str: String = read_line("Enter the provider ID to be removed: ");
-        let id: u32 = match id_str.trim().parse() {
-            Ok(n) => n,
-            Err(_) => {
-                eprintln!("ID is invalid, please enter valid 9 digit number.");
-                return;
-            }
+fn remove_person(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(is_provider) =
+        prompt::yes_no("Is this person a provider? (y/n): ", input, out)?
+    else {
+        return Ok(());
+    };
+    if is_provider {
+        let Some(id) =
+            prompt::id("Enter the provider ID to be removed: ", input, out)?
+        else {
+            return Ok(());
         };
-        match db.remove_provider(id) {
-            Ok(()) => println!("Provider removed successfully."),
-            Err(e) => eprintln!("Error removing provider: {e}"),
+        match db.remove_provider(actor, id) {
+            Ok(()) => writeln!(out, "Provider removed successfully.")?,
+            Err(e) => writeln!(out, "Error removing provider: {e}")?,
         }
     } else {
-        let id_str: String = read_line("Enter the member ID to remove: ");
-        let id: u32 = match id_str.trim().parse() {
-            Ok(n) => n,
-            Err(_) => {
-                eprintln!("ID is invalid, please enter valid 9 digit number.");
-                return;
+        let Some(id) =
+            prompt::id("Enter the member ID to remove: ", input, out)?
+        else {
+            return Ok(());
+        };
+        match db.remove_member(actor, id) {
+            Ok(()) => writeln!(out, "Member removed successfully.")?,
+            Err(e) => writeln!(out, "Error removing Member: {e}")?,
+        }
+    }
+    Ok(())
+}
+
+//sends a report for a period read from the user
+//param name - name of the report for messages
+//param send - sends the report for a period, forced or not
+fn send_report_ui<F>(
+    name: &str,
+    send: F,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()>
+where
+    F: Fn(&ReportPeriod, bool) -> Result<DeliverySummary, Error>,
+{
+    let Some(period) = read_period(input, out)? else {
+        return Ok(());
+    };
+    send_period_report(name, send, &period, input, out)
+}
+
+//sends the provider reports for a period read from the user, then writes
+//the EFT records paying those providers for the same period
+fn send_provider_reports_ui(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(period) = read_period(input, out)? else {
+        return Ok(());
+    };
+    send_period_report(
+        "Provider reports",
+        |period, force| db.send_provider_reports(actor, period, force),
+        &period,
+        input,
+        out,
+    )?;
+    send_period_report(
+        "EFT records",
+        |period, force| db.send_eft_records(actor, period, force),
+        &period,
+        input,
+        out,
+    )
+}
+
+//sends a report for a period
+//asks before sending a period that was already sent
+//lists every recipient the report could not be delivered to
+//param name - name of the report for messages
+//param send - sends the report for a period, forced or not
+//param period - the period to send the report for
+fn send_period_report<F>(
+    name: &str,
+    send: F,
+    period: &ReportPeriod,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()>
+where
+    F: Fn(&ReportPeriod, bool) -> Result<DeliverySummary, Error>,
+{
+    let mut result = send(period, false);
+    if let Err(Error::AlreadySent) = result {
+        let again = prompt::yes_no(
+            &format!("{name} already sent for {period}. Send again? (y/n): "),
+            input,
+            out,
+        )?;
+        if again != Some(true) {
+            writeln!(out, "{name} not sent again.")?;
+            return Ok(());
+        }
+        result = send(period, true);
+    }
+    let summary = match result {
+        Ok(summary) => summary,
+        Err(e) => {
+            writeln!(out, "Error sending {}: {e}", name.to_lowercase())?;
+            return Ok(());
+        }
+    };
+    if summary.is_complete() {
+        writeln!(out, "{name} sent: {summary}.")?;
+    } else {
+        writeln!(out, "{name} partly sent: {summary}.")?;
+        for failure in &summary.failed {
+            writeln!(out, "Could not send to {failure}")?;
+        }
+        writeln!(out, "Failed emails are retried, see the email outbox.")?;
+    }
+    if let Some(manifest) = summary.manifest {
+        writeln!(out, "Manifest written to {manifest}")?;
+    }
+    if let Some(error) = summary.manifest_error {
+        writeln!(out, "Manifest not written: {error}")?;
+    }
+    Ok(())
+}
+
+//lists every report that has been sent
+fn list_report_runs(db: &DB, out: &mut impl Write) -> io::Result<()> {
+    let runs = match db.get_report_runs() {
+        Ok(runs) => runs,
+        Err(e) => {
+            writeln!(out, "Error listing report runs: {e}")?;
+            return Ok(());
+        }
+    };
+    if runs.is_empty() {
+        writeln!(out, "No reports have been sent.")?;
+    }
+    for run in runs {
+        writeln!(
+            out,
+            "#{} {} {} for {}: {} recipient(s), {} file(s), {}",
+            run.id,
+            run.created_at.format("%m-%d-%Y %H:%M:%S"),
+            run.report_type,
+            run.period,
+            run.recipients.len(),
+            run.files.len(),
+            if run.success { "success" } else { "failed" },
+        )?;
+        if !run.message.is_empty() {
+            writeln!(out, "    {}", run.message)?;
+        }
+    }
+    Ok(())
+}
+
+//lists the emails in the outbox and their delivery status
+//param DB - database with the outbox
+fn list_outbox(
+    db: &DB,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(status) = prompt::choice(
+        "Status (queued, sent, failed, all): ",
+        &["queued", "sent", "failed", "all"],
+        input,
+        out,
+    )?
+    else {
+        return Ok(());
+    };
+    let status = match status {
+        "queued" => Some(OutboxStatus::Queued),
+        "sent" => Some(OutboxStatus::Sent),
+        "failed" => Some(OutboxStatus::Failed),
+        _ => None,
+    };
+    let messages = match db.get_outbox(status) {
+        Ok(messages) => messages,
+        Err(e) => {
+            writeln!(out, "Error listing the outbox: {e}")?;
+            return Ok(());
+        }
+    };
+    if messages.is_empty() {
+        writeln!(out, "No emails found.")?;
+    }
+    for message in messages {
+        writeln!(
+            out,
+            "#{} {} {} to {}: {} after {} attempt(s)",
+            message.id,
+            message.created_at.format("%m-%d-%Y %H:%M:%S"),
+            message.email.kind,
+            message.email.to,
+            message.status,
+            message.attempts,
+        )?;
+        if message.status == OutboxStatus::Queued && message.attempts > 0 {
+            writeln!(
+                out,
+                "    next attempt at {}",
+                message.next_attempt_at.format("%m-%d-%Y %H:%M:%S")
+            )?;
+        }
+        if let Some(error) = message.last_error {
+            writeln!(out, "    last error: {error}")?;
+        }
+    }
+    Ok(())
+}
+
+//sends failed and queued emails in the outbox again
+//param DB - database with the outbox
+fn resend_failed_ui(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "----Re-send Unsent Emails----")?;
+    let Some(id) = prompt::ask(
+        "Email # to re-send (blank for every unsent email): ",
+        input,
+        out,
+        |id| match id {
+            "" => Ok(None),
+            id => prompt::parse_id(id.trim_start_matches('#')).map(Some),
+        },
+    )?
+    else {
+        return Ok(());
+    };
+    match db.resend_failed(actor, id) {
+        Ok(summary) if summary.is_complete() => {
+            writeln!(out, "Unsent emails re-sent: {summary}.")?
+        }
+        Ok(summary) => {
+            writeln!(out, "Unsent emails partly re-sent: {summary}.")?;
+            for failure in &summary.failed {
+                writeln!(out, "Could not send to {failure}")?;
             }
+        }
+        Err(Error::NoDataFound) => writeln!(out, "No unsent emails found.")?,
+        Err(e) => writeln!(out, "Error re-sending emails: {e}")?,
+    }
+    Ok(())
+}
+
+//previews a report without sending it or marking the period as sent
+//shows the emails here or writes them to a scratch directory
+//param DB - database to build the report from
+fn preview_report_ui(
+    db: &DB,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "----Preview Reports----")?;
+    let Some(report_type) = prompt::choice(
+        "Report type (member, provider, manager, directory): ",
+        &["member", "provider", "manager", "directory"],
+        input,
+        out,
+    )?
+    else {
+        return Ok(());
+    };
+    let preview = if report_type == "directory" {
+        let Some(id) = prompt::id("Provider ID to preview for: ", input, out)?
+        else {
+            return Ok(());
+        };
+        db.preview_provider_directory(id)
+    } else {
+        let Some(period) = read_period(input, out)? else {
+            return Ok(());
         };
-        match db.remove_member(id) {
-            Ok(()) => println!("Member removed successfully."),
-            Err(e) => eprintln!("Error removing Member: {e}"),
+        match report_type {
+            "member" => db.preview_member_reports(&period),
+            "provider" => db.preview_provider_reports(&period),
+            _ => db.preview_manager_report(&period),
+        }
+    };
+    let preview = match preview {
+        Ok(preview) => preview,
+        Err(Error::NoDataFound) => {
+            writeln!(out, "Nothing to report.")?;
+            return Ok(());
+        }
+        Err(e) => {
+            writeln!(out, "Error previewing report: {e}")?;
+            return Ok(());
+        }
+    };
+
+    let Some(dir) =
+        prompt::text("Scratch directory (blank to show here): ", input, out)?
+    else {
+        return Ok(());
+    };
+    if dir.is_empty() {
+        for email in &preview.emails {
+            writeln!(
+                out,
+                "----To: {} <{}>----",
+                email.recipient_name, email.to
+            )?;
+            writeln!(out, "Subject: {}", email.subject)?;
+            for attachment in &email.attachments {
+                writeln!(out, "Attachment: {}", attachment.file_name)?;
+            }
+            writeln!(out)?;
+            writeln!(out, "{}", email.body)?;
+        }
+    } else {
+        match preview.write(std::path::Path::new(&dir)) {
+            Ok(files) => {
+                for file in files {
+                    writeln!(out, "Wrote {file}")?;
+                }
+            }
+            Err(e) => {
+                writeln!(out, "Error writing preview: {e}")?;
+                return Ok(());
+            }
         }
     }
+    writeln!(out, "{preview}")?;
+    writeln!(out, "Nothing was sent.")?;
+    Ok(())
 }
 
-//helper function to read line from user input
-fn read_line(prompt: &str) -> String {
-    print!("{prompt}");
-    io::stdout().flush().unwrap();
+//reads the week to report on, blank for the last completed week
+//returns None if cancelled
+fn read_period(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<ReportPeriod>> {
+    prompt::ask(
+        "Week ending Friday (MM-DD-YYYY, blank for last week): ",
+        input,
+        out,
+        |date| {
+            if date.is_empty() {
+                return Ok(ReportPeriod::default());
+            }
+            ReportPeriod::week_ending(prompt::parse_date(date)?)
+        },
+    )
+}
+
+//adds a service to the provider directory
+//param DB - database to add the service to
+fn add_service(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let Some(id) = prompt::id("Enter the service id: ", input, out)? else {
+        return Ok(());
+    };
+    let Some(name) = prompt::text("Enter the service name: ", input, out)?
+    else {
+        return Ok(());
+    };
+    let Some(fee) = prompt::money("Enter the service fee: ", input, out)?
+    else {
+        return Ok(());
+    };
 
-    let mut buf = String::new();
-    io::stdin()
-        .read_line(&mut buf)
-        .expect("Failed to read line");
-    buf.trim().to_string()
+    match db.add_service(actor, id, &name, fee) {
+        Ok(_) => writeln!(out, "Service was successfully added.")?,
+        Err(err) => writeln!(out, "Error adding service: {}", err)?,
+    }
+    Ok(())
 }
 
-fn add_service(db: &DB) {
-    let id: u32 = read_line("Enter the service id: ").parse().unwrap();
-    let name: String = read_line("Enter the service name: ");
-    let fee: f64 = read_line("Enter the service fee: ").parse().unwrap();
+//sets the bank account a provider is paid to
+//param DB - database to store the bank account in
+fn set_bank_info_ui(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "----Set Provider Bank Account----")?;
+    let Some(id) = prompt::id("Provider ID: ", input, out)? else {
+        return Ok(());
+    };
+    let Some(routing) = prompt::text("9 digit routing number: ", input, out)?
+    else {
+        return Ok(());
+    };
+    let Some(account) = prompt::text("Account number: ", input, out)? else {
+        return Ok(());
+    };
+    let Some(savings) =
+        prompt::yes_no("Is this a savings account? (y/n): ", input, out)?
+    else {
+        return Ok(());
+    };
+    let account_type = if savings {
+        AccountType::Savings
+    } else {
+        AccountType::Checking
+    };
+
+    let bank = match BankInfo::new(&routing, &account, account_type) {
+        Ok(bank) => bank,
+        Err(msg) => {
+            writeln!(out, "Bank account error {msg}")?;
+            return Ok(());
+        }
+    };
+    match db.set_provider_bank_info(actor, id, &bank) {
+        Ok(()) => writeln!(out, "Bank account was successfully set.")?,
+        Err(e) => writeln!(out, "Error setting bank account: {e}")?,
+    }
+    Ok(())
+}
 
-    match db.add_service(id, &name, fee) {
-        Ok(_) => (),
-        Err(err) => eprintln!("Error adding service: {}", err),
+//sets the format a type of report is rendered in
+//for one recipient or for everyone
+//param DB - database to store the format in
+fn set_report_format_ui(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "----Set Report Format----")?;
+    let Some(report_type) = prompt::choice(
+        "Report type (member, provider, manager, directory): ",
+        &["member", "provider", "manager", "directory"],
+        input,
+        out,
+    )?
+    else {
+        return Ok(());
+    };
+    let report_type = match report_type {
+        "member" => ReportType::Member,
+        "provider" => ReportType::Provider,
+        "manager" => ReportType::Manager,
+        _ => ReportType::Directory,
+    };
+    let Some(recipient) =
+        prompt::text("Recipient email (blank for everyone): ", input, out)?
+    else {
+        return Ok(());
+    };
+    let Some(format) = prompt::ask(
+        "Format (text, html, csv, json): ",
+        input,
+        out,
+        Format::parse,
+    )?
+    else {
+        return Ok(());
+    };
+
+    let recipient = if recipient.is_empty() {
+        None
+    } else {
+        Some(recipient.as_str())
+    };
+    match db.set_report_format(actor, report_type, recipient, format) {
+        Ok(()) => writeln!(out, "Report format was successfully set.")?,
+        Err(e) => writeln!(out, "Error setting report format: {e}")?,
+    }
+    Ok(())
+}
+
+//sets whether provider or manager report emails carry a CSV attachment
+//for one recipient or for everyone
+//param DB - database to store the setting in
+fn set_csv_attachment_ui(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "----Set CSV Attachment----")?;
+    let Some(report_type) = prompt::choice(
+        "Report type (provider, manager): ",
+        &["provider", "manager"],
+        input,
+        out,
+    )?
+    else {
+        return Ok(());
+    };
+    let report_type = if report_type == "provider" {
+        ReportType::Provider
+    } else {
+        ReportType::Manager
+    };
+    let Some(recipient) =
+        prompt::text("Recipient email (blank for everyone): ", input, out)?
+    else {
+        return Ok(());
+    };
+    let Some(attach) = prompt::yes_no(
+        "Attach a CSV of the consultations? (y/n): ",
+        input,
+        out,
+    )?
+    else {
+        return Ok(());
+    };
+
+    let recipient = if recipient.is_empty() {
+        None
+    } else {
+        Some(recipient.as_str())
+    };
+    match db.set_csv_attachment(actor, report_type, recipient, attach) {
+        Ok(()) => writeln!(out, "CSV attachment was successfully set.")?,
+        Err(e) => writeln!(out, "Error setting CSV attachment: {e}")?,
+    }
+    Ok(())
+}
+
+//sets provider PINs and adds, changes, removes, unlocks or lists
+//operator accounts, or changes their roles
+//any operator may change their own password here
+//param DB - database with the credentials
+fn manage_credentials_ui(
+    db: &DB,
+    actor: &Actor,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    writeln!(out, "----Manage PINs and Operator Accounts----")?;
+    let Some(action) = prompt::choice(
+        "Action (pin, add, password, role, remove, unlock, list): ",
+        &["pin", "add", "password", "role", "remove", "unlock", "list"],
+        input,
+        out,
+    )?
+    else {
+        return Ok(());
+    };
+    let result = match action {
+        "pin" => {
+            let Some(id) = prompt::id("Provider ID: ", input, out)? else {
+                return Ok(());
+            };
+            let Some(pin) =
+                ask_secret("New PIN: ", auth::check_pin, input, out)?
+            else {
+                return Ok(());
+            };
+            db.set_provider_pin(actor, id, &pin)
+                .map(|()| "Provider PIN was successfully set.")
+        }
+        "add" => {
+            let Some(username) =
+                prompt::ask("Username: ", input, out, |username| {
+                    auth::check_username(username)
+                        .map(|()| username.to_string())
+                })?
+            else {
+                return Ok(());
+            };
+            let Some(password) =
+                ask_secret("Password: ", auth::check_password, input, out)?
+            else {
+                return Ok(());
+            };
+            let Some(role) = prompt::ask(
+                "Role (auditor, clerk, manager): ",
+                input,
+                out,
+                Role::parse,
+            )?
+            else {
+                return Ok(());
+            };
+            db.add_operator(actor, &username, &password, role)
+                .map(|()| "Operator account was successfully added.")
+        }
+        "password" => {
+            let Some(username) = prompt::text("Username: ", input, out)? else {
+                return Ok(());
+            };
+            let Some(password) =
+                ask_secret("New password: ", auth::check_password, input, out)?
+            else {
+                return Ok(());
+            };
+            db.set_operator_password(actor, &username, &password)
+                .map(|()| "Password was successfully changed.")
+        }
+        "role" => {
+            let Some(username) = prompt::text("Username: ", input, out)? else {
+                return Ok(());
+            };
+            let Some(role) = prompt::ask(
+                "Role (auditor, clerk, manager): ",
+                input,
+                out,
+                Role::parse,
+            )?
+            else {
+                return Ok(());
+            };
+            db.set_operator_role(actor, &username, role)
+                .map(|()| "Operator role was successfully changed.")
+        }
+        "remove" => {
+            let Some(username) = prompt::text("Username: ", input, out)? else {
+                return Ok(());
+            };
+            db.remove_operator(actor, &username)
+                .map(|()| "Operator account was successfully removed.")
+        }
+        "unlock" => {
+            let Some(who) =
+                prompt::text("Provider ID or operator username: ", input, out)?
+            else {
+                return Ok(());
+            };
+            match prompt::parse_id(&who) {
+                Ok(id) => db.unlock_provider(actor, id),
+                Err(_) => db.unlock_operator(actor, &who),
+            }
+            .map(|()| "Successfully unlocked.")
+        }
+        _ => {
+            let operators = match db.get_operators() {
+                Ok(operators) => operators,
+                Err(e) => {
+                    writeln!(out, "Error listing operator accounts: {e}")?;
+                    return Ok(());
+                }
+            };
+            if operators.is_empty() {
+                writeln!(out, "No operator accounts exist.")?;
+            }
+            for account in operators {
+                write!(
+                    out,
+                    "{} ({}) added {}",
+                    account.username,
+                    account.role,
+                    account.created_at.format("%m-%d-%Y %H:%M:%S")
+                )?;
+                match account.locked_until {
+                    Some(until) => writeln!(
+                        out,
+                        ", locked out until {}",
+                        until.format("%m-%d-%Y %H:%M:%S")
+                    )?,
+                    None => writeln!(out)?,
+                }
+            }
+            return Ok(());
+        }
+    };
+    match result {
+        Ok(message) => writeln!(out, "{message}")?,
+        Err(e) => writeln!(out, "Error managing credentials: {e}")?,
+    }
+    Ok(())
+}
+
+//reads a new PIN or password, then asks for it again to confirm it
+//param check - says what is wrong with a PIN or password
+//returns None if cancelled
+fn ask_secret(
+    prompt: &str,
+    check: fn(&str) -> Result<(), String>,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<String>> {
+    loop {
+        let Some(secret) = prompt::ask_hidden(prompt, input, out, |secret| {
+            check(secret).map(|()| secret.to_string())
+        })?
+        else {
+            return Ok(None);
+        };
+        let Some(again) = prompt::secret("Enter it again: ", input, out)?
+        else {
+            return Ok(None);
+        };
+        if again == secret {
+            return Ok(Some(secret));
+        }
+        writeln!(
+            out,
+            "They do not match. Try again, or enter {} to cancel.",
+            prompt::BACK
+        )?;
     }
 }
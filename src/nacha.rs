@@ -0,0 +1,441 @@
+/* File: nacha.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for creating NACHA ACH files to pay providers.
+//!
+//! A file contains a single batch of credits, one entry per provider. Every
+//! record is 94 characters long and the file is padded with `9` records to a
+//! multiple of 10 records (the blocking factor).
+use crate::db::{AccountType, BankInfo, check_routing_number};
+use chrono::{NaiveDate, NaiveDateTime};
+
+const RECORD_SIZE: usize = 94;
+const BLOCKING_FACTOR: usize = 10;
+/// Service class code for a batch of credits only.
+const SERVICE_CLASS_CREDITS: u32 = 220;
+/// Standard entry class code for corporate credits.
+const STANDARD_ENTRY_CLASS: &str = "CCD";
+const ENTRY_DESCRIPTION: &str = "CHOCANPAY";
+const BATCH_NUMBER: u32 = 1;
+
+/// The bank and company that originate the ACH file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Originator {
+    /// Routing number of the bank receiving the file. 9 digits.
+    pub immediate_destination: String,
+    /// Name of the bank receiving the file.
+    pub destination_name: String,
+    /// Identifies the sender of the file. Up to 10 characters.
+    pub immediate_origin: String,
+    /// Name of the sender of the file.
+    pub origin_name: String,
+    /// Name of the company paying the providers.
+    pub company_name: String,
+    /// Identification of the company paying the providers. 10 characters.
+    pub company_id: String,
+    /// First 8 digits of the routing number of the originating bank.
+    pub originating_dfi: String,
+}
+
+impl Originator {
+    /// The originator used for ChocAn provider payouts.
+    pub fn chocan() -> Self {
+        Originator {
+            immediate_destination: "123000220".to_string(),
+            destination_name: "CHOCAN BANK".to_string(),
+            immediate_origin: "1234567890".to_string(),
+            origin_name: "CHOCOHOLICS ANONYMOUS".to_string(),
+            company_name: "CHOCAN".to_string(),
+            company_id: "1234567890".to_string(),
+            originating_dfi: "12300022".to_string(),
+        }
+    }
+
+    /// Checks that every field fits the file, so that nothing is stripped or
+    /// cut off when it is written.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` naming the first field that is not valid.
+    pub fn validate(&self) -> Result<(), String> {
+        check_routing_number(&self.immediate_destination)
+            .map_err(|err| format!("immediate_destination: {}", err))?;
+        check_digits("immediate_origin", &self.immediate_origin, 1, 10)?;
+        check_digits("originating_dfi", &self.originating_dfi, 8, 8)?;
+        check_alpha("destination_name", &self.destination_name, 23)?;
+        check_alpha("origin_name", &self.origin_name, 23)?;
+        check_alpha("company_name", &self.company_name, 16)?;
+        check_alpha("company_id", &self.company_id, 10)
+    }
+}
+
+/// Checks that a field is `min` to `max` digits.
+fn check_digits(
+    name: &str,
+    value: &str,
+    min: usize,
+    max: usize,
+) -> Result<(), String> {
+    if value.len() < min
+        || value.len() > max
+        || !value.chars().all(|c| c.is_ascii_digit())
+    {
+        let size = if min == max {
+            min.to_string()
+        } else {
+            format!("{} to {}", min, max)
+        };
+        return Err(format!("{} must be {} digits: {}", name, size, value));
+    }
+    Ok(())
+}
+
+/// Checks that a field is 1 to `width` printable ASCII characters.
+fn check_alpha(name: &str, value: &str, width: usize) -> Result<(), String> {
+    if value.trim().is_empty()
+        || value.len() > width
+        || !value.chars().all(|c| c.is_ascii() && !c.is_ascii_control())
+    {
+        return Err(format!(
+            "{} must be 1 to {} printable ASCII characters: {}",
+            name, width, value
+        ));
+    }
+    Ok(())
+}
+
+/// A credit to a single provider.
+#[derive(Debug, Clone)]
+pub struct NachaEntry {
+    pub provider_name: String,
+    pub provider_id: u32,
+    pub bank: BankInfo,
+    /// The amount to transfer in cents.
+    pub amount_cents: u64,
+}
+
+/// Creates the contents of a NACHA ACH file.
+///
+/// # Arguments
+///
+/// * `originator` - The bank and company sending the payments.
+/// * `entries` - The credits to the providers.
+/// * `effective_date` - The date the payments should settle.
+/// * `created` - The date and time the file is created.
+///
+/// # Failure
+///
+/// Will return `Err` if a number does not fit its field, such as a payment
+/// of $100,000,000.00 or more.
+pub fn create_nacha_file(
+    originator: &Originator,
+    entries: &[NachaEntry],
+    effective_date: NaiveDate,
+    created: NaiveDateTime,
+) -> Result<String, String> {
+    let mut records: Vec<String> = Vec::new();
+    records.push(file_header(originator, created)?);
+    records.push(batch_header(originator, effective_date)?);
+
+    let mut entry_hash: u64 = 0;
+    let mut total_credit: u64 = 0;
+    for (i, entry) in entries.iter().enumerate() {
+        let receiving_dfi = &entry.bank.routing_number()[..8];
+        entry_hash += receiving_dfi.parse::<u64>().unwrap_or(0);
+        total_credit += entry.amount_cents;
+        records.push(entry_detail(originator, entry, i + 1)?);
+    }
+    // Only the rightmost 10 digits of the hash are kept.
+    let entry_hash = entry_hash % 10_000_000_000;
+
+    records.push(batch_control(
+        originator,
+        entries.len(),
+        entry_hash,
+        total_credit,
+    )?);
+    let block_count = (records.len() + 1).div_ceil(BLOCKING_FACTOR);
+    records.push(file_control(
+        block_count,
+        entries.len(),
+        entry_hash,
+        total_credit,
+    )?);
+    while !records.len().is_multiple_of(BLOCKING_FACTOR) {
+        records.push("9".repeat(RECORD_SIZE));
+    }
+
+    let mut contents = String::new();
+    for record in records {
+        debug_assert_eq!(record.len(), RECORD_SIZE);
+        contents.push_str(&record);
+        contents.push('\n');
+    }
+    Ok(contents)
+}
+
+fn file_header(
+    originator: &Originator,
+    created: NaiveDateTime,
+) -> Result<String, String> {
+    Ok("1".to_string()
+        + "01"
+        + &alpha(&format!(" {}", originator.immediate_destination), 10)
+        + &numeric_str(&originator.immediate_origin, 10)?
+        + &created.format("%y%m%d").to_string()
+        + &created.format("%H%M").to_string()
+        + "A"
+        + "094"
+        + &format!("{}", BLOCKING_FACTOR)
+        + "1"
+        + &alpha(&originator.destination_name, 23)
+        + &alpha(&originator.origin_name, 23)
+        + &alpha("", 8))
+}
+
+fn batch_header(
+    originator: &Originator,
+    effective_date: NaiveDate,
+) -> Result<String, String> {
+    Ok("5".to_string()
+        + &numeric(SERVICE_CLASS_CREDITS.into(), 3)?
+        + &alpha(&originator.company_name, 16)
+        + &alpha("", 20)
+        + &alpha(&originator.company_id, 10)
+        + STANDARD_ENTRY_CLASS
+        + &alpha(ENTRY_DESCRIPTION, 10)
+        + &alpha("", 6)
+        + &effective_date.format("%y%m%d").to_string()
+        + &alpha("", 3)
+        + "1"
+        + &numeric_str(&originator.originating_dfi, 8)?
+        + &numeric(BATCH_NUMBER.into(), 7)?)
+}
+
+fn entry_detail(
+    originator: &Originator,
+    entry: &NachaEntry,
+    sequence: usize,
+) -> Result<String, String> {
+    let transaction_code = match entry.bank.account_type() {
+        AccountType::Checking => "22",
+        AccountType::Savings => "32",
+    };
+    let routing = entry.bank.routing_number();
+    Ok("6".to_string()
+        + transaction_code
+        + &routing[..8]
+        + &routing[8..]
+        + &alpha(entry.bank.account_number(), 17)
+        + &numeric(entry.amount_cents, 10)?
+        + &alpha(&entry.provider_id.to_string(), 15)
+        + &alpha(&entry.provider_name, 22)
+        + &alpha("", 2)
+        + "0"
+        + &numeric_str(&originator.originating_dfi, 8)?
+        + &numeric(sequence as u64, 7)?)
+}
+
+fn batch_control(
+    originator: &Originator,
+    entry_count: usize,
+    entry_hash: u64,
+    total_credit: u64,
+) -> Result<String, String> {
+    Ok("8".to_string()
+        + &numeric(SERVICE_CLASS_CREDITS.into(), 3)?
+        + &numeric(entry_count as u64, 6)?
+        + &numeric(entry_hash, 10)?
+        + &numeric(0, 12)?
+        + &numeric(total_credit, 12)?
+        + &alpha(&originator.company_id, 10)
+        + &alpha("", 19)
+        + &alpha("", 6)
+        + &numeric_str(&originator.originating_dfi, 8)?
+        + &numeric(BATCH_NUMBER.into(), 7)?)
+}
+
+fn file_control(
+    block_count: usize,
+    entry_count: usize,
+    entry_hash: u64,
+    total_credit: u64,
+) -> Result<String, String> {
+    Ok("9".to_string()
+        + &numeric(1, 6)?
+        + &numeric(block_count as u64, 6)?
+        + &numeric(entry_count as u64, 8)?
+        + &numeric(entry_hash, 10)?
+        + &numeric(0, 12)?
+        + &numeric(total_credit, 12)?
+        + &alpha("", 39))
+}
+
+/// Formats an alphanumeric field: uppercase, left justified, space padded.
+fn alpha(value: &str, width: usize) -> String {
+    let value: String = value
+        .to_uppercase()
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .take(width)
+        .collect();
+    format!("{:<width$}", value, width = width)
+}
+
+/// Formats a numeric field: right justified, zero padded.
+///
+/// # Failure
+///
+/// Will return `Err` if the value has more digits than the field.
+fn numeric(value: u64, width: usize) -> Result<String, String> {
+    numeric_str(&value.to_string(), width)
+}
+
+/// Formats a numeric field that is already a string of digits.
+///
+/// # Failure
+///
+/// Will return `Err` if the value has more digits than the field.
+fn numeric_str(value: &str, width: usize) -> Result<String, String> {
+    let value: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
+    if value.len() > width {
+        return Err(format!("{} does not fit in {} digits", value, width));
+    }
+    Ok(format!("{:0>width$}", value, width = width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_an_entry(name: &str, id: u32, amount_cents: u64) -> NachaEntry {
+        NachaEntry {
+            provider_name: name.to_string(),
+            provider_id: id,
+            bank: BankInfo::new("091000019", "12345678", AccountType::Checking)
+                .unwrap(),
+            amount_cents,
+        }
+    }
+
+    fn create_a_file(entries: &[NachaEntry]) -> Result<String, String> {
+        create_nacha_file(
+            &Originator::chocan(),
+            entries,
+            NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 10, 17)
+                .unwrap()
+                .and_hms_opt(0, 5, 0)
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_records_are_94_characters_and_blocked() {
+        let file =
+            create_a_file(&[get_an_entry("ProviderName1", 1, 1099)]).unwrap();
+        let lines: Vec<&str> = file.lines().collect();
+        assert_eq!(lines.len() % BLOCKING_FACTOR, 0);
+        for line in lines {
+            assert_eq!(line.len(), RECORD_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_entry_hash_keeps_rightmost_10_digits() {
+        let entries: Vec<NachaEntry> = (0..1100)
+            .map(|id| get_an_entry("ProviderName", id, 100))
+            .collect();
+        let file = create_a_file(&entries).unwrap();
+        let control = file.lines().find(|l| l.starts_with('9')).unwrap();
+        // 1100 * 09100001 = 10010001100
+        assert_eq!(&control[21..31], "0010001100");
+        assert_eq!(&control[13..21], "00001100");
+    }
+
+    #[test]
+    fn test_originator_validate() {
+        Originator::chocan().validate().unwrap();
+        let bad = [
+            Originator {
+                immediate_destination: "123000221".to_string(),
+                ..Originator::chocan()
+            },
+            Originator {
+                immediate_origin: "12345-6789".to_string(),
+                ..Originator::chocan()
+            },
+            Originator {
+                originating_dfi: "1230002".to_string(),
+                ..Originator::chocan()
+            },
+            Originator {
+                company_name: "CHOCOHOLICS ANONYMOUS".to_string(),
+                ..Originator::chocan()
+            },
+            Originator {
+                company_id: "12345678901".to_string(),
+                ..Originator::chocan()
+            },
+            Originator {
+                origin_name: "CHOCAN\tBANK".to_string(),
+                ..Originator::chocan()
+            },
+            Originator {
+                destination_name: " ".to_string(),
+                ..Originator::chocan()
+            },
+        ];
+        for originator in bad {
+            if originator.validate().is_ok() {
+                panic!("Expected error for originator: {:?}", originator)
+            }
+        }
+    }
+
+    #[test]
+    fn test_alpha_truncates_and_pads() {
+        assert_eq!(alpha("abc", 5), "ABC  ");
+        assert_eq!(alpha("abcdef", 3), "ABC");
+    }
+
+    #[test]
+    fn test_numeric_pads() {
+        assert_eq!(numeric(42, 5).unwrap(), "00042");
+        assert_eq!(numeric_str("12-34", 6).unwrap(), "001234");
+    }
+
+    #[test]
+    fn test_numeric_too_large_error() {
+        assert_eq!(numeric(12345, 5).unwrap(), "12345");
+        assert_eq!(
+            numeric(123456, 5).unwrap_err(),
+            "123456 does not fit in 5 digits"
+        );
+        assert!(numeric_str("1234-56", 5).is_err());
+        let entry = get_an_entry("ProviderName1", 1, 10_000_000_000);
+        if create_a_file(&[entry]).is_ok() {
+            panic!("Expected error for a payment of $100,000,000.00")
+        }
+        let entry = get_an_entry("ProviderName1", 1, 9_999_999_999);
+        create_a_file(&[entry]).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+/* File: ops.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the operations shared by the commands and the HTTP API.
+//!
+//! Each operation checks its input, calls the database and returns its
+//! result as the JSON both frontends print or send back, so a command and
+//! the matching endpoint always agree.
+use crate::access::Actor;
+use crate::config::Config;
+use crate::db::{
+    Consultation, DB, DeliverySummary, Error, PersonInfo, ReportPeriod,
+    ReportType,
+};
+use chrono::NaiveDate;
+use serde_json::{Value, json};
+
+/// Why an operation did not succeed.
+#[derive(Debug)]
+pub enum OpError {
+    /// A value was understood but could not be used, such as an unknown
+    /// member id.
+    Invalid(String),
+    /// The database returned an error.
+    Db(Error),
+    /// A report was sent but not to every recipient, with the summary.
+    Undelivered(Value),
+}
+
+impl From<Error> for OpError {
+    fn from(err: Error) -> Self {
+        OpError::Db(err)
+    }
+}
+
+/// Converts a member or provider to JSON.
+pub fn person_json(person: &PersonInfo) -> Value {
+    let location = person.location();
+    json!({
+        "id": person.id(),
+        "name": person.name(),
+        "address": location.address(),
+        "city": location.city(),
+        "state": location.state(),
+        "zipcode": location.zipcode(),
+        "email": person.email(),
+    })
+}
+
+/// Lists the services in the provider directory as JSON.
+///
+/// # Failure
+///
+/// Will return `Err` if the database could not be read.
+pub fn list_services(db: &DB) -> Result<Value, Error> {
+    let services = match db.build_provider_directory() {
+        Ok(directory) => directory.services,
+        Err(Error::NoDataFound) => Vec::new(),
+        Err(err) => return Err(err),
+    };
+    let services: Vec<Value> = services
+        .iter()
+        .map(|service| {
+            json!({
+                "code": service.service_id,
+                "name": service.name,
+                "fee": service.fee,
+            })
+        })
+        .collect();
+    Ok(json!({ "services": services }))
+}
+
+/// Records a consultation after checking the provider, member and service,
+/// returning it as JSON along with its fee.
+///
+/// # Arguments
+///
+/// * `db` - The database to record the consultation in.
+/// * `actor` - Who is recording it.
+/// * `provider_id` - The provider who gave the service.
+/// * `member_id` - The member who received it.
+/// * `service_code` - The service given.
+/// * `service_date` - The date of the service as `MM-DD-YYYY`.
+/// * `comments` - Any comments, which may be empty.
+///
+/// # Failure
+///
+/// Will return `Err` with `Invalid` if the provider, member, service or
+/// date is not valid, or `Db` if the actor may not record it.
+pub fn record_consultation(
+    db: &DB,
+    actor: &Actor,
+    provider_id: u32,
+    member_id: u32,
+    service_code: u32,
+    service_date: &str,
+    comments: &str,
+) -> Result<Value, OpError> {
+    if !db.is_valid_provider_id(provider_id)? {
+        return Err(OpError::Invalid(format!(
+            "invalid provider id: {}",
+            provider_id
+        )));
+    }
+    if !db.is_valid_member_id(member_id)? {
+        return Err(OpError::Invalid(format!(
+            "invalid or suspended member id: {}",
+            member_id
+        )));
+    }
+    if !db.is_valid_service_id(service_code)? {
+        return Err(OpError::Invalid(format!(
+            "invalid service code: {}",
+            service_code
+        )));
+    }
+    let curr_date = chrono::Local::now().format("%m-%d-%Y %H:%M:%S");
+    let consul = Consultation::new(
+        &curr_date.to_string(),
+        service_date,
+        provider_id,
+        member_id,
+        service_code,
+        comments,
+    )
+    .map_err(OpError::Invalid)?;
+    db.add_consultation_record(actor, &consul)?;
+    let fee = db.get_service_fee(service_code)?;
+    Ok(json!({
+        "consultation": {
+            "provider": provider_id,
+            "member": member_id,
+            "service": service_code,
+            "date": service_date,
+            "fee": fee,
+        }
+    }))
+}
+
+/// Parses the type of a report that can be sent and the week it covers,
+/// given by the Friday it ends on as `YYYY-MM-DD`.
+///
+/// # Failure
+///
+/// Will return `Err` if the type cannot be sent or the date is not a Friday.
+pub fn parse_report_week(
+    report_type: &str,
+    week: &str,
+) -> Result<(ReportType, ReportPeriod), String> {
+    let report_type = match ReportType::from_str(report_type) {
+        Some(ReportType::Directory | ReportType::Accounting) | None => {
+            return Err(format!("invalid report type: {}", report_type));
+        }
+        Some(report_type) => report_type,
+    };
+    let friday = NaiveDate::parse_from_str(week, "%Y-%m-%d")
+        .map_err(|_| format!("invalid week: {}", week))?;
+    let period = ReportPeriod::week_ending(friday)?;
+    Ok((report_type, period))
+}
+
+/// Sends a report for a week, returning a summary of the run as JSON.
+///
+/// # Arguments
+///
+/// * `db` - The database to send the report from.
+/// * `actor` - Who is sending it.
+/// * `config` - The configuration, which has the NACHA originator.
+/// * `report_type` - The report to send, from [`parse_report_week`].
+/// * `period` - The week to send it for.
+/// * `force` - Whether to send it again if it was already sent.
+///
+/// # Failure
+///
+/// Will return `Err` with `Db` if the report could not be sent, or
+/// `Undelivered` with the summary if some recipients were not delivered to.
+pub fn send_week_report(
+    db: &DB,
+    actor: &Actor,
+    config: &Config,
+    report_type: ReportType,
+    period: &ReportPeriod,
+    force: bool,
+) -> Result<Value, OpError> {
+    let summary = match report_type {
+        ReportType::Member => db.send_member_reports(actor, period, force),
+        ReportType::Provider => db.send_provider_reports(actor, period, force),
+        ReportType::Manager => db.send_manager_report(actor, period, force),
+        ReportType::Eft => db.send_eft_records(actor, period, force),
+        ReportType::Nacha => {
+            db.send_nacha_file(actor, period, &config.originator, force)
+        }
+        ReportType::Directory | ReportType::Accounting => {
+            return Err(OpError::Invalid(format!(
+                "invalid report type: {}",
+                report_type.as_str()
+            )));
+        }
+    }?;
+    let json = summary_json(report_type, period, &summary);
+    if summary.is_complete() {
+        Ok(json)
+    } else {
+        Err(OpError::Undelivered(json))
+    }
+}
+
+/// Converts the outcome of sending a report to JSON.
+fn summary_json(
+    report_type: ReportType,
+    period: &ReportPeriod,
+    summary: &DeliverySummary,
+) -> Value {
+    let failed: Vec<Value> = summary
+        .failed
+        .iter()
+        .map(|failure| {
+            json!({ "recipient": failure.recipient, "error": failure.error })
+        })
+        .collect();
+    json!({
+        "report_type": report_type.as_str(),
+        "period_start": period.start().format("%Y-%m-%d").to_string(),
+        "period_end": period.end().format("%Y-%m-%d").to_string(),
+        "recipients": summary.recipients,
+        "files": summary.files,
+        "failed": failed,
+        "manifest": summary.manifest,
+        "manifest_error": summary.manifest_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_report_week() {
+        let (report_type, period) =
+            parse_report_week("provider", "2026-10-16").unwrap();
+        assert_eq!(report_type, ReportType::Provider);
+        assert_eq!(period.start().format("%Y-%m-%d").to_string(), "2026-10-10");
+        assert_eq!(
+            parse_report_week("directory", "2026-10-16").unwrap_err(),
+            "invalid report type: directory"
+        );
+        assert_eq!(
+            parse_report_week("member", "10-16-2026").unwrap_err(),
+            "invalid week: 10-16-2026"
+        );
+        assert!(parse_report_week("member", "2026-10-15").is_err());
+    }
+}
@@ -0,0 +1,466 @@
+/* File: prompt.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the prompts shared by the terminals.
+//!
+//! Each typed prompt asks again until the input is valid, so bad input never
+//! panics. Entering `back` at any prompt but [`free_text`] cancels it, which
+//! is returned as `Ok(None)` so the screen can return to its menu. When the
+//! input ends, the prompt returns an `UnexpectedEof` error, which
+//! [`end_on_eof`] turns into a clean end of the session. PINs and passwords
+//! are not shown as they are typed once [`reads_stdin`] has been called and
+//! the standard input is a terminal.
+use chrono::NaiveDate;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// What is entered at a prompt to cancel it.
+pub const BACK: &str = "back";
+
+/// The format dates are entered in.
+pub const DATE_FORMAT: &str = "%m-%d-%Y";
+
+/// Whether the prompts read from the standard input.
+static READS_STDIN: AtomicBool = AtomicBool::new(false);
+
+/// Marks the prompts as reading from the standard input, so that PINs and
+/// passwords are hidden as they are typed when it is a terminal.
+///
+/// Only call this when the input given to the prompts is `io::stdin()`.
+/// Otherwise the terminal settings of the standard input are never touched,
+/// such as when a provider terminal reads from a network connection.
+pub fn reads_stdin() {
+    READS_STDIN.store(true, Ordering::Relaxed);
+}
+
+/// Prompts for and reads a line of input, without surrounding whitespace.
+///
+/// # Arguments
+///
+/// * `prompt` - What to ask for.
+/// * `input` - Where the input is read from.
+/// * `out` - Where the prompt is written to.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended, or if the
+/// prompt could not be written.
+pub fn line(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<String> {
+    write!(out, "{}", prompt)?;
+    out.flush()?;
+    let mut buf = String::new();
+    if input.read_line(&mut buf)? == 0 {
+        writeln!(out)?;
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+    }
+    Ok(buf.trim().to_string())
+}
+
+/// Prompts until the input can be parsed, or the prompt is cancelled.
+///
+/// # Arguments
+///
+/// * `prompt` - What to ask for.
+/// * `input` - Where the input is read from.
+/// * `out` - Where the prompt and any problem with the input are written.
+/// * `parse` - Parses the input, or says what is wrong with it.
+///
+/// # Success
+///
+/// Will return the parsed input, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn ask<T>(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> io::Result<Option<T>> {
+    ask_line(prompt, false, input, out, parse)
+}
+
+/// Prompts like [`ask`] for a PIN or password, which is not shown as it is
+/// typed.
+///
+/// # Success
+///
+/// Will return the parsed input, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn ask_hidden<T>(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> io::Result<Option<T>> {
+    ask_line(prompt, true, input, out, parse)
+}
+
+/// Prompts until the input can be parsed, hiding what is typed if `hidden`.
+fn ask_line<T>(
+    prompt: &str,
+    hidden: bool,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> io::Result<Option<T>> {
+    loop {
+        let text = if hidden {
+            let _echo = EchoOff::new();
+            line(prompt, input, out)?
+        } else {
+            line(prompt, input, out)?
+        };
+        if text.eq_ignore_ascii_case(BACK) {
+            return Ok(None);
+        }
+        match parse(&text) {
+            Ok(value) => return Ok(Some(value)),
+            Err(msg) => writeln!(
+                out,
+                "{}. Try again, or enter {} to cancel.",
+                msg, BACK
+            )?,
+        }
+    }
+}
+
+/// Prompts for any text, including none.
+///
+/// # Success
+///
+/// Will return the text, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn text(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<String>> {
+    ask(prompt, input, out, |text| Ok(text.to_string()))
+}
+
+/// Prompts for text that is taken as it is, such as comments.
+///
+/// This prompt cannot be cancelled, so that `back` can be the text itself.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn free_text(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<String> {
+    line(prompt, input, out)
+}
+
+/// Prompts for a PIN or password, which is not shown as it is typed.
+///
+/// # Success
+///
+/// Will return the PIN or password, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn secret(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<String>> {
+    ask_hidden(prompt, input, out, |text| Ok(text.to_string()))
+}
+
+/// Prompts for an id or code made of digits.
+///
+/// # Success
+///
+/// Will return the id, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn id(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<u32>> {
+    ask(prompt, input, out, parse_id)
+}
+
+/// Prompts for a date as MM-DD-YYYY.
+///
+/// # Success
+///
+/// Will return the date, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn date(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<NaiveDate>> {
+    ask(prompt, input, out, parse_date)
+}
+
+/// Prompts for an amount of money in dollars, such as `10.25` or `$10`.
+///
+/// # Success
+///
+/// Will return the amount, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn money(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<f64>> {
+    ask(prompt, input, out, parse_money)
+}
+
+/// Prompts for yes or no.
+///
+/// # Success
+///
+/// Will return `true` for yes, or `None` if `back` was entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn yes_no(
+    prompt: &str,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<bool>> {
+    ask(prompt, input, out, parse_yes_no)
+}
+
+/// Prompts for one of a list of choices, ignoring case.
+///
+/// # Arguments
+///
+/// * `prompt` - What to ask for.
+/// * `choices` - The choices that may be entered.
+/// * `input` - Where the input is read from.
+/// * `out` - Where the prompt is written to.
+///
+/// # Success
+///
+/// Will return the choice as it is in `choices`, or `None` if `back` was
+/// entered.
+///
+/// # Failure
+///
+/// Will return `Err` with `UnexpectedEof` if the input has ended.
+pub fn choice<'a>(
+    prompt: &str,
+    choices: &[&'a str],
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<&'a str>> {
+    ask(prompt, input, out, |text| parse_choice(text, choices))
+}
+
+/// Parses an id or code made of digits.
+pub fn parse_id(text: &str) -> Result<u32, String> {
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid id, enter digits only: {}", text));
+    }
+    text.parse()
+        .map_err(|_| format!("Invalid id, too many digits: {}", text))
+}
+
+/// Parses a date as MM-DD-YYYY.
+pub fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(text, DATE_FORMAT).map_err(|_| {
+        format!("Invalid date, enter a date as MM-DD-YYYY: {}", text)
+    })
+}
+
+/// Parses an amount of money in dollars with at most two decimal places.
+pub fn parse_money(text: &str) -> Result<f64, String> {
+    let amount = text.strip_prefix('$').unwrap_or(text);
+    let cents = amount.split_once('.').map_or(0, |(_, cents)| cents.len());
+    match amount.parse::<f64>() {
+        Ok(value) if value >= 0.0 && value.is_finite() && cents <= 2 => {
+            Ok(value)
+        }
+        _ => Err(format!("Invalid amount, enter dollars as 0.00: {}", text)),
+    }
+}
+
+/// Parses yes or no.
+pub fn parse_yes_no(text: &str) -> Result<bool, String> {
+    match text.to_lowercase().as_str() {
+        "y" | "yes" => Ok(true),
+        "n" | "no" => Ok(false),
+        _ => Err(format!("Invalid answer, enter y or n: {}", text)),
+    }
+}
+
+/// Parses one of a list of choices, ignoring case.
+pub fn parse_choice<'a>(
+    text: &str,
+    choices: &[&'a str],
+) -> Result<&'a str, String> {
+    choices
+        .iter()
+        .find(|choice| choice.eq_ignore_ascii_case(text))
+        .copied()
+        .ok_or_else(|| {
+            format!("Invalid choice, enter {}: {}", choices.join(", "), text)
+        })
+}
+
+/// Ends a session cleanly when its input has ended.
+///
+/// # Arguments
+///
+/// * `result` - The outcome of the session.
+///
+/// # Failure
+///
+/// Will return `Err` for any error other than the input ending.
+pub fn end_on_eof(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(()),
+        result => result,
+    }
+}
+
+/// Turns off the echo of the standard input while it is alive, if the
+/// prompts read from it and it is a terminal. The newline is still echoed so
+/// the next output starts on its own line.
+struct EchoOff {
+    #[cfg(unix)]
+    saved: Option<rustix::termios::Termios>,
+}
+
+impl EchoOff {
+    #[cfg(unix)]
+    fn new() -> Self {
+        use rustix::termios::{self, LocalModes, OptionalActions};
+        use std::io::IsTerminal;
+        let stdin = io::stdin();
+        if !READS_STDIN.load(Ordering::Relaxed) || !stdin.is_terminal() {
+            return EchoOff { saved: None };
+        }
+        let saved = termios::tcgetattr(&stdin).ok();
+        if let Some(saved) = &saved {
+            let mut hidden = saved.clone();
+            hidden.local_modes.remove(LocalModes::ECHO);
+            hidden.local_modes.insert(LocalModes::ECHONL);
+            let _ = termios::tcsetattr(&stdin, OptionalActions::Now, &hidden);
+        }
+        EchoOff { saved }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        EchoOff {}
+    }
+}
+
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = &self.saved {
+            use rustix::termios::{self, OptionalActions};
+            let _ =
+                termios::tcsetattr(io::stdin(), OptionalActions::Now, saved);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run<T>(
+        script: &str,
+        prompt: impl Fn(&mut io::Cursor<&str>, &mut Vec<u8>) -> io::Result<T>,
+    ) -> (io::Result<T>, String) {
+        let mut input = io::Cursor::new(script);
+        let mut out = Vec::new();
+        let result = prompt(&mut input, &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_id_asks_again() {
+        let (result, out) =
+            run("12a\n\n123456789\n", |input, out| id("ID: ", input, out));
+        assert_eq!(result.unwrap(), Some(123456789));
+        assert_eq!(out.matches("ID: ").count(), 3);
+        assert!(out.contains("Invalid id, enter digits only: 12a."));
+    }
+
+    #[test]
+    fn test_back_cancels() {
+        let (result, _) =
+            run("Back\n", |input, out| money("Fee: ", input, out));
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn test_free_text_keeps_back() {
+        let (result, _) =
+            run("back\n", |input, out| free_text("Comments: ", input, out));
+        assert_eq!(result.unwrap(), "back");
+        let (result, _) =
+            run("back\n", |input, out| text("Username: ", input, out));
+        assert_eq!(result.unwrap(), None);
+    }
+
+    #[test]
+    fn test_eof_ends() {
+        let (result, _) = run("abc\n", |input, out| yes_no("OK? ", input, out));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert!(end_on_eof(Err(io::ErrorKind::UnexpectedEof.into())).is_ok());
+        assert!(end_on_eof(Err(io::ErrorKind::Other.into())).is_err());
+    }
+
+    #[test]
+    fn test_parse_typed_input() {
+        assert!(parse_id("99999999999").is_err());
+        assert_eq!(
+            parse_date("10-16-2026").unwrap(),
+            NaiveDate::from_ymd_opt(2026, 10, 16).unwrap()
+        );
+        assert!(parse_date("2026-10-16").is_err());
+        assert_eq!(parse_money("$10.25").unwrap(), 10.25);
+        assert!(parse_money("10.255").is_err());
+        assert!(parse_money("-1").is_err());
+        assert!(parse_yes_no("YES").unwrap());
+        assert!(!parse_yes_no("n").unwrap());
+        assert_eq!(parse_choice("CSV", &["text", "csv"]).unwrap(), "csv");
+        assert!(parse_choice("pdf", &["text", "csv"]).is_err());
+    }
+}
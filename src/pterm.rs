@@ -15,8 +15,10 @@
 
 //! Module for the provider terminal.
 
-use crate::db::{Consultation, DB};
-use std::io::{self, Write};
+use crate::access::Actor;
+use crate::db::{Consultation, DB, Error};
+use crate::prompt;
+use std::io::{self, BufRead, Write};
 
 #[derive(Debug)]
 enum MenuOption {
@@ -26,61 +28,87 @@ enum MenuOption {
     Quit,
 }
 
-/// Runs the provider terminal at the commmand line with user input and output.
+/// Runs the provider terminal with user input and output.
 ///
 /// # Arguments
 ///
 /// * `db` - The database to interact with.
-pub fn run(db: &DB) {
+/// * `input` - Where the provider's input is read from.
+/// * `out` - Where the terminal is written to.
+///
+/// The terminal ends cleanly when the input ends.
+///
+/// # Failure
+///
+/// Will return `Err` if the output could not be written.
+pub fn run(
+    db: &DB,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    prompt::end_on_eof(run_session(db, input, out))
+}
+
+/// Runs the provider terminal until the provider quits.
+fn run_session(
+    db: &DB,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<()> {
     let mut quit: bool = false;
 
-    let id: i32 = validate_provider(db);
-    let provider_id = id.cast_unsigned();
-    if id < 0 {
-        println!("Invalid provider id.");
-        println!(
-            "Ensure that the provider has been added from the manager terminal."
-        );
-        return;
-    }
+    let Some(provider_id) = read_provider_id(input, out)? else {
+        return Ok(());
+    };
+    let Some(actor) = check_pin(db, provider_id, input, out)? else {
+        writeln!(out, "Contact a manager to set or reset your PIN.")?;
+        return Ok(());
+    };
 
     while !quit {
-        print_menu_options();
-        let option = get_menu_option();
+        print_menu_options(out)?;
+        let option = get_menu_option(input, out)?;
 
         match option {
             MenuOption::Quit => {
-                println!("Exiting provider terminal...");
+                writeln!(out, "Exiting provider terminal...")?;
                 quit = true;
             }
             MenuOption::ValidateMember => {
-                if validate_member(db) > 0 {
-                    println!("Validated");
+                if validate_member(db, input, out)? > 0 {
+                    writeln!(out, "Validated")?;
                     continue;
                 } else {
-                    println!("Invalid Number");
+                    writeln!(out, "Invalid Number")?;
                     continue;
                 }
             }
             MenuOption::AddConsultationRecord => {
-                let member_id = validate_member(db);
+                let member_id = validate_member(db, input, out)?;
                 if member_id < 0 {
-                    println!("Invalid Number");
+                    writeln!(out, "Invalid Number")?;
                     continue;
                 } else {
-                    println!("Validated");
+                    writeln!(out, "Validated")?;
                 }
-                print!("\n---Add Consultation Record---\n");
+                write!(out, "\n---Add Consultation Record---\n")?;
                 let curr_date = chrono::Local::now()
                     .format("%m-%d-%Y %H:%M:%S")
                     .to_string();
-                let service_date = input("Service date (MM-DD-YYYY): ");
-                let service_code: u32 = get_service_code(db);
-                let comments = input("Comments: ");
+                let Some(service_date) =
+                    prompt::date("Service date (MM-DD-YYYY): ", input, out)?
+                else {
+                    continue;
+                };
+                let Some(service_code) = get_service_code(db, input, out)?
+                else {
+                    continue;
+                };
+                let comments = prompt::free_text("Comments: ", input, out)?;
 
                 let consul = match Consultation::new(
                     curr_date.as_str(),
-                    service_date.as_str(),
+                    &service_date.format(prompt::DATE_FORMAT).to_string(),
                     provider_id,
                     member_id.cast_unsigned(),
                     service_code,
@@ -88,126 +116,137 @@ pub fn run(db: &DB) {
                 ) {
                     Ok(c) => c,
                     Err(e) => {
-                        println!("Error creating consultation: {}", e);
-                        return;
+                        writeln!(out, "Error creating consultation: {}", e)?;
+                        continue;
                     }
                 };
 
-                match db.add_consultation_record(&consul) {
-                    Ok(_) => {
-                        println!("Consultation record added successfully.")
-                    }
-                    Err(e) => {
-                        println!("Failed to add consultation record: {}", e)
-                    }
+                match db.add_consultation_record(&actor, &consul) {
+                    Ok(_) => writeln!(
+                        out,
+                        "Consultation record added successfully."
+                    )?,
+                    Err(e) => writeln!(
+                        out,
+                        "Failed to add consultation record: {}",
+                        e
+                    )?,
                 }
             }
             MenuOption::GetProviderDirectory => {
-                println!();
-                let id: u32 = input("Please enter your id: ").parse().unwrap();
+                writeln!(out)?;
+                let Some(id) =
+                    prompt::id("Please enter your id: ", input, out)?
+                else {
+                    continue;
+                };
 
-                match db.send_provider_directory(id) {
-                    Ok(_) => println!("Retrieving Provider Directory."),
-                    Err(e) => {
-                        println!("Failed to send Provider Directory: {}", e)
-                    }
+                match db.send_provider_directory(&actor, id) {
+                    Ok(_) => writeln!(out, "Retrieving Provider Directory.")?,
+                    Err(e) => writeln!(
+                        out,
+                        "Failed to send Provider Directory: {}",
+                        e
+                    )?,
                 }
             }
         }
-        println!();
+        writeln!(out)?;
     }
+    Ok(())
 }
 
-fn print_menu_options() {
-    println!("---Provider Terminal---");
-    println!("( 0 ) Quit");
-    println!("( 1 ) Add Consultation Record");
-    println!("( 2 ) Get Provider Directory");
-    println!("( 3 ) Validate Member");
-    print!("Choice: ");
-    io::stdout().flush().unwrap();
+fn print_menu_options(out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "---Provider Terminal---")?;
+    writeln!(out, "( 0 ) Quit")?;
+    writeln!(out, "( 1 ) Add Consultation Record")?;
+    writeln!(out, "( 2 ) Get Provider Directory")?;
+    writeln!(out, "( 3 ) Validate Member")?;
+    write!(out, "Choice: ")?;
+    out.flush()
 }
 
-fn get_menu_option() -> MenuOption {
+fn get_menu_option(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<MenuOption> {
     loop {
-        let mut input = String::new();
-
-        if let Err(e) = io::stdin().read_line(&mut input) {
-            println!("Input error: {e}. Try again.");
-            continue;
-        }
-
-        match input.trim() {
-            "0" => return MenuOption::Quit,
-            "1" => return MenuOption::AddConsultationRecord,
-            "2" => return MenuOption::GetProviderDirectory,
-            "3" => return MenuOption::ValidateMember,
+        match prompt::line("", input, out)?.as_str() {
+            "0" => return Ok(MenuOption::Quit),
+            "1" => return Ok(MenuOption::AddConsultationRecord),
+            "2" => return Ok(MenuOption::GetProviderDirectory),
+            "3" => return Ok(MenuOption::ValidateMember),
             _ => {
-                println!("Invalid option. Please enter 0, 1, 2, or 3.");
-                print!("Choice: ");
-                io::stdout().flush().unwrap();
+                writeln!(out, "Invalid option. Please enter 0, 1, 2, or 3.")?;
+                write!(out, "Choice: ")?;
+                out.flush()?;
             }
         }
     }
 }
 
-fn input(prompt: &str) -> String {
-    print!("{}", prompt);
-    io::stdout().flush().unwrap();
-    let mut s = String::new();
-    io::stdin().read_line(&mut s).unwrap();
-    s.trim().to_string()
+/// Obtains the id of a provider who is logging in. The id is only checked
+/// along with the PIN, so the terminal does not tell which ids exist.
+///
+/// # Arguments
+///
+/// * `input` - Where the id is read from.
+/// * `out` - Where the prompt is written to.
+///
+/// # Success
+///
+/// Will return the id, or `None` if it was cancelled.
+///
+/// # Failure
+///
+/// Will return `Err` if the input has ended.
+fn read_provider_id(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<u32>> {
+    writeln!(out, "Enter your provider number/id: ")?;
+    prompt::id("", input, out)
 }
 
-/// Obtains and checks if the provider id is valid.
+/// Obtains and checks the PIN of a provider who is logging in.
 ///
 /// # Arguments
 ///
-/// * `db` - The database to check the id validity inside of.
+/// * `db` - The database with the provider's PIN.
+/// * `id` - The id of the provider.
+/// * `input` - Where the PIN is read from.
+/// * `out` - Where the prompt is written to.
 ///
 /// # Success
 ///
-/// Will return the valid id.
+/// Will return the provider to make changes as if the PIN is right, or
+/// `None` if it is wrong, the provider is locked out or it was cancelled.
 ///
 /// # Failure
 ///
-/// Will return a negative value.
-fn validate_provider(db: &DB) -> i32 {
-    println!("Enter your provider number/id: ");
-    io::stdout().flush().unwrap();
-
-    let mut valid_input = false;
-    let mut number: i32 = -1;
-
-    while !valid_input {
-        let mut input = String::new();
-        std::io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
-        let input = input.trim();
-
-        match input.parse::<u32>() {
-            Ok(n) => {
-                valid_input = true;
-                number = n.cast_signed();
-            }
-            Err(_) => println!("Invalid input. Try again"),
-        }
-    }
-
-    match db.is_valid_provider_id(number.cast_unsigned()) {
-        Ok(valid) => {
-            if valid {
-                number
-            } else {
-                -1
-            }
-        }
-        Err(err) => {
-            println!("Error validating id: {}", err);
-            -1
+/// Will return `Err` if the input has ended.
+fn check_pin(
+    db: &DB,
+    id: u32,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<Actor>> {
+    let Some(pin) = prompt::secret("Enter your PIN: ", input, out)? else {
+        return Ok(None);
+    };
+    match db.login_provider(id, &pin) {
+        Ok(actor) => return Ok(Some(actor)),
+        Err(Error::NoDataFound | Error::LoginFailed) => {
+            writeln!(out, "Invalid provider id or PIN.")?
         }
+        Err(Error::LockedOut(until)) => writeln!(
+            out,
+            "Too many invalid PINs, locked out until {}.",
+            until.format("%m-%d-%Y %H:%M:%S")
+        )?,
+        Err(err) => writeln!(out, "Error checking PIN: {}", err)?,
     }
+    Ok(None)
 }
 
 /// Obtains and checks if the member id is valid.
@@ -215,75 +254,91 @@ fn validate_provider(db: &DB) -> i32 {
 /// # Arguments
 ///
 /// * `db` - The database to check the id validity inside of.
+/// * `input` - Where the id is read from.
+/// * `out` - Where the prompt is written to.
 ///
 /// # Success
 ///
-/// Will return the valid id.
+/// Will return the valid id, or a negative value if it is not valid or
+/// was cancelled.
 ///
 /// # Failure
 ///
-/// Will return a negative value.
-fn validate_member(db: &DB) -> i32 {
-    println!("Enter the member number/id: ");
-    io::stdout().flush().unwrap();
-
-    let mut valid_input = false;
-    let mut number: i32 = -1;
-
-    while !valid_input {
-        let mut input = String::new();
-        std::io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
-        let input = input.trim();
-
-        match input.parse::<u32>() {
-            Ok(n) => {
-                valid_input = true;
-                number = n.cast_signed();
-            }
-            Err(_) => println!("Invalid input. Try again"),
-        }
-    }
+/// Will return `Err` if the input has ended.
+fn validate_member(
+    db: &DB,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<i32> {
+    writeln!(out, "Enter the member number/id: ")?;
+    let Some(number) = prompt::id("", input, out)? else {
+        return Ok(-1);
+    };
+    let number = number.cast_signed();
 
     match db.is_valid_member_id(number.cast_unsigned()) {
         Ok(valid) => {
             if valid {
-                number
+                Ok(number)
             } else {
-                -1
+                Ok(-1)
             }
         }
         Err(err) => {
-            println!("Error validating id: {}", err);
-            -1
+            writeln!(out, "Error validating id: {}", err)?;
+            Ok(-1)
         }
     }
 }
 
-fn get_service_code(db: &DB) -> u32 {
-    let id: u32;
+/// Obtains a service code and checks its name with the provider.
+///
+/// # Arguments
+///
+/// * `db` - The database with the provider directory.
+/// * `input` - Where the service code is read from.
+/// * `out` - Where the prompts are written to.
+///
+/// # Success
+///
+/// Will return the service code, or `None` if it was cancelled.
+///
+/// # Failure
+///
+/// Will return `Err` if the input has ended.
+fn get_service_code(
+    db: &DB,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<u32>> {
     loop {
-        let service_code: u32 = input("Service code: ").parse().unwrap();
+        let Some(service_code) = prompt::id("Service code: ", input, out)?
+        else {
+            return Ok(None);
+        };
         match db.is_valid_service_id(service_code) {
-            Ok(valid) => {
-                if valid {
-                    match db.get_service_name(service_code) {
-                        Ok(name) => {
-                            println!("Service Name: {}", name);
-                            if input("Is the name correct (y/n): ") == "y" {
-                                id = service_code;
-                                break;
-                            }
-                        }
-                        Err(err) => {
-                            println!("Error for getting service code: {}", err)
-                        }
+            Ok(true) => match db.get_service_name(service_code) {
+                Ok(name) => {
+                    writeln!(out, "Service Name: {}", name)?;
+                    let correct = prompt::yes_no(
+                        "Is the name correct (y/n): ",
+                        input,
+                        out,
+                    )?;
+                    match correct {
+                        Some(true) => return Ok(Some(service_code)),
+                        Some(false) => (),
+                        None => return Ok(None),
                     }
                 }
+                Err(err) => {
+                    writeln!(out, "Error for getting service code: {}", err)?
+                }
+            },
+            Ok(false) => writeln!(out, "Invalid service code.")?,
+            Err(err) => {
+                writeln!(out, "Error for verify service code: {}", err)?
             }
-            Err(err) => println!("Error for verify service code: {}", err),
         }
     }
-    id
 }
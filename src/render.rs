@@ -0,0 +1,675 @@
+/* File: render.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for rendering ChocAn reports in different formats.
+//!
+//! Every format implements the `Renderer` trait. The format used for a
+//! report is chosen per report type and per recipient in the database.
+use crate::db::{PersonInfo, ReportPeriod};
+use crate::report::{
+    ManagerReport, MemberReport, ProviderDirectory, ProviderReport,
+};
+use crate::template::Templates;
+use serde_json::{Value, json};
+
+/// The date format used for report periods in rendered reports.
+const PERIOD_DATE_FORMAT: &str = "%m-%d-%Y";
+
+/// Renders each kind of report as the body of an email.
+pub trait Renderer {
+    /// Renders a member report.
+    fn member_report(&self, report: &MemberReport) -> String;
+
+    /// Renders a provider report.
+    fn provider_report(&self, report: &ProviderReport) -> String;
+
+    /// Renders a manager report.
+    fn manager_report(&self, report: &ManagerReport) -> String;
+
+    /// Renders the provider directory.
+    fn provider_directory(&self, directory: &ProviderDirectory) -> String;
+}
+
+/// The formats a report can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// The plain text layout ChocAn has always used.
+    #[default]
+    Text,
+    Html,
+    Csv,
+    Json,
+}
+
+impl Format {
+    /// Every format, in the order they are offered to users.
+    pub const ALL: [Format; 4] =
+        [Format::Text, Format::Html, Format::Csv, Format::Json];
+
+    /// Parses a format from its name such as "html".
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The name of the format, case insensitive.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the name is not a known format.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text.trim().to_lowercase().as_str() {
+            "text" | "txt" => Ok(Format::Text),
+            "html" => Ok(Format::Html),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!(
+                "Unknown format {text}, expected text, html, csv or json"
+            )),
+        }
+    }
+
+    /// The name of the format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Html => "html",
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+
+    /// Gets the renderer for the format.
+    ///
+    /// # Arguments
+    ///
+    /// * `templates` - The templates used by the plain text format.
+    pub fn renderer(&self, templates: &Templates) -> Box<dyn Renderer> {
+        match self {
+            Format::Text => Box::new(TextRenderer {
+                templates: templates.clone(),
+            }),
+            Format::Html => Box::new(HtmlRenderer),
+            Format::Csv => Box::new(CsvRenderer),
+            Format::Json => Box::new(JsonRenderer),
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Renders the bodies of an email for a report.
+///
+/// HTML is sent along with a plain text alternative for mail clients that
+/// cannot show it, while every other format is the only body.
+///
+/// # Arguments
+///
+/// * `format` - The format chosen for the recipient.
+/// * `templates` - The templates used by the plain text format.
+/// * `report` - The report to render.
+/// * `render` - Renders the report with a renderer.
+///
+/// # Success
+///
+/// Will return the plain text body and the HTML body, if any.
+pub fn email_bodies<R>(
+    format: Format,
+    templates: &Templates,
+    report: &R,
+    render: impl Fn(&dyn Renderer, &R) -> String,
+) -> (String, Option<String>) {
+    if format == Format::Html {
+        let text = Format::Text.renderer(templates);
+        (
+            render(text.as_ref(), report),
+            Some(render(&HtmlRenderer, report)),
+        )
+    } else {
+        (render(format.renderer(templates).as_ref(), report), None)
+    }
+}
+
+/// Renders reports as plain text from templates.
+#[derive(Default)]
+pub struct TextRenderer {
+    pub templates: Templates,
+}
+
+impl Renderer for TextRenderer {
+    fn member_report(&self, report: &MemberReport) -> String {
+        report.render(&self.templates)
+    }
+
+    fn provider_report(&self, report: &ProviderReport) -> String {
+        report.render(&self.templates)
+    }
+
+    fn manager_report(&self, report: &ManagerReport) -> String {
+        report.render(&self.templates)
+    }
+
+    fn provider_directory(&self, directory: &ProviderDirectory) -> String {
+        directory.render(&self.templates)
+    }
+}
+
+/// Renders reports as HTML documents.
+pub struct HtmlRenderer;
+
+impl HtmlRenderer {
+    fn document(title: &str, body: &str) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n{1}\
+             </body>\n</html>\n",
+            escape_html(title),
+            body
+        )
+    }
+
+    fn person(label: &str, person: &PersonInfo) -> String {
+        let location = person.location();
+        format!(
+            "<p>\n{label} name: {}<br>\n{label} number: {}<br>\n\
+             {}<br>\n{}, {} {}\n</p>\n",
+            escape_html(person.name()),
+            person.id(),
+            escape_html(location.address()),
+            escape_html(location.city()),
+            escape_html(location.state()),
+            location.zipcode(),
+        )
+    }
+
+    fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+        let mut html = String::from("<table>\n<tr>");
+        for header in headers {
+            html.push_str(&format!("<th>{}</th>", escape_html(header)));
+        }
+        html.push_str("</tr>\n");
+        for row in rows {
+            html.push_str("<tr>");
+            for cell in row {
+                html.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+        html
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    fn member_report(&self, report: &MemberReport) -> String {
+        let rows: Vec<Vec<String>> = report
+            .consultations
+            .iter()
+            .map(|c| {
+                vec![
+                    c.service_date.clone(),
+                    c.provider_name.clone(),
+                    c.service_name.clone(),
+                ]
+            })
+            .collect();
+        let body = format!("<p>Services from {}</p>\n", report.period)
+            + &Self::person("Member", &report.member)
+            + &Self::table(
+                &["Date of service", "Provider name", "Service name"],
+                &rows,
+            );
+        Self::document(&report.subject(), &body)
+    }
+
+    fn provider_report(&self, report: &ProviderReport) -> String {
+        let rows: Vec<Vec<String>> = report
+            .consultations
+            .iter()
+            .map(|c| {
+                vec![
+                    c.service_date.clone(),
+                    c.received.clone(),
+                    c.member_name.clone(),
+                    c.member_id.to_string(),
+                    c.service_code.to_string(),
+                    format!("{:.2}", c.fee),
+                ]
+            })
+            .collect();
+        let body = format!("<p>Services from {}</p>\n", report.period)
+            + &Self::person("Provider", &report.provider)
+            + &Self::table(
+                &[
+                    "Date of service",
+                    "Date and time received",
+                    "Member name",
+                    "Member number",
+                    "Service code",
+                    "Fee",
+                ],
+                &rows,
+            )
+            + &format!(
+                "<p>\nTotal consultations: {}<br>\nTotal fee: {:.2}\n</p>\n",
+                report.total_consultations, report.total_fee
+            );
+        Self::document(&report.subject(), &body)
+    }
+
+    fn manager_report(&self, report: &ManagerReport) -> String {
+        let rows: Vec<Vec<String>> = report
+            .providers
+            .iter()
+            .map(|line| {
+                vec![
+                    line.provider_id.to_string(),
+                    line.consultations.to_string(),
+                    format!("{:.2}", line.fees),
+                ]
+            })
+            .collect();
+        let body = format!("<p>Services from {}</p>\n", report.period)
+            + &Self::table(
+                &["Provider number", "Consultations", "Fees"],
+                &rows,
+            )
+            + &format!(
+                "<p>\nTotal providers: {}<br>\nTotal consultations: {}<br>\n\
+                 Total fees: {:.2}\n</p>\n",
+                report.total_providers,
+                report.total_consultations,
+                report.total_fees
+            );
+        Self::document(&report.subject(), &body)
+    }
+
+    fn provider_directory(&self, directory: &ProviderDirectory) -> String {
+        let rows: Vec<Vec<String>> = directory
+            .services
+            .iter()
+            .map(|s| {
+                vec![
+                    s.name.clone(),
+                    s.service_id.to_string(),
+                    format!("{:.2}", s.fee),
+                ]
+            })
+            .collect();
+        let body = Self::table(&["Service name", "Service code", "Fee"], &rows);
+        Self::document(&directory.subject(), &body)
+    }
+}
+
+/// Renders reports as CSV with a header row, for spreadsheets.
+pub struct CsvRenderer;
+
+impl CsvRenderer {
+    fn record(fields: &[String]) -> String {
+        let fields: Vec<String> =
+            fields.iter().map(|field| escape_csv(field)).collect();
+        fields.join(",") + "\r\n"
+    }
+}
+
+impl Renderer for CsvRenderer {
+    fn member_report(&self, report: &MemberReport) -> String {
+        let member = &report.member;
+        let mut csv = Self::record(&[
+            "Member name".to_string(),
+            "Member number".to_string(),
+            "Date of service".to_string(),
+            "Provider name".to_string(),
+            "Service name".to_string(),
+        ]);
+        for consul in &report.consultations {
+            csv.push_str(&Self::record(&[
+                member.name().to_string(),
+                member.id().to_string(),
+                consul.service_date.clone(),
+                consul.provider_name.clone(),
+                consul.service_name.clone(),
+            ]));
+        }
+        csv
+    }
+
+    fn provider_report(&self, report: &ProviderReport) -> String {
+        let mut csv = Self::record(&[
+            "Date of service".to_string(),
+            "Date and time received".to_string(),
+            "Member name".to_string(),
+            "Member number".to_string(),
+            "Service code".to_string(),
+            "Fee".to_string(),
+        ]);
+        for consul in &report.consultations {
+            csv.push_str(&Self::record(&[
+                consul.service_date.clone(),
+                consul.received.clone(),
+                consul.member_name.clone(),
+                consul.member_id.to_string(),
+                consul.service_code.to_string(),
+                format!("{:.2}", consul.fee),
+            ]));
+        }
+        csv.push_str(&Self::record(&[
+            "Total".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+            format!("{:.2}", report.total_fee),
+        ]));
+        csv
+    }
+
+    fn manager_report(&self, report: &ManagerReport) -> String {
+        let mut csv = Self::record(&[
+            "Provider number".to_string(),
+            "Consultations".to_string(),
+            "Fees".to_string(),
+        ]);
+        for line in &report.providers {
+            csv.push_str(&Self::record(&[
+                line.provider_id.to_string(),
+                line.consultations.to_string(),
+                format!("{:.2}", line.fees),
+            ]));
+        }
+        csv.push_str(&Self::record(&[
+            "Total".to_string(),
+            report.total_consultations.to_string(),
+            format!("{:.2}", report.total_fees),
+        ]));
+        csv
+    }
+
+    fn provider_directory(&self, directory: &ProviderDirectory) -> String {
+        let mut csv = Self::record(&[
+            "Service name".to_string(),
+            "Service code".to_string(),
+            "Fee".to_string(),
+        ]);
+        for service in &directory.services {
+            csv.push_str(&Self::record(&[
+                service.name.clone(),
+                service.service_id.to_string(),
+                format!("{:.2}", service.fee),
+            ]));
+        }
+        csv
+    }
+}
+
+/// Renders reports as pretty printed JSON documents.
+pub struct JsonRenderer;
+
+impl JsonRenderer {
+    fn period(period: &ReportPeriod) -> Value {
+        json!({
+            "start": period.start().format(PERIOD_DATE_FORMAT).to_string(),
+            "end": period.end().format(PERIOD_DATE_FORMAT).to_string(),
+        })
+    }
+
+    fn person(person: &PersonInfo) -> Value {
+        let location = person.location();
+        json!({
+            "name": person.name(),
+            "number": person.id(),
+            "address": location.address(),
+            "city": location.city(),
+            "state": location.state(),
+            "zipcode": location.zipcode(),
+        })
+    }
+
+    fn to_string(value: Value) -> String {
+        serde_json::to_string_pretty(&value).unwrap_or_default() + "\n"
+    }
+}
+
+impl Renderer for JsonRenderer {
+    fn member_report(&self, report: &MemberReport) -> String {
+        let consultations: Vec<Value> = report
+            .consultations
+            .iter()
+            .map(|c| {
+                json!({
+                    "service_date": c.service_date,
+                    "provider_name": c.provider_name,
+                    "service_name": c.service_name,
+                })
+            })
+            .collect();
+        Self::to_string(json!({
+            "report": "member",
+            "period": Self::period(&report.period),
+            "member": Self::person(&report.member),
+            "consultations": consultations,
+        }))
+    }
+
+    fn provider_report(&self, report: &ProviderReport) -> String {
+        let consultations: Vec<Value> = report
+            .consultations
+            .iter()
+            .map(|c| {
+                json!({
+                    "service_date": c.service_date,
+                    "received": c.received,
+                    "member_name": c.member_name,
+                    "member_number": c.member_id,
+                    "service_code": c.service_code,
+                    "fee": c.fee,
+                })
+            })
+            .collect();
+        Self::to_string(json!({
+            "report": "provider",
+            "period": Self::period(&report.period),
+            "provider": Self::person(&report.provider),
+            "consultations": consultations,
+            "total_consultations": report.total_consultations,
+            "total_fee": report.total_fee,
+        }))
+    }
+
+    fn manager_report(&self, report: &ManagerReport) -> String {
+        let providers: Vec<Value> = report
+            .providers
+            .iter()
+            .map(|line| {
+                json!({
+                    "provider_number": line.provider_id,
+                    "consultations": line.consultations,
+                    "fees": line.fees,
+                })
+            })
+            .collect();
+        Self::to_string(json!({
+            "report": "manager",
+            "period": Self::period(&report.period),
+            "providers": providers,
+            "total_providers": report.total_providers,
+            "total_consultations": report.total_consultations,
+            "total_fees": report.total_fees,
+        }))
+    }
+
+    fn provider_directory(&self, directory: &ProviderDirectory) -> String {
+        let services: Vec<Value> = directory
+            .services
+            .iter()
+            .map(|s| {
+                json!({
+                    "name": s.name,
+                    "service_code": s.service_id,
+                    "fee": s.fee,
+                })
+            })
+            .collect();
+        Self::to_string(json!({
+            "report": "directory",
+            "services": services,
+        }))
+    }
+}
+
+/// Escapes the characters that have meaning in HTML.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quotes a CSV field if it contains a comma, quote or line break.
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::LocationInfo;
+    use crate::report::{ManagerReportLine, MemberConsultation};
+    use chrono::NaiveDate;
+
+    fn get_a_period() -> ReportPeriod {
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        ReportPeriod::week_ending(friday).unwrap()
+    }
+
+    fn get_a_member_report() -> MemberReport {
+        let location =
+            LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+        MemberReport {
+            period: get_a_period(),
+            member: PersonInfo::new("Tim <Smith>", 1, &location, "tim@pdx.edu")
+                .unwrap(),
+            consultations: vec![MemberConsultation {
+                service_date: "10-15-2026".to_string(),
+                provider_name: "Provider, One".to_string(),
+                service_name: "Say \"hi\"".to_string(),
+            }],
+        }
+    }
+
+    fn get_a_manager_report() -> ManagerReport {
+        ManagerReport {
+            period: get_a_period(),
+            providers: vec![
+                ManagerReportLine {
+                    provider_id: 61,
+                    consultations: 2,
+                    fees: 20.5,
+                },
+                ManagerReportLine {
+                    provider_id: 62,
+                    consultations: 1,
+                    fees: 10.25,
+                },
+            ],
+            total_providers: 2,
+            total_consultations: 3,
+            total_fees: 30.75,
+        }
+    }
+
+    #[test]
+    fn test_format_parse() {
+        for format in Format::ALL {
+            assert_eq!(Format::parse(format.as_str()).unwrap(), format);
+        }
+        assert_eq!(Format::parse(" HTML ").unwrap(), Format::Html);
+        if Format::parse("pdf").is_ok() {
+            panic!("Expected error for an unknown format")
+        }
+    }
+
+    #[test]
+    fn test_text_renderer_matches_report_text() {
+        let report = get_a_member_report();
+        assert_eq!(
+            Format::Text
+                .renderer(&Templates::default())
+                .member_report(&report),
+            report.to_text()
+        );
+    }
+
+    #[test]
+    fn test_html_renderer_escapes() {
+        let html = HtmlRenderer.member_report(&get_a_member_report());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Member name: Tim &lt;Smith&gt;"));
+        assert!(html.contains("<td>Say &quot;hi&quot;</td>"));
+        assert!(!html.contains("<Smith>"));
+    }
+
+    #[test]
+    fn test_csv_renderer_quotes_fields() {
+        let csv = CsvRenderer.member_report(&get_a_member_report());
+        assert_eq!(
+            csv,
+            "Member name,Member number,Date of service,Provider name,\
+             Service name\r\n\
+             Tim <Smith>,1,10-15-2026,\"Provider, One\",\"Say \"\"hi\"\"\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_renderer_manager_totals() {
+        let csv = CsvRenderer.manager_report(&get_a_manager_report());
+        assert_eq!(
+            csv,
+            "Provider number,Consultations,Fees\r\n\
+             61,2,20.50\r\n\
+             62,1,10.25\r\n\
+             Total,3,30.75\r\n"
+        );
+    }
+
+    #[test]
+    fn test_json_renderer_round_trips() {
+        let json = JsonRenderer.manager_report(&get_a_manager_report());
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["report"], "manager");
+        assert_eq!(value["period"]["end"], "10-16-2026");
+        assert_eq!(value["providers"][1]["provider_number"], 62);
+        assert_eq!(value["total_fees"], 30.75);
+
+        let json = JsonRenderer.member_report(&get_a_member_report());
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["member"]["name"], "Tim <Smith>");
+        assert_eq!(value["consultations"][0]["service_name"], "Say \"hi\"");
+    }
+}
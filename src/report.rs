@@ -0,0 +1,320 @@
+/* File: report.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the contents of ChocAn reports.
+//!
+//! Reports are built from the database by the `build_*` methods on `DB` and
+//! are delivered as a separate step.
+use crate::db::{PersonInfo, ReportPeriod};
+use crate::template::{Context, Templates};
+
+/// The date format used for report periods in templates.
+const PERIOD_DATE_FORMAT: &str = "%m-%d-%Y";
+
+/// A report of the services a member received during a period.
+#[derive(Debug, Clone)]
+pub struct MemberReport {
+    pub period: ReportPeriod,
+    pub member: PersonInfo,
+    pub consultations: Vec<MemberConsultation>,
+}
+
+/// A service a member received.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberConsultation {
+    pub service_date: String,
+    pub provider_name: String,
+    pub service_name: String,
+}
+
+impl MemberReport {
+    /// The subject line of the report.
+    pub fn subject(&self) -> String {
+        "Member Report for ".to_owned() + self.member.name()
+    }
+
+    /// The report as plain text using the shipped template.
+    pub fn to_text(&self) -> String {
+        self.render(&Templates::default())
+    }
+
+    /// The report as plain text using the given templates.
+    pub fn render(&self, templates: &Templates) -> String {
+        let member = &self.member;
+        let mut context = period_context(&self.period);
+        context.set("member_name", member.name());
+        context.set("member_number", member.id());
+        context.set("member_address", member.location().address());
+        context.set("member_city", member.location().city());
+        context.set("member_state", member.location().state());
+        context.set("member_zipcode", member.location().zipcode());
+        for consul in &self.consultations {
+            let mut item = Context::default();
+            item.set("service_date", &consul.service_date);
+            item.set("provider_name", &consul.provider_name);
+            item.set("service_name", &consul.service_name);
+            context.push_item(item);
+        }
+        templates.member.render(&context)
+    }
+}
+
+/// A report of the services a provider gave during a period.
+#[derive(Debug, Clone)]
+pub struct ProviderReport {
+    pub period: ReportPeriod,
+    pub provider: PersonInfo,
+    pub consultations: Vec<ProviderConsultation>,
+    pub total_consultations: u32,
+    pub total_fee: f64,
+}
+
+/// A service a provider gave.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderConsultation {
+    pub service_date: String,
+    /// The date and time the data were received by the computer.
+    pub received: String,
+    pub member_name: String,
+    pub member_id: u32,
+    pub service_code: u32,
+    pub fee: f64,
+}
+
+impl ProviderReport {
+    /// The subject line of the report.
+    pub fn subject(&self) -> String {
+        "Provider Report for ".to_owned() + self.provider.name()
+    }
+
+    /// The report as plain text using the shipped template.
+    pub fn to_text(&self) -> String {
+        self.render(&Templates::default())
+    }
+
+    /// The report as plain text using the given templates.
+    pub fn render(&self, templates: &Templates) -> String {
+        let provider = &self.provider;
+        let mut context = period_context(&self.period);
+        context.set("provider_name", provider.name());
+        context.set("provider_number", provider.id());
+        context.set("provider_address", provider.location().address());
+        context.set("provider_city", provider.location().city());
+        context.set("provider_state", provider.location().state());
+        context.set("provider_zipcode", provider.location().zipcode());
+        context.set("total_consultations", self.total_consultations);
+        context.set("total_fee", self.total_fee);
+        for consul in &self.consultations {
+            let mut item = Context::default();
+            item.set("service_date", &consul.service_date);
+            item.set("received", &consul.received);
+            item.set("member_name", &consul.member_name);
+            item.set("member_number", consul.member_id);
+            item.set("service_code", consul.service_code);
+            item.set("fee", consul.fee);
+            context.push_item(item);
+        }
+        templates.provider.render(&context)
+    }
+}
+
+/// A summary of every provider that gave services during a period.
+#[derive(Debug, Clone)]
+pub struct ManagerReport {
+    pub period: ReportPeriod,
+    pub providers: Vec<ManagerReportLine>,
+    pub total_providers: u32,
+    pub total_consultations: u32,
+    pub total_fees: f64,
+}
+
+/// The totals for a single provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManagerReportLine {
+    pub provider_id: u32,
+    pub consultations: u32,
+    pub fees: f64,
+}
+
+impl ManagerReport {
+    /// The subject line of the report.
+    pub fn subject(&self) -> String {
+        "Manager report".to_string()
+    }
+
+    /// The report as plain text using the shipped template.
+    pub fn to_text(&self) -> String {
+        self.render(&Templates::default())
+    }
+
+    /// The report as plain text using the given templates.
+    pub fn render(&self, templates: &Templates) -> String {
+        let mut context = period_context(&self.period);
+        context.set("total_providers", self.total_providers);
+        context.set("total_consultations", self.total_consultations);
+        context.set("total_fees", self.total_fees);
+        for line in &self.providers {
+            let mut item = Context::default();
+            item.set("provider_number", line.provider_id);
+            item.set("consultations", line.consultations);
+            item.set("fees", line.fees);
+            context.push_item(item);
+        }
+        templates.manager.render(&context)
+    }
+}
+
+/// Every service in the provider directory.
+#[derive(Debug, Clone)]
+pub struct ProviderDirectory {
+    pub services: Vec<DirectoryService>,
+}
+
+/// A service in the provider directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryService {
+    pub service_id: u32,
+    pub name: String,
+    pub fee: f64,
+}
+
+impl ProviderDirectory {
+    /// The subject line of the directory.
+    pub fn subject(&self) -> String {
+        "Provider Directory".to_string()
+    }
+
+    /// The directory as plain text using the shipped template.
+    pub fn to_text(&self) -> String {
+        self.render(&Templates::default())
+    }
+
+    /// The directory as plain text using the given templates.
+    pub fn render(&self, templates: &Templates) -> String {
+        let mut context = Context::default();
+        for service in &self.services {
+            let mut item = Context::default();
+            item.set("service_name", &service.name);
+            item.set("service_code", service.service_id);
+            item.set("fee", service.fee);
+            context.push_item(item);
+        }
+        templates.directory.render(&context)
+    }
+}
+
+/// Creates a template context with the variables of a report period.
+fn period_context(period: &ReportPeriod) -> Context {
+    let mut context = Context::default();
+    context.set("period_start", period.start().format(PERIOD_DATE_FORMAT));
+    context.set("period_end", period.end().format(PERIOD_DATE_FORMAT));
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::LocationInfo;
+    use chrono::NaiveDate;
+
+    fn get_a_period() -> ReportPeriod {
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        ReportPeriod::week_ending(friday).unwrap()
+    }
+
+    fn get_a_person(name: &str, id: u32) -> PersonInfo {
+        let location =
+            LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+        PersonInfo::new(name, id, &location, "person@pdx.edu").unwrap()
+    }
+
+    #[test]
+    fn test_member_report_text() {
+        let report = MemberReport {
+            period: get_a_period(),
+            member: get_a_person("MemberName1", 1),
+            consultations: vec![MemberConsultation {
+                service_date: "10-15-2026".to_string(),
+                provider_name: "ProviderName1".to_string(),
+                service_name: "ServiceName1".to_string(),
+            }],
+        };
+        assert_eq!(report.subject(), "Member Report for MemberName1");
+        assert_eq!(
+            report.to_text(),
+            "Member name: MemberName1
+Member number: 1
+Member street address: 1234 Main st
+Member city: Portland
+Member state: OR
+Member zip code: 56789
+----------------------------------------
+Date of service: 10-15-2026
+Provider name: ProviderName1
+Service name: ServiceName1
+"
+        );
+    }
+
+    #[test]
+    fn test_provider_report_text_has_totals() {
+        let report = ProviderReport {
+            period: get_a_period(),
+            provider: get_a_person("ProviderName1", 61),
+            consultations: vec![ProviderConsultation {
+                service_date: "10-15-2026".to_string(),
+                received: "10-15-2026 12:00:00".to_string(),
+                member_name: "MemberName1".to_string(),
+                member_id: 1,
+                service_code: 123456,
+                fee: 99.99,
+            }],
+            total_consultations: 1,
+            total_fee: 99.99,
+        };
+        let text = report.to_text();
+        assert!(text.contains("Member number: 1\nService code: 123456\n"));
+        assert!(text.ends_with(
+            "----------------------------------------
+Total consultations: 1
+Total fee: 99.99
+"
+        ));
+    }
+
+    #[test]
+    fn test_manager_report_text() {
+        let report = ManagerReport {
+            period: get_a_period(),
+            providers: vec![ManagerReportLine {
+                provider_id: 61,
+                consultations: 2,
+                fees: 20.5,
+            }],
+            total_providers: 1,
+            total_consultations: 2,
+            total_fees: 20.5,
+        };
+        assert_eq!(
+            report.to_text(),
+            "- ID: 61, Consultations: 2, Fees: 20.5
+----------------------------------------
+Total providers: 1
+Total consultations: 2
+Total fees: 20.5
+"
+        );
+    }
+}
@@ -0,0 +1,490 @@
+/* File: sched.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for running the weekly accounting procedure on a schedule.
+//!
+//! Every run sends the member, provider and manager reports and writes the
+//! EFT records for the ChocAn week ending on the last Friday on or before
+//! the run. Runs are recorded in the database so that a period is never sent
+//! twice, runs that were missed while the scheduler was down are caught up
+//! on startup, and runs that failed are retried until they succeed.
+//! The scheduler also works as the delivery worker for the email outbox.
+use crate::access::Actor;
+use crate::db::{DB, Error, ReportPeriod, ReportRun, ReportType};
+use chrono::{
+    Datelike, Duration, Local, NaiveDateTime, NaiveTime, Timelike, Weekday,
+};
+use std::thread;
+
+/// The longest time to sleep before checking the schedule again.
+const MAX_SLEEP_SECONDS: i64 = 60;
+
+/// How long to wait after a failed run before retrying it.
+const RETRY_FAILED_MINUTES: i64 = 15;
+
+/// A weekly time for the accounting procedure to run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    weekday: Weekday,
+    time: NaiveTime,
+}
+
+impl Default for Schedule {
+    /// Friday at midnight, which is the very start of Saturday.
+    fn default() -> Self {
+        Schedule {
+            weekday: Weekday::Sat,
+            time: NaiveTime::MIN,
+        }
+    }
+}
+
+impl std::fmt::Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.weekday, self.time.format("%H:%M"))
+    }
+}
+
+impl Schedule {
+    /// Create a schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `weekday` - The day of the week to run on.
+    /// * `time` - The time of day to run at.
+    pub fn new(weekday: Weekday, time: NaiveTime) -> Self {
+        Schedule {
+            weekday,
+            time: time.with_nanosecond(0).unwrap_or(time),
+        }
+    }
+
+    /// Create a schedule from text such as "Sat 00:00" or "friday 23:30".
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The day of the week followed by the time as HH:MM.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the text is not a valid schedule.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parts = text.split_whitespace();
+        let (Some(day), Some(time), None) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!(
+                "schedule must be a day and a time (E.g. Sat 00:00): {}",
+                text
+            ));
+        };
+        let weekday: Weekday = day
+            .parse()
+            .map_err(|_| format!("invalid day of the week: {}", day))?;
+        let time = NaiveTime::parse_from_str(time, "%H:%M")
+            .map_err(|_| format!("time must match format HH:MM: {}", time))?;
+        Ok(Schedule::new(weekday, time))
+    }
+
+    /// Gets the latest scheduled time at or before a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time to search back from.
+    pub fn latest_at_or_before(&self, time: NaiveDateTime) -> NaiveDateTime {
+        let days_back = (time.weekday().num_days_from_monday() + 7
+            - self.weekday.num_days_from_monday())
+            % 7;
+        let scheduled = (time.date() - Duration::days(days_back.into()))
+            .and_time(self.time);
+        if scheduled > time {
+            scheduled - Duration::weeks(1)
+        } else {
+            scheduled
+        }
+    }
+
+    /// Gets the next scheduled time after a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time to search forward from.
+    pub fn next_after(&self, time: NaiveDateTime) -> NaiveDateTime {
+        self.latest_at_or_before(time) + Duration::weeks(1)
+    }
+
+    /// Gets every scheduled time after one time and up to another.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - Scheduled times must be after this time.
+    /// * `until` - Scheduled times must be at or before this time.
+    pub fn due_between(
+        &self,
+        after: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Vec<NaiveDateTime> {
+        let mut due = Vec::new();
+        let mut scheduled = self.next_after(after);
+        while scheduled <= until {
+            due.push(scheduled);
+            scheduled += Duration::weeks(1);
+        }
+        due
+    }
+}
+
+/// Gets the period that a scheduled run reports on, which is the ChocAn
+/// week ending on the last Friday on or before the day of the run.
+///
+/// A run late on Friday reports on the week ending that day, and a run at
+/// Friday midnight reports on the week that just ended.
+///
+/// # Arguments
+///
+/// * `scheduled_for` - When the run is scheduled.
+pub fn period_for(scheduled_for: NaiveDateTime) -> ReportPeriod {
+    let days_back = (scheduled_for.weekday().num_days_from_monday() + 7
+        - Weekday::Fri.num_days_from_monday())
+        % 7;
+    let friday = scheduled_for.date() - Duration::days(days_back.into());
+    ReportPeriod::week_of(friday)
+}
+
+/// Runs the accounting procedure against a database on a schedule.
+pub struct Scheduler<'a> {
+    db: &'a DB,
+    schedule: Schedule,
+    actor: Actor,
+}
+
+impl<'a> Scheduler<'a> {
+    /// Create a scheduler.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to run the accounting procedure on.
+    /// * `schedule` - When to run the accounting procedure.
+    ///
+    /// The scheduler makes its changes as the `scheduler` system actor.
+    pub fn new(db: &'a DB, schedule: Schedule) -> Self {
+        Scheduler {
+            db,
+            schedule,
+            actor: Actor::system("scheduler"),
+        }
+    }
+
+    /// Runs the scheduler forever, sleeping between runs.
+    ///
+    /// While it waits, emails in the outbox that are due to be retried are
+    /// delivered.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the runs could not be read or recorded.
+    pub fn run(&self) -> Result<(), Error> {
+        println!("Scheduler running every {}", self.schedule);
+        loop {
+            let now = Local::now().naive_local();
+            for run in self.run_pending(now)? {
+                println!(
+                    "Ran accounting for {} ({}): {}",
+                    run.period,
+                    if run.success { "success" } else { "failed" },
+                    run.message
+                );
+            }
+            let retried = self.db.deliver_outbox(&self.actor, now)?;
+            if !retried.recipients.is_empty() || !retried.failed.is_empty() {
+                println!("Retried queued emails: {}", retried);
+            }
+            let next = self.schedule.next_after(now);
+            let wait = (next - now).min(Duration::seconds(MAX_SLEEP_SECONDS));
+            thread::sleep(wait.to_std().unwrap_or_default());
+        }
+    }
+
+    /// Runs every scheduled run that is due and has not run yet, and retries
+    /// the runs that failed.
+    ///
+    /// Runs missed since the last recorded run are caught up. If nothing
+    /// has been recorded yet only the latest scheduled run is due. A failed
+    /// run is retried once it has been failed for `RETRY_FAILED_MINUTES`.
+    /// A period that succeeded is never run again, and a retry skips the
+    /// reports that were already sent, so that reports are never sent twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time.
+    ///
+    /// # Success
+    ///
+    /// Will return the runs that happened.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the runs could not be read or recorded.
+    pub fn run_pending(
+        &self,
+        now: NaiveDateTime,
+    ) -> Result<Vec<ReportRun>, Error> {
+        let recorded = self.db.get_scheduled_runs()?;
+        // Only the latest attempt at a period decides if it is retried.
+        let mut latest: Vec<&ReportRun> = Vec::new();
+        for run in &recorded {
+            match latest.iter_mut().find(|other| other.period == run.period) {
+                Some(other) => *other = run,
+                None => latest.push(run),
+            }
+        }
+        let retry_before = now - Duration::minutes(RETRY_FAILED_MINUTES);
+        let mut due: Vec<NaiveDateTime> = latest
+            .iter()
+            .filter(|run| !run.success && run.created_at <= retry_before)
+            .filter_map(|run| run.scheduled_for)
+            .collect();
+        due.extend(
+            match recorded.iter().filter_map(|run| run.scheduled_for).max() {
+                Some(last) => self.schedule.due_between(last, now),
+                None => vec![self.schedule.latest_at_or_before(now)],
+            },
+        );
+
+        let mut runs = Vec::new();
+        for scheduled_for in due {
+            let period = period_for(scheduled_for);
+            if self.db.is_period_scheduled_run(&period)? {
+                continue;
+            }
+            let (success, message) = self.run_accounting(&period);
+            let run = ReportRun {
+                id: 0,
+                report_type: ReportType::Accounting,
+                period,
+                recipients: Vec::new(),
+                files: Vec::new(),
+                success,
+                created_at: Local::now().naive_local(),
+                scheduled_for: Some(scheduled_for),
+                message,
+            };
+            self.db.add_scheduled_run(&self.actor, &run)?;
+            runs.push(run);
+        }
+        Ok(runs)
+    }
+
+    /// Sends every report and writes the EFT records for a period.
+    ///
+    /// # Success
+    ///
+    /// Will return whether every step succeeded and a description of each.
+    fn run_accounting(&self, period: &ReportPeriod) -> (bool, String) {
+        let steps = [
+            (
+                "member reports",
+                self.db.send_member_reports(&self.actor, period, false),
+            ),
+            (
+                "provider reports",
+                self.db.send_provider_reports(&self.actor, period, false),
+            ),
+            (
+                "manager report",
+                self.db.send_manager_report(&self.actor, period, false),
+            ),
+            (
+                "EFT records",
+                self.db.send_eft_records(&self.actor, period, false),
+            ),
+        ];
+
+        let mut success = true;
+        let mut messages = Vec::new();
+        for (name, result) in steps {
+            match result {
+                Ok(summary) if summary.is_complete() => {
+                    messages.push(format!("{}: sent", name))
+                }
+                // The emails that could not be delivered stay queued in the
+                // outbox, which retries them.
+                Ok(summary) => {
+                    let failed: Vec<String> =
+                        summary.failed.iter().map(|f| f.to_string()).collect();
+                    messages.push(format!(
+                        "{}: {}, retrying: {}",
+                        name,
+                        summary,
+                        failed.join(", ")
+                    ));
+                }
+                Err(Error::NoDataFound) => {
+                    messages.push(format!("{}: no activity", name))
+                }
+                Err(Error::AlreadySent) => {
+                    messages.push(format!("{}: already sent", name))
+                }
+                Err(err) => {
+                    success = false;
+                    messages.push(format!("{}: failed: {}", name, err));
+                }
+            }
+        }
+        (success, messages.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// Path to the ChocAn database file.
+    const TEST_DB_PATH: &str = "./test_sched_chocanon.db3";
+
+    fn remove_test_db() {
+        let _ = std::fs::remove_file(TEST_DB_PATH);
+    }
+
+    fn date_time(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, month, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_schedule_parse() {
+        let schedule = Schedule::parse("fri 23:30").unwrap();
+        assert_eq!(
+            schedule,
+            Schedule::new(
+                Weekday::Fri,
+                NaiveTime::from_hms_opt(23, 30, 0).unwrap()
+            )
+        );
+        assert!(Schedule::parse("someday 23:30").is_err());
+        assert!(Schedule::parse("fri").is_err());
+        assert!(Schedule::parse("fri 25:00").is_err());
+    }
+
+    #[test]
+    fn test_schedule_latest_and_next() {
+        let schedule = Schedule::default();
+        // Monday October 19th, 2026
+        let now = date_time(10, 19, 12);
+        assert_eq!(schedule.latest_at_or_before(now), date_time(10, 17, 0));
+        assert_eq!(schedule.next_after(now), date_time(10, 24, 0));
+        let exactly = date_time(10, 17, 0);
+        assert_eq!(schedule.latest_at_or_before(exactly), exactly);
+        assert_eq!(schedule.next_after(exactly), date_time(10, 24, 0));
+    }
+
+    #[test]
+    fn test_schedule_due_between() {
+        let schedule = Schedule::default();
+        let due =
+            schedule.due_between(date_time(10, 3, 0), date_time(10, 19, 0));
+        assert_eq!(due, vec![date_time(10, 10, 0), date_time(10, 17, 0)]);
+    }
+
+    #[test]
+    fn test_period_for_friday_midnight() {
+        let period = period_for(date_time(10, 17, 0));
+        let friday = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        assert_eq!(period, ReportPeriod::week_ending(friday).unwrap());
+        // A run late on Friday reports on the week ending that day.
+        let period = period_for(date_time(10, 16, 23));
+        assert_eq!(period, ReportPeriod::week_ending(friday).unwrap());
+        let period = period_for(date_time(10, 22, 9));
+        assert_eq!(period, ReportPeriod::week_ending(friday).unwrap());
+    }
+
+    #[test]
+    fn test_run_pending_never_runs_a_period_twice() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let scheduler = Scheduler::new(&db, Schedule::default());
+        let runs = scheduler.run_pending(date_time(10, 19, 12)).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].success);
+        let runs = scheduler.run_pending(date_time(10, 19, 13)).unwrap();
+        assert!(runs.is_empty());
+    }
+
+    #[test]
+    fn test_run_pending_retries_failed_runs() {
+        let path = "./test_sched_retry_chocanon.db3";
+        let _ = std::fs::remove_file(path);
+        let db = DB::new(path).unwrap();
+        let scheduler = Scheduler::new(&db, Schedule::default());
+        let failed = ReportRun {
+            id: 0,
+            report_type: ReportType::Accounting,
+            period: period_for(date_time(10, 17, 0)),
+            recipients: Vec::new(),
+            files: Vec::new(),
+            success: false,
+            created_at: date_time(10, 17, 1),
+            scheduled_for: Some(date_time(10, 17, 0)),
+            message: "manager report: failed".to_string(),
+        };
+        db.add_scheduled_run(&Actor::system("test"), &failed)
+            .unwrap();
+        assert!(
+            scheduler
+                .run_pending(date_time(10, 17, 1))
+                .unwrap()
+                .is_empty()
+        );
+        let runs = scheduler.run_pending(date_time(10, 17, 2)).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].period, failed.period);
+        assert!(runs[0].success);
+        let recorded = db.get_scheduled_runs().unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert!(!recorded[0].success);
+        assert!(recorded[1].success);
+        assert_eq!(recorded[1].scheduled_for, failed.scheduled_for);
+        assert!(
+            scheduler
+                .run_pending(date_time(10, 17, 5))
+                .unwrap()
+                .is_empty()
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_run_pending_catches_up_missed_runs() {
+        remove_test_db();
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let scheduler = Scheduler::new(&db, Schedule::default());
+        scheduler.run_pending(date_time(9, 26, 1)).unwrap();
+        let runs = scheduler.run_pending(date_time(10, 19, 12)).unwrap();
+        let periods: Vec<NaiveDate> =
+            runs.iter().map(|run| run.period.end()).collect();
+        assert_eq!(
+            periods,
+            vec![
+                NaiveDate::from_ymd_opt(2026, 10, 2).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 10, 9).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 10, 16).unwrap(),
+            ]
+        );
+        assert_eq!(db.get_scheduled_runs().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,510 @@
+/* File: server.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the provider server, which lets provider terminals in other
+//! offices use the database over TCP.
+//!
+//! # Protocol
+//!
+//! A terminal connects and sends one request per line, each a JSON object
+//! with an `op` field. The server answers each request with one line, a JSON
+//! object with `ok` set to `true` and the result, or `ok` set to `false` and
+//! an `error` message. A connection starts logged out and every request but
+//! `login` and `logout` needs a provider to be logged in.
+//!
+//! ```text
+//! {"op":"login","provider":ID,"pin":PIN}      -> "provider": {id, name}
+//! {"op":"validate_member","member":ID}        -> "valid": true or false
+//! {"op":"service","code":CODE}                -> "service": {code, name, fee}
+//! {"op":"submit_consultation","member":ID,"service":CODE,
+//!  "date":"MM-DD-YYYY","comments":TEXT}       -> "consultation": {provider,
+//!                                                member, service, date, fee}
+//! {"op":"directory","email":true}             -> "services": [{code, name,
+//!                                                fee}]
+//! {"op":"logout"}
+//! ```
+//!
+//! `comments` and `email` may be left out. When `email` is `true` the
+//! directory is also emailed to the logged in provider. A login with an
+//! unknown provider id, a provider without a PIN or a wrong PIN gets the
+//! same `invalid provider id or PIN` error. After too many wrong PINs in a
+//! row a provider is locked out for a while. At most 64 terminals are
+//! answered at once and any more are sent `server is busy`. The server
+//! closes the connection after `logout`, after a line longer than 64 KiB, or
+//! when the terminal has been idle for five minutes. For example:
+//!
+//! ```text
+//! > {"op":"login","provider":111111111,"pin":"1234"}
+//! < {"ok":true,"provider":{"id":111111111,"name":"ProviderName"}}
+//! > {"op":"validate_member","member":222222222}
+//! < {"ok":true,"valid":true}
+//! > {"op":"service","code":999999}
+//! < {"ok":false,"error":"invalid service code: 999999"}
+//! ```
+use crate::access::{Actor, Role};
+use crate::db::{Consultation, DB, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+/// The address the server listens on and terminals connect to by default.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7314";
+
+/// How long a terminal may wait between requests before it is disconnected.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The longest request line that is read, in bytes.
+const MAX_LINE_SIZE: u64 = 64 * 1024;
+
+/// The most terminals that are answered at once.
+const MAX_CONNECTIONS: usize = 64;
+
+/// The error for a login with an unknown provider id or a wrong PIN.
+const LOGIN_FAILED: &str = "invalid provider id or PIN";
+
+/// A request from a provider terminal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Request {
+    /// Logs a provider in with their PIN for the rest of the connection.
+    Login { provider: u32, pin: String },
+    /// Checks if a member may receive services.
+    ValidateMember { member: u32 },
+    /// Looks up the name and fee of a service.
+    Service { code: u32 },
+    /// Records a consultation by the logged in provider.
+    SubmitConsultation {
+        member: u32,
+        service: u32,
+        date: String,
+        #[serde(default)]
+        comments: String,
+    },
+    /// Gets the provider directory, optionally emailing it as well.
+    Directory {
+        #[serde(default)]
+        email: bool,
+    },
+    /// Ends the connection.
+    Logout,
+}
+
+/// The state of one connection.
+#[derive(Debug, Default)]
+pub struct Session {
+    actor: Option<Actor>,
+    closed: bool,
+}
+
+impl Session {
+    /// The id of the logged in provider.
+    pub fn provider(&self) -> Option<u32> {
+        match self.actor.as_ref().map(Actor::role) {
+            Some(Role::Provider(id)) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// The logged in provider, who changes to the database are made as.
+    fn actor(&self) -> Result<&Actor, String> {
+        self.actor
+            .as_ref()
+            .ok_or_else(|| "not logged in".to_string())
+    }
+
+    /// Checks if the connection should be closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+/// Answers one request line.
+///
+/// # Arguments
+///
+/// * `db` - The database the request is made against.
+/// * `session` - The state of the connection the request came from.
+/// * `line` - The request, as a line of JSON.
+///
+/// # Success
+///
+/// Will return the response, with `ok` set to `false` and an `error` message
+/// if the request could not be carried out.
+pub fn handle_request(db: &DB, session: &mut Session, line: &str) -> Value {
+    let result =
+        parse_request(line).and_then(|request| respond(db, session, request));
+    to_response(result)
+}
+
+fn parse_request(line: &str) -> Result<Request, String> {
+    serde_json::from_str::<Request>(line)
+        .map_err(|err| format!("invalid request: {}", err))
+}
+
+fn to_response(result: Result<Value, String>) -> Value {
+    match result {
+        Ok(mut response) => {
+            response["ok"] = json!(true);
+            response
+        }
+        Err(error) => json!({ "ok": false, "error": error }),
+    }
+}
+
+fn respond(
+    db: &DB,
+    session: &mut Session,
+    request: Request,
+) -> Result<Value, String> {
+    let provider = match (&request, session.provider()) {
+        (Request::Login { .. } | Request::Logout, _) => 0,
+        (_, Some(provider)) => provider,
+        (_, None) => return Err("not logged in".to_string()),
+    };
+    match request {
+        Request::Login { provider, pin } => {
+            let login = db.login_provider(provider, &pin);
+            log_in(db, session, provider, login)
+        }
+        Request::ValidateMember { member } => {
+            let valid = db.is_valid_member_id(member).map_err(db_error)?;
+            Ok(json!({ "valid": valid }))
+        }
+        Request::Service { code } => {
+            check_service(db, code)?;
+            let name = db.get_service_name(code).map_err(db_error)?;
+            let fee = db.get_service_fee(code).map_err(db_error)?;
+            Ok(json!({ "service": { "code": code, "name": name, "fee": fee } }))
+        }
+        Request::SubmitConsultation {
+            member,
+            service,
+            date,
+            comments,
+        } => {
+            if !db.is_valid_member_id(member).map_err(db_error)? {
+                return Err(format!(
+                    "invalid or suspended member id: {}",
+                    member
+                ));
+            }
+            check_service(db, service)?;
+            let curr_date = chrono::Local::now().format("%m-%d-%Y %H:%M:%S");
+            let consul = Consultation::new(
+                &curr_date.to_string(),
+                &date,
+                provider,
+                member,
+                service,
+                &comments,
+            )?;
+            db.add_consultation_record(session.actor()?, &consul)
+                .map_err(db_error)?;
+            let fee = db.get_service_fee(service).map_err(db_error)?;
+            Ok(json!({
+                "consultation": {
+                    "provider": provider,
+                    "member": member,
+                    "service": service,
+                    "date": date,
+                    "fee": fee,
+                }
+            }))
+        }
+        Request::Directory { email } => {
+            let services = match db.build_provider_directory() {
+                Ok(directory) => directory.services,
+                Err(Error::NoDataFound) => Vec::new(),
+                Err(err) => return Err(db_error(err)),
+            };
+            if email {
+                db.send_provider_directory(session.actor()?, provider)
+                    .map_err(db_error)?;
+            }
+            let services: Vec<Value> = services
+                .iter()
+                .map(|service| {
+                    json!({
+                        "code": service.service_id,
+                        "name": service.name,
+                        "fee": service.fee,
+                    })
+                })
+                .collect();
+            Ok(json!({ "services": services }))
+        }
+        Request::Logout => {
+            session.actor = None;
+            session.closed = true;
+            Ok(json!({}))
+        }
+    }
+}
+
+/// Logs the connection in as a provider once their PIN was checked. An
+/// unknown provider, a provider without a PIN and a wrong PIN get the same
+/// error, so a terminal cannot find out which provider ids exist.
+fn log_in(
+    db: &DB,
+    session: &mut Session,
+    provider: u32,
+    login: Result<Actor, Error>,
+) -> Result<Value, String> {
+    let actor = match login {
+        Ok(actor) => actor,
+        Err(Error::NoDataFound | Error::LoginFailed) => {
+            return Err(LOGIN_FAILED.to_string());
+        }
+        Err(err) => return Err(db_error(err)),
+    };
+    let info = db.get_provider_info(provider).map_err(db_error)?;
+    session.actor = Some(actor);
+    Ok(json!({ "provider": { "id": provider, "name": info.name() } }))
+}
+
+fn check_service(db: &DB, code: u32) -> Result<(), String> {
+    if db.is_valid_service_id(code).map_err(db_error)? {
+        Ok(())
+    } else {
+        Err(format!("invalid service code: {}", code))
+    }
+}
+
+fn db_error(err: Error) -> String {
+    err.to_string()
+}
+
+/// A server that answers provider terminals over TCP.
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    db: Arc<Mutex<DB>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl Server {
+    /// Create a server listening on an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address to listen on (E.g. `127.0.0.1:7314`). Port
+    ///   `0` picks any free port.
+    /// * `db` - The database the requests are made against.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the address could not be listened on.
+    pub fn bind(address: &str, db: DB) -> io::Result<Self> {
+        Ok(Server {
+            listener: TcpListener::bind(address)?,
+            db: Arc::new(Mutex::new(db)),
+            connections: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// The address the server is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Answers terminals until the program is killed. Each terminal is
+    /// answered on its own thread, one request at a time, and a terminal
+    /// that connects while `MAX_CONNECTIONS` are answered is turned away.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the server could no longer accept terminals.
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let mut stream = stream?;
+            let Some(connection) = Connection::open(&self.connections) else {
+                let error = json!({ "ok": false, "error": "server is busy" });
+                let _ = writeln!(stream, "{}", error);
+                continue;
+            };
+            let db = Arc::clone(&self.db);
+            thread::spawn(move || {
+                let _connection = connection;
+                let peer = stream
+                    .peer_addr()
+                    .map_or("unknown".to_string(), |addr| addr.to_string());
+                if let Err(err) = serve(&db, stream) {
+                    eprintln!("Error serving {}: {}", peer, err);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Answers the requests of one terminal until it logs out or disconnects.
+fn serve(db: &Mutex<DB>, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut session = Session::default();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let size = (&mut reader).take(MAX_LINE_SIZE).read_line(&mut line)?;
+        if size == 0 {
+            return Ok(());
+        }
+        if !line.ends_with('\n') && size as u64 == MAX_LINE_SIZE {
+            let error = json!({ "ok": false, "error": "request too long" });
+            writeln!(writer, "{}", error)?;
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match parse_request(line.trim()) {
+            // The PIN is checked without the database locked, as it is slow
+            // on purpose and would hold up every other terminal.
+            Ok(Request::Login { provider, pin }) => {
+                let pending = lock(db)?.begin_provider_login(provider);
+                let verified =
+                    pending.as_ref().is_ok_and(|pending| pending.verify(&pin));
+                let db = lock(db)?;
+                let login = pending
+                    .and_then(|pending| db.finish_login(pending, verified));
+                to_response(log_in(&db, &mut session, provider, login))
+            }
+            Ok(request) => {
+                let db = lock(db)?;
+                to_response(respond(&db, &mut session, request))
+            }
+            Err(error) => to_response(Err(error)),
+        };
+        writeln!(writer, "{}", response)?;
+        if session.is_closed() {
+            return Ok(());
+        }
+    }
+}
+
+fn lock(db: &Mutex<DB>) -> io::Result<MutexGuard<'_, DB>> {
+    db.lock()
+        .map_err(|_| io::Error::other("database lock poisoned"))
+}
+
+/// Counts a terminal as connected until it is dropped.
+struct Connection {
+    count: Arc<AtomicUsize>,
+}
+
+impl Connection {
+    /// Counts a new terminal, or returns `None` if too many are connected.
+    fn open(count: &Arc<AtomicUsize>) -> Option<Self> {
+        count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < MAX_CONNECTIONS).then_some(n + 1)
+            })
+            .ok()?;
+        Some(Connection {
+            count: Arc::clone(count),
+        })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{LocationInfo, PersonInfo};
+
+    const TEST_DB_PATH: &str = "./test_server.db3";
+
+    fn create_a_unique_person(name: &str, id: u32) -> PersonInfo {
+        let location =
+            LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+        let email = format!("{}@pdx.edu", name);
+        PersonInfo::new(name, id, &location, &email).unwrap()
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let _ = std::fs::remove_file(TEST_DB_PATH);
+        let db = DB::new(TEST_DB_PATH).unwrap();
+        let actor = Actor::system("test");
+        db.add_provider(&actor, &create_a_unique_person("ProviderName", 1))
+            .unwrap();
+        db.add_member(&actor, &create_a_unique_person("MemberName", 2))
+            .unwrap();
+        db.add_service(&actor, 123456, "ServiceName", 10.25)
+            .unwrap();
+        let mut session = Session::default();
+        let mut request = |line: &str| handle_request(&db, &mut session, line);
+
+        let response = request(r#"{"op":"validate_member","member":2}"#);
+        assert_eq!(response, json!({ "ok": false, "error": "not logged in" }));
+        let response = request(r#"{"op":"login","provider":2,"pin":"1234"}"#);
+        assert_eq!(response["error"], LOGIN_FAILED);
+        let response = request(r#"{"op":"login","provider":1,"pin":"1234"}"#);
+        assert_eq!(response["error"], LOGIN_FAILED);
+        db.set_provider_pin(&actor, 1, "1234").unwrap();
+        let response = request(r#"{"op":"login","provider":1,"pin":"4321"}"#);
+        assert_eq!(response["error"], LOGIN_FAILED);
+        let response = request(r#"{"op":"login","provider":1,"pin":"1234"}"#);
+        assert_eq!(response["provider"]["name"], "ProviderName");
+
+        let response = request(r#"{"op":"validate_member","member":2}"#);
+        assert_eq!(response, json!({ "ok": true, "valid": true }));
+        let response = request(r#"{"op":"service","code":123456}"#);
+        assert_eq!(response["service"]["fee"], 10.25);
+        let response = request(
+            r#"{"op":"submit_consultation","member":2,"service":123456,"date":"10-16-2026"}"#,
+        );
+        assert_eq!(response["consultation"]["provider"], 1);
+        let response = request(
+            r#"{"op":"submit_consultation","member":2,"service":123456,"date":"2026-10-16"}"#,
+        );
+        assert_eq!(response["ok"], false);
+        let response = request(r#"{"op":"directory"}"#);
+        assert_eq!(response["services"][0]["name"], "ServiceName");
+
+        let response = request("not json");
+        assert!(response["error"].as_str().unwrap().starts_with("invalid"));
+        let response = request(r#"{"op":"validate_member","id":2}"#);
+        assert_eq!(response["ok"], false);
+        let response = request(r#"{"op":"logout"}"#);
+        assert_eq!(response, json!({ "ok": true }));
+        assert!(session.is_closed());
+        assert_eq!(session.provider(), None);
+        let _ = std::fs::remove_file(TEST_DB_PATH);
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let connections: Vec<Connection> = (0..MAX_CONNECTIONS)
+            .map(|_| Connection::open(&count).unwrap())
+            .collect();
+        assert!(Connection::open(&count).is_none());
+        drop(connections);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        assert!(Connection::open(&count).is_some());
+    }
+}
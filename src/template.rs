@@ -0,0 +1,541 @@
+/* File: template.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the plain text report templates.
+//!
+//! Templates are read from `./templates` so the wording of a report can be
+//! changed without a rebuild. A template that is missing from the directory
+//! falls back to the default shipped with ChocAn.
+//!
+//! `{{name}}` is replaced with the value of a variable and
+//! `{{#list}} ... {{/list}}` repeats its contents for every item in a list.
+//! A tag on a line by itself does not leave a blank line behind. Every
+//! variable is checked when a template is loaded, so a misspelled name is
+//! reported instead of silently left blank.
+//!
+//! | Template        | Variables                                        |
+//! |-----------------|--------------------------------------------------|
+//! | `member.txt`    | `period_start`, `period_end`, `member_name`,     |
+//! |                 | `member_number`, `member_address`, `member_city`,|
+//! |                 | `member_state`, `member_zipcode`                 |
+//! | `provider.txt`  | `period_start`, `period_end`, `provider_name`,   |
+//! |                 | `provider_number`, `provider_address`,           |
+//! |                 | `provider_city`, `provider_state`,               |
+//! |                 | `provider_zipcode`, `total_consultations`,       |
+//! |                 | `total_fee`                                      |
+//! | `manager.txt`   | `period_start`, `period_end`, `total_providers`, |
+//! |                 | `total_consultations`, `total_fees`              |
+//! | `directory.txt` | none                                             |
+//!
+//! | Template        | List            | Item variables                    |
+//! |-----------------|-----------------|-----------------------------------|
+//! | `member.txt`    | `consultations` | `service_date`, `provider_name`,  |
+//! |                 |                 | `service_name`                    |
+//! | `provider.txt`  | `consultations` | `service_date`, `received`,       |
+//! |                 |                 | `member_name`, `member_number`,   |
+//! |                 |                 | `service_code`, `fee`             |
+//! | `manager.txt`   | `providers`     | `provider_number`,                |
+//! |                 |                 | `consultations`, `fees`           |
+//! | `directory.txt` | `services`      | `service_name`, `service_code`,   |
+//! |                 |                 | `fee`                             |
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The path in the file system where templates are read from.
+pub const TEMPLATE_PATH: &str = "./templates";
+
+#[derive(Debug)]
+pub enum TemplateError {
+    Io(String, std::io::Error),
+    Invalid {
+        file: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TemplateError::Io(file, err) => {
+                write!(f, "Could not read template {}: {}", file, err)
+            }
+            TemplateError::Invalid {
+                file,
+                line,
+                message,
+            } => write!(f, "Template {} line {}: {}", file, line, message),
+        }
+    }
+}
+
+/// The kinds of reports that have a template.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemplateKind {
+    Member,
+    Provider,
+    Manager,
+    Directory,
+}
+
+impl TemplateKind {
+    /// The name of the template file.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            TemplateKind::Member => "member.txt",
+            TemplateKind::Provider => "provider.txt",
+            TemplateKind::Manager => "manager.txt",
+            TemplateKind::Directory => "directory.txt",
+        }
+    }
+
+    /// The template shipped with ChocAn.
+    fn default_text(&self) -> &'static str {
+        match self {
+            TemplateKind::Member => include_str!("../templates/member.txt"),
+            TemplateKind::Provider => {
+                include_str!("../templates/provider.txt")
+            }
+            TemplateKind::Manager => include_str!("../templates/manager.txt"),
+            TemplateKind::Directory => {
+                include_str!("../templates/directory.txt")
+            }
+        }
+    }
+
+    /// The variables that may be used anywhere in the template.
+    fn variables(&self) -> &'static [&'static str] {
+        match self {
+            TemplateKind::Member => &[
+                "period_start",
+                "period_end",
+                "member_name",
+                "member_number",
+                "member_address",
+                "member_city",
+                "member_state",
+                "member_zipcode",
+            ],
+            TemplateKind::Provider => &[
+                "period_start",
+                "period_end",
+                "provider_name",
+                "provider_number",
+                "provider_address",
+                "provider_city",
+                "provider_state",
+                "provider_zipcode",
+                "total_consultations",
+                "total_fee",
+            ],
+            TemplateKind::Manager => &[
+                "period_start",
+                "period_end",
+                "total_providers",
+                "total_consultations",
+                "total_fees",
+            ],
+            TemplateKind::Directory => &[],
+        }
+    }
+
+    /// The list that may be looped over and the variables of its items.
+    fn list(&self) -> (&'static str, &'static [&'static str]) {
+        match self {
+            TemplateKind::Member => (
+                "consultations",
+                &["service_date", "provider_name", "service_name"],
+            ),
+            TemplateKind::Provider => (
+                "consultations",
+                &[
+                    "service_date",
+                    "received",
+                    "member_name",
+                    "member_number",
+                    "service_code",
+                    "fee",
+                ],
+            ),
+            TemplateKind::Manager => {
+                ("providers", &["provider_number", "consultations", "fees"])
+            }
+            TemplateKind::Directory => {
+                ("services", &["service_name", "service_code", "fee"])
+            }
+        }
+    }
+}
+
+/// The values a template is rendered with.
+#[derive(Debug, Default)]
+pub struct Context {
+    variables: HashMap<&'static str, String>,
+    items: Vec<Context>,
+}
+
+impl Context {
+    /// Sets the value of a variable.
+    pub fn set(&mut self, name: &'static str, value: impl ToString) {
+        self.variables.insert(name, value.to_string());
+    }
+
+    /// Adds an item to the list of the template.
+    pub fn push_item(&mut self, item: Context) {
+        self.items.push(item);
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Variable(String),
+    List(Vec<Node>),
+}
+
+/// A parsed and validated report template.
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    /// Parses a template and checks that it only uses known variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of report the template is for.
+    /// * `text` - The contents of the template.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` naming the line of the first unknown variable,
+    /// unknown list, or unclosed tag.
+    pub fn parse(
+        kind: TemplateKind,
+        text: &str,
+    ) -> Result<Self, TemplateError> {
+        let invalid = |line: usize, message: String| TemplateError::Invalid {
+            file: kind.file_name().to_string(),
+            line,
+            message,
+        };
+        let (list_name, item_variables) = kind.list();
+
+        let mut nodes = Vec::new();
+        let mut list: Option<(usize, Vec<Node>)> = None;
+        let mut rest = text;
+        let mut line = 1;
+        while let Some(start) = rest.find("{{") {
+            let (before, after) = rest.split_at(start);
+            line += before.matches('\n').count();
+            let offset = text.len() - after.len();
+            let at_line_start = offset == 0 || text[..offset].ends_with('\n');
+            let Some(end) = after.find("}}") else {
+                return Err(invalid(line, "unclosed {{".to_string()));
+            };
+            let tag = after[2..end].trim();
+            rest = &after[end + 2..];
+
+            if !before.is_empty() {
+                current(&mut nodes, &mut list)
+                    .push(Node::Text(before.to_string()));
+            }
+            if let Some(name) = tag.strip_prefix('#') {
+                if list.is_some() {
+                    return Err(invalid(
+                        line,
+                        format!("lists cannot be nested: {{{{{tag}}}}}"),
+                    ));
+                }
+                if name.trim() != list_name {
+                    return Err(invalid(
+                        line,
+                        format!("unknown list {{{{{tag}}}}}"),
+                    ));
+                }
+                list = Some((line, Vec::new()));
+            } else if let Some(name) = tag.strip_prefix('/') {
+                let Some((_, items)) = list.take() else {
+                    return Err(invalid(
+                        line,
+                        format!(
+                            "{{{{{tag}}}}} closes a list that was not opened"
+                        ),
+                    ));
+                };
+                if name.trim() != list_name {
+                    return Err(invalid(
+                        line,
+                        format!(
+                            "expected {{{{/{list_name}}}}}, found {{{{{tag}}}}}"
+                        ),
+                    ));
+                }
+                nodes.push(Node::List(items));
+            } else {
+                let known = kind.variables().contains(&tag)
+                    || list.is_some() && item_variables.contains(&tag);
+                if !known {
+                    return Err(invalid(
+                        line,
+                        format!("unknown variable {{{{{tag}}}}}"),
+                    ));
+                }
+                current(&mut nodes, &mut list)
+                    .push(Node::Variable(tag.to_string()));
+                continue;
+            }
+            // A list tag on a line by itself does not leave a blank line.
+            if at_line_start && rest.starts_with('\n') {
+                rest = &rest[1..];
+                line += 1;
+            }
+        }
+        if let Some((open_line, _)) = list {
+            return Err(invalid(
+                open_line,
+                format!("{{{{#{list_name}}}}} is never closed"),
+            ));
+        }
+        if !rest.is_empty() {
+            nodes.push(Node::Text(rest.to_string()));
+        }
+        Ok(Template { nodes })
+    }
+
+    /// Renders the template.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The values of the variables and the items of the list.
+    pub fn render(&self, context: &Context) -> String {
+        let mut text = String::new();
+        render_nodes(&self.nodes, context, None, &mut text);
+        text
+    }
+}
+
+/// Gets the nodes of the open list, or the top level nodes otherwise.
+fn current<'a>(
+    nodes: &'a mut Vec<Node>,
+    list: &'a mut Option<(usize, Vec<Node>)>,
+) -> &'a mut Vec<Node> {
+    match list {
+        Some((_, items)) => items,
+        None => nodes,
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    context: &Context,
+    item: Option<&Context>,
+    text: &mut String,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(t) => text.push_str(t),
+            Node::Variable(name) => {
+                let value = item
+                    .and_then(|item| item.variables.get(name.as_str()))
+                    .or_else(|| context.variables.get(name.as_str()));
+                if let Some(value) = value {
+                    text.push_str(value);
+                }
+            }
+            Node::List(list) => {
+                for item in &context.items {
+                    render_nodes(list, context, Some(item), text);
+                }
+            }
+        }
+    }
+}
+
+/// The templates for every kind of report.
+#[derive(Debug, Clone)]
+pub struct Templates {
+    pub member: Template,
+    pub provider: Template,
+    pub manager: Template,
+    pub directory: Template,
+}
+
+impl Default for Templates {
+    /// The templates shipped with ChocAn.
+    fn default() -> Self {
+        let parse = |kind: TemplateKind| {
+            Template::parse(kind, kind.default_text())
+                .expect("shipped templates are valid")
+        };
+        Templates {
+            member: parse(TemplateKind::Member),
+            provider: parse(TemplateKind::Provider),
+            manager: parse(TemplateKind::Manager),
+            directory: parse(TemplateKind::Directory),
+        }
+    }
+}
+
+impl Templates {
+    /// Loads and validates the templates in a directory.
+    ///
+    /// A template missing from the directory uses the shipped default.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory containing the templates.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if a template could not be read or is invalid.
+    pub fn load(dir: &str) -> Result<Self, TemplateError> {
+        let load = |kind: TemplateKind| {
+            let path = Path::new(dir).join(kind.file_name());
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    kind.default_text().to_string()
+                }
+                Err(err) => {
+                    return Err(TemplateError::Io(
+                        path.display().to_string(),
+                        err,
+                    ));
+                }
+            };
+            Template::parse(kind, &text)
+        };
+        Ok(Templates {
+            member: load(TemplateKind::Member)?,
+            provider: load(TemplateKind::Provider)?,
+            manager: load(TemplateKind::Manager)?,
+            directory: load(TemplateKind::Directory)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_a_context() -> Context {
+        let mut context = Context::default();
+        context.set("total_providers", 2);
+        for (id, fees) in [(61, 20.5), (62, 10.25)] {
+            let mut item = Context::default();
+            item.set("provider_number", id);
+            item.set("fees", fees);
+            context.push_item(item);
+        }
+        context
+    }
+
+    #[test]
+    fn test_render_list_and_standalone_tags() {
+        let template = Template::parse(
+            TemplateKind::Manager,
+            "Providers:\n{{#providers}}\n* {{provider_number}} {{ fees }}\n\
+             {{/providers}}\nTotal: {{total_providers}}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            template.render(&get_a_context()),
+            "Providers:\n* 61 20.5\n* 62 10.25\nTotal: 2\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_list() {
+        let template = Template::parse(
+            TemplateKind::Manager,
+            "IDs:{{#providers}} {{provider_number}}{{/providers}}\n",
+        )
+        .unwrap();
+        assert_eq!(template.render(&get_a_context()), "IDs: 61 62\n");
+    }
+
+    #[test]
+    fn test_unknown_variable_error() {
+        let err = Template::parse(
+            TemplateKind::Member,
+            "Member name: {{member_name}}\nMember: {{member_nmae}}\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Template member.txt line 2: unknown variable {{member_nmae}}"
+        );
+    }
+
+    #[test]
+    fn test_item_variable_outside_list_error() {
+        if Template::parse(TemplateKind::Member, "{{service_date}}").is_ok() {
+            panic!("Expected error for an item variable outside the list")
+        }
+    }
+
+    #[test]
+    fn test_unclosed_and_mismatched_tags_error() {
+        let kind = TemplateKind::Provider;
+        for text in [
+            "{{provider_name",
+            "{{#consultations}}\n{{fee}}\n",
+            "{{/consultations}}",
+            "{{#services}}{{/services}}",
+            "{{#consultations}}{{#consultations}}",
+        ] {
+            if Template::parse(kind, text).is_ok() {
+                panic!("Expected error for template: {}", text)
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_missing_directory_uses_defaults() {
+        let templates = Templates::load("./test_no_templates_here").unwrap();
+        let mut context = Context::default();
+        let mut item = Context::default();
+        item.set("service_name", "Aerobics");
+        item.set("service_code", 111111);
+        item.set("fee", 10.5);
+        context.push_item(item);
+        assert_eq!(
+            templates.directory.render(&context),
+            "Aerobics, ID: 111111, Fee: 10.5\n"
+        );
+    }
+
+    #[test]
+    fn test_load_invalid_template_error() {
+        let dir = "./test_templates_invalid";
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            Path::new(dir).join("manager.txt"),
+            "Total: {{total}}\n",
+        )
+        .unwrap();
+        let result = Templates::load(dir);
+        let _ = std::fs::remove_dir_all(dir);
+        match result {
+            Err(TemplateError::Invalid { file, line, .. }) => {
+                assert_eq!(file, "manager.txt");
+                assert_eq!(line, 1);
+            }
+            _ => panic!("Expected error for an unknown variable"),
+        }
+    }
+}
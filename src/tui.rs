@@ -0,0 +1,1010 @@
+/* File: tui.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+//! Module for the full-screen manager terminal.
+//!
+//! The screen shows a table of members, providers, services or recent
+//! consultations, with a status bar at the bottom. The line-based manager
+//! terminal in [`crate::mterm`] is still available for scripting.
+//!
+//! | Key             | Action                                        |
+//! |-----------------|-----------------------------------------------|
+//! | `←` `→` `Tab`   | Switch table                                  |
+//! | `↑` `↓` `j` `k` | Select a row                                  |
+//! | `a`             | Add a member, provider or service             |
+//! | `e` `Enter`     | Edit the selected row                         |
+//! | `d` `Delete`    | Remove the selected row, after confirming     |
+//! | `s`             | Suspend or reinstate the selected member      |
+//! | `r`             | Reload the tables                             |
+//! | `q` `Esc`       | Quit                                          |
+//!
+//! In a form, `Tab` and the arrow keys move between fields, `Enter` saves
+//! and `Esc` cancels. Each field is checked when it is left, and every field
+//! is checked again before saving. Changes are made as the logged in
+//! operator, so the status bar shows an error for those their role does not
+//! allow.
+use crate::access::Actor;
+use crate::db::{
+    Consultation, DB, Error, LocationInfo, MAX_ADDRESS_SIZE, MAX_CITY_SIZE,
+    MAX_MEMBER_ID, MAX_NAME_SIZE, MAX_SERVICE_CODE, MAX_ZIPCODE, PersonInfo,
+    STATE_SIZE,
+};
+use crate::prompt::{parse_id, parse_money};
+use crate::report::DirectoryService;
+use ratatui::backend::Backend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Block, Borders, Clear, Paragraph, Row, Table, TableState, Tabs,
+};
+use ratatui::{Frame, Terminal};
+use std::io;
+
+/// How many of the most recent consultations are shown.
+const RECENT_CONSULTATIONS: u32 = 100;
+
+/// The tables that can be shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tab {
+    Members,
+    Providers,
+    Services,
+    Consultations,
+}
+
+impl Tab {
+    const ALL: [Tab; 4] = [
+        Tab::Members,
+        Tab::Providers,
+        Tab::Services,
+        Tab::Consultations,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Tab::Members => "Members",
+            Tab::Providers => "Providers",
+            Tab::Services => "Services",
+            Tab::Consultations => "Consultations",
+        }
+    }
+
+    fn index(&self) -> usize {
+        Tab::ALL.iter().position(|tab| tab == self).unwrap_or(0)
+    }
+
+    /// The name of one row of the table, for messages.
+    fn noun(&self) -> &'static str {
+        match self {
+            Tab::Members => "member",
+            Tab::Providers => "provider",
+            Tab::Services => "service",
+            Tab::Consultations => "consultation",
+        }
+    }
+}
+
+/// A field of a form, checked when it is left and before saving.
+#[derive(Debug)]
+struct Field {
+    label: &'static str,
+    value: String,
+    editable: bool,
+    error: Option<String>,
+    validate: fn(&str) -> Result<(), String>,
+}
+
+impl Field {
+    fn new(
+        label: &'static str,
+        value: &str,
+        validate: fn(&str) -> Result<(), String>,
+    ) -> Self {
+        Field {
+            label,
+            value: value.to_string(),
+            editable: true,
+            error: None,
+            validate,
+        }
+    }
+
+    /// Checks the field, keeping the problem to show beside it.
+    fn check(&mut self) -> bool {
+        self.error = (self.validate)(&self.value).err();
+        self.error.is_none()
+    }
+}
+
+/// A form to add or edit a row of a table.
+#[derive(Debug)]
+struct Form {
+    tab: Tab,
+    editing: bool,
+    fields: Vec<Field>,
+    focus: usize,
+}
+
+impl Form {
+    fn person(tab: Tab, person: Option<&PersonInfo>) -> Self {
+        let (id, name, address, city, state, zipcode, email) = match person {
+            Some(person) => (
+                person.id().to_string(),
+                person.name(),
+                person.location().address(),
+                person.location().city(),
+                person.location().state(),
+                person.location().zipcode().to_string(),
+                person.email(),
+            ),
+            None => (String::new(), "", "", "", "", String::new(), ""),
+        };
+        let mut fields = vec![
+            Field::new("ID", &id, validate_person_id),
+            Field::new("Name", name, validate_name),
+            Field::new("Address", address, validate_address),
+            Field::new("City", city, validate_city),
+            Field::new("State", state, validate_state),
+            Field::new("Zip", &zipcode, validate_zipcode),
+            Field::new("Email", email, validate_email),
+        ];
+        fields[0].editable = person.is_none();
+        Form {
+            tab,
+            editing: person.is_some(),
+            fields,
+            focus: usize::from(person.is_some()),
+        }
+    }
+
+    fn service(service: Option<&DirectoryService>) -> Self {
+        let (id, name, fee) = match service {
+            Some(service) => (
+                service.service_id.to_string(),
+                service.name.as_str(),
+                format!("{:.2}", service.fee),
+            ),
+            None => (String::new(), "", String::new()),
+        };
+        let mut fields = vec![
+            Field::new("Code", &id, validate_service_code),
+            Field::new("Name", name, validate_service_name),
+            Field::new("Fee", &fee, validate_fee),
+        ];
+        fields[0].editable = service.is_none();
+        Form {
+            tab: Tab::Services,
+            editing: service.is_some(),
+            fields,
+            focus: usize::from(service.is_some()),
+        }
+    }
+
+    fn value(&self, index: usize) -> &str {
+        self.fields[index].value.trim()
+    }
+
+    /// Moves to the next or previous editable field, checking the one left.
+    fn move_focus(&mut self, forward: bool) {
+        self.fields[self.focus].check();
+        let count = self.fields.len();
+        loop {
+            self.focus = if forward {
+                (self.focus + 1) % count
+            } else {
+                (self.focus + count - 1) % count
+            };
+            if self.fields[self.focus].editable {
+                break;
+            }
+        }
+    }
+
+    /// Checks every field and focuses the first with a problem.
+    fn check(&mut self) -> bool {
+        let mut valid = true;
+        for field in &mut self.fields {
+            valid &= field.check();
+        }
+        if let Some(index) =
+            self.fields.iter().position(|field| field.error.is_some())
+        {
+            self.focus = index;
+        }
+        valid
+    }
+}
+
+/// What the keys currently do.
+#[derive(Debug)]
+enum Mode {
+    Browse,
+    Form(Form),
+    ConfirmRemove(Tab, u32),
+}
+
+/// The state of the full-screen manager terminal.
+#[derive(Debug)]
+pub struct App {
+    actor: Actor,
+    tab: Tab,
+    members: Vec<(PersonInfo, bool)>,
+    providers: Vec<PersonInfo>,
+    services: Vec<DirectoryService>,
+    consultations: Vec<Consultation>,
+    selected: [usize; 4],
+    mode: Mode,
+    status: String,
+    quit: bool,
+}
+
+impl App {
+    /// Create the terminal with the tables loaded from a database.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to show and change.
+    /// * `actor` - The operator who changes are made as.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the tables could not be loaded.
+    pub fn new(db: &DB, actor: &Actor) -> Result<Self, Error> {
+        let mut app = App {
+            actor: actor.clone(),
+            tab: Tab::Members,
+            members: Vec::new(),
+            providers: Vec::new(),
+            services: Vec::new(),
+            consultations: Vec::new(),
+            selected: [0; 4],
+            mode: Mode::Browse,
+            status: "Press a to add, e to edit or q to quit.".to_string(),
+            quit: false,
+        };
+        app.reload(db)?;
+        Ok(app)
+    }
+
+    /// The table being shown.
+    pub fn tab(&self) -> Tab {
+        self.tab
+    }
+
+    /// The message in the status bar.
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Checks if the user asked to quit.
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Loads every table from the database again.
+    ///
+    /// # Failure
+    ///
+    /// Will return `Err` if the tables could not be loaded.
+    pub fn reload(&mut self, db: &DB) -> Result<(), Error> {
+        self.members = db
+            .get_members()?
+            .into_iter()
+            .map(|member| {
+                let valid = db.is_valid_member_id(member.id())?;
+                Ok((member, valid))
+            })
+            .collect::<Result<_, Error>>()?;
+        self.providers = db.get_providers()?;
+        self.services = match db.build_provider_directory() {
+            Ok(directory) => directory.services,
+            Err(Error::NoDataFound) => Vec::new(),
+            Err(err) => return Err(err),
+        };
+        self.consultations =
+            db.get_recent_consultations(RECENT_CONSULTATIONS)?;
+        for tab in Tab::ALL {
+            let len = self.len(tab);
+            let selected = &mut self.selected[tab.index()];
+            *selected = (*selected).min(len.saturating_sub(1));
+        }
+        Ok(())
+    }
+
+    fn len(&self, tab: Tab) -> usize {
+        match tab {
+            Tab::Members => self.members.len(),
+            Tab::Providers => self.providers.len(),
+            Tab::Services => self.services.len(),
+            Tab::Consultations => self.consultations.len(),
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        let index = self.selected[self.tab.index()];
+        (index < self.len(self.tab)).then_some(index)
+    }
+
+    /// The id of the selected row, if it has one.
+    fn selected_id(&self) -> Option<u32> {
+        let index = self.selected()?;
+        match self.tab {
+            Tab::Members => Some(self.members[index].0.id()),
+            Tab::Providers => Some(self.providers[index].id()),
+            Tab::Services => Some(self.services[index].service_id),
+            Tab::Consultations => None,
+        }
+    }
+
+    /// Handles a key press.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to change.
+    /// * `key` - The key that was pressed.
+    pub fn handle_key(&mut self, db: &DB, key: KeyEvent) {
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Browse => self.browse_key(db, key),
+            Mode::Form(form) => self.form_key(db, form, key),
+            Mode::ConfirmRemove(tab, id) => {
+                if key.code == KeyCode::Char('y') {
+                    self.remove(db, tab, id);
+                } else {
+                    self.status =
+                        format!("The {} was not removed.", tab.noun());
+                }
+            }
+        }
+    }
+
+    fn browse_key(&mut self, db: &DB, key: KeyEvent) {
+        let len = self.len(self.tab);
+        let selected = &mut self.selected[self.tab.index()];
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Right | KeyCode::Tab => {
+                self.tab = Tab::ALL[(self.tab.index() + 1) % Tab::ALL.len()];
+            }
+            KeyCode::Left | KeyCode::BackTab => {
+                self.tab = Tab::ALL
+                    [(self.tab.index() + Tab::ALL.len() - 1) % Tab::ALL.len()];
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                *selected = (*selected + 1).min(len.saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                *selected = selected.saturating_sub(1);
+            }
+            KeyCode::Char('a') => self.open_form(false),
+            KeyCode::Char('e') | KeyCode::Enter => self.open_form(true),
+            KeyCode::Char('d') | KeyCode::Delete => match self.selected_id() {
+                Some(id) => {
+                    self.status = format!(
+                        "Remove {} {}? Press y to confirm.",
+                        self.tab.noun(),
+                        id
+                    );
+                    self.mode = Mode::ConfirmRemove(self.tab, id);
+                }
+                None => self.status = "Nothing to remove.".to_string(),
+            },
+            KeyCode::Char('s') => self.toggle_suspended(db),
+            KeyCode::Char('r') => match self.reload(db) {
+                Ok(()) => self.status = "Reloaded.".to_string(),
+                Err(err) => self.status = format!("Error reloading: {}", err),
+            },
+            _ => (),
+        }
+    }
+
+    fn open_form(&mut self, edit: bool) {
+        let index = self.selected();
+        if edit && index.is_none() {
+            self.status = "Nothing to edit.".to_string();
+            return;
+        }
+        let index = if edit { index } else { None };
+        let form = match self.tab {
+            Tab::Members => Form::person(
+                Tab::Members,
+                index.map(|index| &self.members[index].0),
+            ),
+            Tab::Providers => Form::person(
+                Tab::Providers,
+                index.map(|index| &self.providers[index]),
+            ),
+            Tab::Services => {
+                Form::service(index.map(|index| &self.services[index]))
+            }
+            Tab::Consultations => {
+                self.status =
+                    "Consultations are recorded at the provider terminal."
+                        .to_string();
+                return;
+            }
+        };
+        self.status = "Enter saves, Esc cancels.".to_string();
+        self.mode = Mode::Form(form);
+    }
+
+    fn form_key(&mut self, db: &DB, mut form: Form, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.status = "Cancelled.".to_string();
+                return;
+            }
+            KeyCode::Enter => {
+                if !form.check() {
+                    self.status = "Fix the highlighted fields.".to_string();
+                } else if self.save(db, &form) {
+                    return;
+                }
+            }
+            KeyCode::Tab | KeyCode::Down => form.move_focus(true),
+            KeyCode::BackTab | KeyCode::Up => form.move_focus(false),
+            KeyCode::Backspace => {
+                form.fields[form.focus].value.pop();
+            }
+            KeyCode::Char(c) => form.fields[form.focus].value.push(c),
+            _ => (),
+        }
+        self.mode = Mode::Form(form);
+    }
+
+    /// Saves a checked form, returning `true` if it was saved.
+    fn save(&mut self, db: &DB, form: &Form) -> bool {
+        let result = match form.tab {
+            Tab::Members | Tab::Providers => save_person(db, &self.actor, form)
+                .map_err(|err| err.to_string()),
+            _ => save_service(db, &self.actor, form)
+                .map_err(|err| err.to_string()),
+        };
+        let id = form.value(0);
+        let noun = form.tab.noun();
+        match result.and_then(|()| self.reload(db).map_err(|e| e.to_string())) {
+            Ok(()) => {
+                self.status = if form.editing {
+                    format!("Saved {} {}.", noun, id)
+                } else {
+                    format!("Added {} {}.", noun, id)
+                };
+                true
+            }
+            Err(err) => {
+                self.status = format!("Error saving {} {}: {}", noun, id, err);
+                false
+            }
+        }
+    }
+
+    fn remove(&mut self, db: &DB, tab: Tab, id: u32) {
+        let result = match tab {
+            Tab::Members => db.remove_member(&self.actor, id),
+            Tab::Providers => db.remove_provider(&self.actor, id),
+            Tab::Services => db.remove_service(&self.actor, id),
+            Tab::Consultations => Ok(()),
+        };
+        self.status = match result.and_then(|()| self.reload(db)) {
+            Ok(()) => format!("Removed {} {}.", tab.noun(), id),
+            Err(err) => {
+                format!("Error removing {} {}: {}", tab.noun(), id, err)
+            }
+        };
+    }
+
+    fn toggle_suspended(&mut self, db: &DB) {
+        if self.tab != Tab::Members {
+            self.status = "Only members can be suspended.".to_string();
+            return;
+        }
+        let Some(index) = self.selected() else {
+            self.status = "Nothing to suspend.".to_string();
+            return;
+        };
+        let (member, valid) = &self.members[index];
+        let id = member.id();
+        let (result, done) = if *valid {
+            (db.suspend_member(&self.actor, id), "Suspended")
+        } else {
+            (db.reinstate_member(&self.actor, id), "Reinstated")
+        };
+        self.status = match result.and_then(|()| self.reload(db)) {
+            Ok(()) => format!("{} member {}.", done, id),
+            Err(err) => format!("Error changing member {}: {}", id, err),
+        };
+    }
+
+    /// Draws the terminal.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to draw in.
+    pub fn draw(&self, frame: &mut Frame) {
+        let [tabs_area, table_area, status_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let tabs = Tabs::new(Tab::ALL.iter().map(|tab| tab.title()))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("ChocAn Manager Terminal"),
+            )
+            .select(self.tab.index())
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_widget(tabs, tabs_area);
+        self.draw_table(frame, table_area);
+
+        let hints = match self.mode {
+            Mode::Browse => {
+                "a add  e edit  d remove  s suspend  r reload  q quit"
+            }
+            Mode::Form(_) => "Tab next field  Enter save  Esc cancel",
+            Mode::ConfirmRemove(..) => "y remove  any other key cancels",
+        };
+        let status = Line::from(vec![
+            Span::styled(
+                format!(" {} ", self.status),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(hints, Style::default().fg(Color::DarkGray)),
+        ]);
+        frame.render_widget(Paragraph::new(status), status_area);
+
+        if let Mode::Form(form) = &self.mode {
+            draw_form(frame, form);
+        }
+    }
+
+    fn draw_table(&self, frame: &mut Frame, area: Rect) {
+        let (header, widths, rows): (Vec<&str>, Vec<Constraint>, Vec<Row>) =
+            match self.tab {
+                Tab::Members => (
+                    vec!["ID", "Name", "City", "State", "Email", "Status"],
+                    vec![
+                        Constraint::Length(10),
+                        Constraint::Min(12),
+                        Constraint::Length(15),
+                        Constraint::Length(6),
+                        Constraint::Min(12),
+                        Constraint::Length(10),
+                    ],
+                    self.members
+                        .iter()
+                        .map(|(member, valid)| {
+                            let mut row = person_row(member);
+                            row.push(
+                                if *valid { "active" } else { "suspended" }
+                                    .to_string(),
+                            );
+                            Row::new(row)
+                        })
+                        .collect(),
+                ),
+                Tab::Providers => (
+                    vec!["ID", "Name", "City", "State", "Email"],
+                    vec![
+                        Constraint::Length(10),
+                        Constraint::Min(12),
+                        Constraint::Length(15),
+                        Constraint::Length(6),
+                        Constraint::Min(12),
+                    ],
+                    self.providers
+                        .iter()
+                        .map(|provider| Row::new(person_row(provider)))
+                        .collect(),
+                ),
+                Tab::Services => (
+                    vec!["Code", "Name", "Fee"],
+                    vec![
+                        Constraint::Length(8),
+                        Constraint::Min(20),
+                        Constraint::Length(10),
+                    ],
+                    self.services
+                        .iter()
+                        .map(|service| {
+                            Row::new(vec![
+                                format!("{:06}", service.service_id),
+                                service.name.clone(),
+                                format!("${:.2}", service.fee),
+                            ])
+                        })
+                        .collect(),
+                ),
+                Tab::Consultations => (
+                    vec![
+                        "Recorded", "Date", "Member", "Provider", "Service",
+                        "Comments",
+                    ],
+                    vec![
+                        Constraint::Length(20),
+                        Constraint::Length(11),
+                        Constraint::Length(10),
+                        Constraint::Length(10),
+                        Constraint::Length(8),
+                        Constraint::Min(10),
+                    ],
+                    self.consultations
+                        .iter()
+                        .map(|consul| {
+                            Row::new(vec![
+                                consul.curr_date().to_string(),
+                                consul.service_date().to_string(),
+                                consul.member_id().to_string(),
+                                consul.provider_id().to_string(),
+                                format!("{:06}", consul.service_code()),
+                                consul.comments().to_string(),
+                            ])
+                        })
+                        .collect(),
+                ),
+            };
+        let title = format!("{} ({})", self.tab.title(), rows.len());
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(header)
+                    .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .row_highlight_style(
+                Style::default().add_modifier(Modifier::REVERSED),
+            )
+            .highlight_symbol("> ");
+        let mut state = TableState::default().with_selected(self.selected());
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+}
+
+fn person_row(person: &PersonInfo) -> Vec<String> {
+    vec![
+        person.id().to_string(),
+        person.name().to_string(),
+        person.location().city().to_string(),
+        person.location().state().to_string(),
+        person.email().to_string(),
+    ]
+}
+
+fn draw_form(frame: &mut Frame, form: &Form) {
+    let height = u16::try_from(form.fields.len() * 2 + 2).unwrap_or(u16::MAX);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::horizontal([Constraint::Length(60)])
+        .flex(Flex::Center)
+        .areas(area);
+    let mut lines = Vec::new();
+    for (index, field) in form.fields.iter().enumerate() {
+        let focused = index == form.focus;
+        let style = if !field.editable {
+            Style::default().fg(Color::DarkGray)
+        } else if focused {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        let cursor = if focused { "_" } else { "" };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:>8}: ", field.label)),
+            Span::styled(format!("{}{}", field.value, cursor), style),
+        ]));
+        lines.push(match &field.error {
+            Some(error) => Line::styled(
+                format!("{:>10}{}", "", error),
+                Style::default().fg(Color::Red),
+            ),
+            None => Line::default(),
+        });
+    }
+    let title = format!(
+        "{} {}",
+        if form.editing { "Edit" } else { "Add" },
+        form.tab.noun()
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(title)),
+        area,
+    );
+}
+
+fn save_person(db: &DB, actor: &Actor, form: &Form) -> Result<(), String> {
+    let id = parse_id(form.value(0))?;
+    let zipcode = parse_id(form.value(5))?;
+    let location = LocationInfo::new(
+        form.value(2),
+        form.value(3),
+        &form.value(4).to_uppercase(),
+        zipcode,
+    )?;
+    let person = PersonInfo::new(form.value(1), id, &location, form.value(6))?;
+    let result = match (form.tab, form.editing) {
+        (Tab::Members, false) => db.add_member(actor, &person),
+        (Tab::Members, true) => db.update_member(actor, &person),
+        (_, false) => db.add_provider(actor, &person),
+        (_, true) => db.update_provider(actor, &person),
+    };
+    result.map_err(|err| err.to_string())
+}
+
+fn save_service(db: &DB, actor: &Actor, form: &Form) -> Result<(), String> {
+    let id = parse_id(form.value(0))?;
+    let fee = parse_money(form.value(2))?;
+    let result = if form.editing {
+        db.update_service(actor, id, form.value(1), fee)
+    } else {
+        db.add_service(actor, id, form.value(1), fee)
+    };
+    result.map_err(|err| err.to_string())
+}
+
+fn validate_length(text: &str, max: u32) -> Result<(), String> {
+    let count = text.trim().chars().count();
+    if count == 0 {
+        return Err("Required".to_string());
+    }
+    if count > usize::try_from(max).unwrap_or(usize::MAX) {
+        return Err(format!("At most {} characters", max));
+    }
+    Ok(())
+}
+
+fn validate_number(text: &str, max: u32) -> Result<(), String> {
+    match parse_id(text.trim()) {
+        Ok(number) if number <= max => Ok(()),
+        Ok(_) => Err(format!("At most {}", max)),
+        Err(_) => Err("Enter digits only".to_string()),
+    }
+}
+
+fn validate_person_id(text: &str) -> Result<(), String> {
+    validate_number(text, MAX_MEMBER_ID)
+}
+
+fn validate_name(text: &str) -> Result<(), String> {
+    validate_length(text, MAX_NAME_SIZE)
+}
+
+fn validate_address(text: &str) -> Result<(), String> {
+    validate_length(text, MAX_ADDRESS_SIZE)
+}
+
+fn validate_city(text: &str) -> Result<(), String> {
+    validate_length(text, MAX_CITY_SIZE)
+}
+
+fn validate_state(text: &str) -> Result<(), String> {
+    let text = text.trim();
+    if text.chars().count() == STATE_SIZE
+        && text.chars().all(|c| c.is_ascii_alphabetic())
+    {
+        Ok(())
+    } else {
+        Err(format!("Enter {} letters", STATE_SIZE))
+    }
+}
+
+fn validate_zipcode(text: &str) -> Result<(), String> {
+    validate_number(text, MAX_ZIPCODE)
+}
+
+fn validate_email(text: &str) -> Result<(), String> {
+    let text = text.trim();
+    match text.split_once('@') {
+        Some((user, domain)) if !user.is_empty() && !domain.is_empty() => {
+            Ok(())
+        }
+        _ => Err("Enter an email address such as name@pdx.edu".to_string()),
+    }
+}
+
+fn validate_service_code(text: &str) -> Result<(), String> {
+    validate_number(text, MAX_SERVICE_CODE)
+}
+
+fn validate_service_name(text: &str) -> Result<(), String> {
+    if text.trim().is_empty() {
+        Err("Required".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_fee(text: &str) -> Result<(), String> {
+    parse_money(text.trim())
+        .map(|_| ())
+        .map_err(|_| "Enter dollars as 0.00".to_string())
+}
+
+/// Runs the full-screen manager terminal until the user quits.
+///
+/// # Arguments
+///
+/// * `db` - The database to show and change.
+/// * `actor` - The logged in operator who changes are made as.
+///
+/// # Failure
+///
+/// Will return `Err` if the terminal could not be used or the tables could
+/// not be loaded.
+pub fn run(db: &DB, actor: &Actor) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = run_app(&mut terminal, db, actor);
+    ratatui::try_restore()?;
+    result
+}
+
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    db: &DB,
+    actor: &Actor,
+) -> io::Result<()> {
+    let mut app =
+        App::new(db, actor).map_err(|err| io::Error::other(err.to_string()))?;
+    while !app.should_quit() {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(db, key);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::Role;
+    use ratatui::backend::TestBackend;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn create_a_db(path: &str) -> DB {
+        let _ = std::fs::remove_file(path);
+        DB::new(path).unwrap()
+    }
+
+    fn create_a_unique_person(name: &str, id: u32) -> PersonInfo {
+        let location =
+            LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+        let email = format!("{}@pdx.edu", name);
+        PersonInfo::new(name, id, &location, &email).unwrap()
+    }
+
+    fn press(app: &mut App, db: &DB, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                '\x1b' => KeyCode::Esc,
+                '\x08' => KeyCode::Backspace,
+                '↑' => KeyCode::Up,
+                c => KeyCode::Char(c),
+            };
+            app.handle_key(db, KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(90, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let mut text = String::new();
+        for y in 0..buffer.area.height {
+            for x in 0..buffer.area.width {
+                text.push_str(buffer[(x, y)].symbol());
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn test_tables_and_navigation() {
+        let path = "./test_tui_tables.db3";
+        let db = create_a_db(path);
+        let actor = Actor::system("test");
+        db.add_member(&actor, &create_a_unique_person("Amy", 1))
+            .unwrap();
+        db.add_member(&actor, &create_a_unique_person("Bob", 2))
+            .unwrap();
+        db.add_service(&actor, 123456, "ServiceName", 10.25)
+            .unwrap();
+        let mut app = App::new(&db, &actor).unwrap();
+
+        let text = screen(&app);
+        assert!(text.contains("Members (2)"));
+        assert!(text.contains("> 1"));
+        assert!(text.contains("active"));
+
+        press(&mut app, &db, "js");
+        assert_eq!(app.status(), "Suspended member 2.");
+        assert!(screen(&app).contains("suspended"));
+        assert!(!db.is_valid_member_id(2).unwrap());
+
+        press(&mut app, &db, "\t\t");
+        assert_eq!(app.tab(), Tab::Services);
+        assert!(screen(&app).contains("$10.25"));
+
+        press(&mut app, &db, "q");
+        assert!(app.should_quit());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_add_form_checks_fields() {
+        let path = "./test_tui_form.db3";
+        let db = create_a_db(path);
+        let mut app = App::new(&db, &Actor::system("test")).unwrap();
+
+        press(&mut app, &db, "a12x\t");
+        assert!(screen(&app).contains("Enter digits only"));
+        press(&mut app, &db, "↑\x08\x08\x083\tAmy\t1 Main st\tPortland\t");
+        press(&mut app, &db, "oregon\t97201\tamy@pdx.edu\n");
+        assert_eq!(app.status(), "Fix the highlighted fields.");
+        assert!(screen(&app).contains("Enter 2 letters"));
+        press(&mut app, &db, "\x08\x08\x08\x08\n");
+        assert_eq!(app.status(), "Added member 3.");
+        assert_eq!(db.get_member_info(3).unwrap().location().state(), "OR");
+
+        press(&mut app, &db, "e\x08\x08\x08Ann\n");
+        assert_eq!(app.status(), "Saved member 3.");
+        assert_eq!(db.get_member_info(3).unwrap().name(), "Ann");
+
+        press(&mut app, &db, "dn");
+        assert_eq!(app.status(), "The member was not removed.");
+        press(&mut app, &db, "dy");
+        assert_eq!(app.status(), "Removed member 3.");
+        assert!(db.get_members().unwrap().is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_service_form() {
+        let path = "./test_tui_service.db3";
+        let db = create_a_db(path);
+        let mut app = App::new(&db, &Actor::system("test")).unwrap();
+
+        press(&mut app, &db, "\t\ta123456\tServiceName\tten\n");
+        assert!(screen(&app).contains("Enter dollars as 0.00"));
+        press(&mut app, &db, "\x08\x08\x0810.25\n");
+        assert_eq!(app.status(), "Added service 123456.");
+        assert_eq!(db.get_service_fee(123456).unwrap(), 10.25);
+
+        press(&mut app, &db, "a123456\tOther\t1\n");
+        assert!(app.status().starts_with("Error saving service 123456"));
+        press(&mut app, &db, "\x1b");
+        assert_eq!(app.status(), "Cancelled.");
+
+        let clerk = Actor::new("ann", Role::Clerk);
+        let mut app = App::new(&db, &clerk).unwrap();
+        press(&mut app, &db, "\t\tdy");
+        assert!(app.status().contains("clerk ann may not edit services"));
+        assert_eq!(db.get_service_fee(123456).unwrap(), 10.25);
+        let _ = std::fs::remove_file(path);
+    }
+}
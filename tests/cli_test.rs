@@ -0,0 +1,330 @@
+/* File: cli_test.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+use chocanon::db::{DB, LocationInfo, PersonInfo};
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const TEST_DB_PATH: &str = "./test_cli.db3";
+const TEST_OUTPUT_PATH: &str = "./test_cli_output";
+const TEST_BACKUP_PATH: &str = "./test_cli_backup.db3";
+const TEST_CREDENTIALS_DB_PATH: &str = "./test_cli_credentials.db3";
+const USERNAME: &str = "manager";
+const PASSWORD: &str = "password1";
+
+/// Runs the binary with a command against the test database, logging in
+/// with a password given in the environment.
+fn chocanon_as(username: &str, password: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_chocanon"))
+        .args(["--config", "./tests/fixtures/empty.toml"])
+        .args(["--db", TEST_DB_PATH, "--output", TEST_OUTPUT_PATH])
+        .args(["--user", username])
+        .args(args)
+        .env("CHOCANON_PASSWORD", password)
+        .output()
+        .unwrap()
+}
+
+/// Runs the binary with a command against the test database as the manager.
+fn chocanon(args: &[&str]) -> Output {
+    chocanon_as(USERNAME, PASSWORD, args)
+}
+
+/// Runs the binary with a command against the credentials test database,
+/// writing the password and any other lines of input to it.
+fn chocanon_with_input(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chocanon"))
+        .args(["--config", "./tests/fixtures/empty.toml"])
+        .args(["--db", TEST_CREDENTIALS_DB_PATH])
+        .args(args)
+        .env_remove("CHOCANON_PASSWORD")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    // Commands that fail before reading exit without reading their input.
+    let _ = stdin.write_all(input.as_bytes());
+    drop(stdin);
+    child.wait_with_output().unwrap()
+}
+
+fn stdout_json(output: &Output) -> Value {
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn stderr_json(output: &Output) -> Value {
+    let text = String::from_utf8_lossy(&output.stderr);
+    serde_json::from_str(text.lines().next().unwrap()).unwrap()
+}
+
+fn add_member(id: &str) -> Output {
+    chocanon(&[
+        "member",
+        "add",
+        "--id",
+        id,
+        "--name",
+        "Ann Smith",
+        "--address",
+        "1234 Main st",
+        "--city",
+        "Portland",
+        "--state",
+        "OR",
+        "--zip",
+        "56789",
+        "--email",
+        "ann@pdx.edu",
+    ])
+}
+
+/// Adds the provider and service that the commands cannot add.
+fn add_provider_and_service() {
+    let db = DB::new(TEST_DB_PATH).unwrap();
+    let location =
+        LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+    let provider =
+        PersonInfo::new("Bob Jones", 1, &location, "provider@pdx.edu").unwrap();
+    let manager = db.login_operator(USERNAME, PASSWORD).unwrap();
+    db.add_provider(&manager, &provider).unwrap();
+    db.add_service(&manager, 123456, "ServiceName", 10.25)
+        .unwrap();
+}
+
+#[test]
+fn test_commands() {
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    let _ = std::fs::remove_file(TEST_BACKUP_PATH);
+    let _ = std::fs::remove_dir_all(TEST_OUTPUT_PATH);
+    let db = DB::new(TEST_DB_PATH).unwrap();
+    db.add_first_operator(USERNAME, PASSWORD).unwrap();
+    drop(db);
+
+    let output = chocanon_as(USERNAME, "password2", &["service", "list"]);
+    assert_eq!(output.status.code(), Some(1));
+    let output = Command::new(env!("CARGO_BIN_EXE_chocanon"))
+        .args(["--config", "./tests/fixtures/empty.toml"])
+        .args(["--db", TEST_DB_PATH, "member", "suspend", "123456789"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        stderr_json(&output)["error"],
+        "Usage error: --user is required"
+    );
+
+    let output = add_member("123456789");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout_json(&output)["member"]["name"], "Ann Smith");
+
+    let output = add_member("123456789");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr_json(&output)["error"].is_string());
+
+    let output = chocanon(&["member", "suspend", "123456789"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout_json(&output)["suspended"], true);
+
+    let output = chocanon(&["member", "suspend", "987654321"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = chocanon(&["service", "list"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout_json(&output)["services"], serde_json::json!([]));
+
+    let output = chocanon(&[
+        "consultation",
+        "add",
+        "--provider",
+        "1",
+        "--member",
+        "123456789",
+        "--service",
+        "123456",
+        "--date",
+        "10-16-2026",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+
+    add_provider_and_service();
+    let output = chocanon(&["member", "reinstate", "123456789"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = chocanon(&[
+        "consultation",
+        "add",
+        "--provider",
+        "1",
+        "--member",
+        "123456789",
+        "--service",
+        "123456",
+        "--date",
+        "10-16-2026",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout_json(&output)["consultation"]["fee"], 10.25);
+
+    let output = chocanon(&[
+        "report",
+        "send",
+        "--type",
+        "provider",
+        "--week",
+        "2026-10-16",
+    ]);
+    assert_eq!(output.status.code(), Some(0));
+    let summary = stdout_json(&output);
+    assert_eq!(summary["period_start"], "2026-10-10");
+    assert_eq!(summary["recipients"][0], "provider@pdx.edu");
+
+    let output = chocanon(&["report", "send", "--type", "provider"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        stderr_json(&output)["error"],
+        "Usage error: --week is required"
+    );
+
+    let output = chocanon(&[
+        "report",
+        "send",
+        "--type",
+        "provider",
+        "--week",
+        "2026-10-15",
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = chocanon(&["db", "backup", TEST_BACKUP_PATH]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(std::path::Path::new(TEST_BACKUP_PATH).exists());
+
+    let output = chocanon(&["member", "unknown"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    let _ = std::fs::remove_file(TEST_BACKUP_PATH);
+    let _ = std::fs::remove_dir_all(TEST_OUTPUT_PATH);
+}
+
+#[test]
+fn test_credential_commands() {
+    let _ = std::fs::remove_file(TEST_CREDENTIALS_DB_PATH);
+    let output =
+        chocanon_with_input(&["operator", "add", "ann"], "password1\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout_json(&output)["added"], true);
+    assert_eq!(stdout_json(&output)["role"], "manager");
+    let output =
+        chocanon_with_input(&["operator", "add", "bob"], "password1\n");
+    assert_eq!(output.status.code(), Some(1));
+    let output = chocanon_with_input(
+        &[
+            "--user", "ann", "operator", "add", "cal", "--role", "auditor",
+        ],
+        "password1\npassword1\n",
+    );
+    assert_eq!(stdout_json(&output)["role"], "auditor");
+    let output = chocanon_with_input(
+        &["--user", "cal", "operator", "add", "bob"],
+        "password1\npassword1\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "add", "bob"],
+        "password1\nshort\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "add", "bob"],
+        "password1\n",
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "add", "bob", "--role", "boss"],
+        "password1\npassword1\n",
+    );
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "password", "ann"],
+        "password1\npassword2\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let output = chocanon_with_input(
+        &["--user", "cal", "operator", "list"],
+        "password1\n",
+    );
+    let operators = &stdout_json(&output)["operators"];
+    assert_eq!(operators[0]["username"], "ann");
+    assert_eq!(operators[0]["role"], "manager");
+    assert_eq!(operators[0]["locked_until"], Value::Null);
+    assert_eq!(operators[1]["role"], "auditor");
+
+    let output = chocanon_with_input(
+        &["--user", "ann", "provider", "pin", "1"],
+        "password2\n1234\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let db = DB::new(TEST_CREDENTIALS_DB_PATH).unwrap();
+    let location =
+        LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+    let provider =
+        PersonInfo::new("Bob Jones", 1, &location, "provider@pdx.edu").unwrap();
+    let ann = db.login_operator("ann", "password2").unwrap();
+    db.add_provider(&ann, &provider).unwrap();
+    let output = chocanon_with_input(
+        &["--user", "ann", "provider", "pin", "1"],
+        "password2\n1234\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    db.login_provider(1, "1234").unwrap();
+    db.login_operator("ann", "password2").unwrap();
+    let output = chocanon_with_input(
+        &["--user", "ann", "provider", "unlock", "1"],
+        "password2\n",
+    );
+    assert_eq!(stdout_json(&output)["locked"], false);
+
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "role", "ann", "clerk"],
+        "password2\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "role", "cal", "clerk"],
+        "password2\n",
+    );
+    assert_eq!(stdout_json(&output)["role"], "clerk");
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "remove", "ann"],
+        "password2\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "remove", "cal"],
+        "password2\n",
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let output = chocanon_with_input(
+        &["--user", "ann", "operator", "unlock", "cal"],
+        "password2\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    let _ = std::fs::remove_file(TEST_CREDENTIALS_DB_PATH);
+}
@@ -0,0 +1,188 @@
+/* File: http_test.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+#![cfg(feature = "http")]
+
+use chocanon::access::Role;
+use chocanon::config::Config;
+use chocanon::db::DB;
+use chocanon::esend::FileTransport;
+use chocanon::http::HttpServer;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+const TEST_DB_PATH: &str = "./test_http_api.db3";
+const TEST_OUTPUT_PATH: &str = "./test_http_api_output";
+/// `manager:password1` in base64.
+const MANAGER: &str = "bWFuYWdlcjpwYXNzd29yZDE=";
+/// `cal:password1` in base64, an auditor.
+const AUDITOR: &str = "Y2FsOnBhc3N3b3JkMQ==";
+
+/// Starts the API on a free port of localhost, returning its address.
+fn start_server() -> String {
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    let _ = std::fs::remove_dir_all(TEST_OUTPUT_PATH);
+    let config = Config {
+        output_path: TEST_OUTPUT_PATH.into(),
+        ..Config::default()
+    };
+    let transport = Box::new(FileTransport::new(TEST_OUTPUT_PATH));
+    let db = DB::with_config(TEST_DB_PATH, transport, config.clone()).unwrap();
+    let actor = db.add_first_operator("manager", "password1").unwrap();
+    db.add_operator(&actor, "cal", "password1", Role::Auditor)
+        .unwrap();
+    let server = HttpServer::bind("127.0.0.1:0", db, config).unwrap();
+    let address = server.local_addr().unwrap().to_string();
+    std::thread::spawn(move || server.run());
+    address
+}
+
+/// Sends a request as the manager and returns the status code and JSON body.
+fn request(
+    address: &str,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> (u16, Value) {
+    request_as(address, MANAGER, method, path, body)
+}
+
+/// Sends a request with Basic credentials and returns the status code and
+/// JSON body.
+fn request_as(
+    address: &str,
+    credentials: &str,
+    method: &str,
+    path: &str,
+    body: &str,
+) -> (u16, Value) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Authorization: Basic {}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        method,
+        path,
+        address,
+        credentials,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    let status = response[9..12].parse().unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.contains("application/json"));
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_http_api() {
+    let address = start_server();
+
+    let (status, body) = request(
+        &address,
+        "POST",
+        "/providers",
+        r#"{"id": 1, "name": "ProviderName", "address": "1234 Main st",
+            "city": "Portland", "state": "OR", "zipcode": 56789,
+            "email": "provider@pdx.edu"}"#,
+    );
+    assert_eq!(status, 201, "{}", body);
+    let (status, _) = request(
+        &address,
+        "POST",
+        "/members",
+        r#"{"id": 2, "name": "MemberName", "address": "1234 Main st",
+            "city": "Portland", "state": "OR", "zipcode": 56789,
+            "email": "member@pdx.edu"}"#,
+    );
+    assert_eq!(status, 201);
+    let (status, _) = request(
+        &address,
+        "POST",
+        "/services",
+        r#"{"code": 123456, "name": "ServiceName", "fee": 10.25}"#,
+    );
+    assert_eq!(status, 201);
+    let (_, body) = request(&address, "GET", "/services", "");
+    assert_eq!(body["services"][0]["fee"], 10.25);
+    assert_eq!(body["services"][0]["code"], 123456);
+    let (_, body) = request(&address, "GET", "/services/123456", "");
+    assert_eq!(body["service"]["code"], 123456);
+    let (status, body) = request(
+        &address,
+        "PUT",
+        "/services/123456",
+        r#"{"code": 654321, "name": "ServiceName", "fee": 12.5}"#,
+    );
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "code does not match the path: 654321");
+    let (_, body) = request(&address, "GET", "/services/123456", "");
+    assert_eq!(body["service"]["fee"], 10.25);
+
+    let (status, body) = request(
+        &address,
+        "POST",
+        "/consultations",
+        r#"{"provider": 1, "member": 2, "service": 123456,
+            "date": "10-16-2026", "comments": "Went well"}"#,
+    );
+    assert_eq!(status, 201);
+    assert_eq!(body["consultation"]["fee"], 10.25);
+    let (status, body) = request(
+        &address,
+        "POST",
+        "/consultations",
+        r#"{"provider": 1, "member": 3, "service": 123456,
+            "date": "10-16-2026"}"#,
+    );
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "invalid or suspended member id: 3");
+    let (_, body) = request(&address, "GET", "/consultations?limit=5", "");
+    assert_eq!(body["consultations"][0]["comments"], "Went well");
+
+    let report = r#"{"type": "provider", "week": "2026-10-16"}"#;
+    let (status, body) = request(&address, "POST", "/report-runs", report);
+    assert_eq!(status, 201);
+    assert_eq!(body["recipients"][0], "provider@pdx.edu");
+    let (status, body) = request(&address, "POST", "/report-runs", report);
+    assert_eq!(status, 409);
+    assert_eq!(body["kind"], "conflict");
+    let (_, body) = request(&address, "GET", "/report-runs", "");
+    assert_eq!(body["report_runs"][0]["report_type"], "provider");
+    assert_eq!(body["report_runs"][0]["success"], true);
+
+    let (status, body) =
+        request_as(&address, AUDITOR, "GET", "/report-runs", "");
+    assert_eq!(status, 200, "{}", body);
+    let (status, body) =
+        request_as(&address, AUDITOR, "POST", "/report-runs", report);
+    assert_eq!(status, 403);
+    assert_eq!(body["kind"], "forbidden");
+    let (status, body) =
+        request_as(&address, "bm9ib2R5OnBhc3N3b3JkMQ==", "GET", "/", "");
+    assert_eq!(status, 401);
+    assert_eq!(body["kind"], "unauthorized");
+
+    let (status, body) = request(&address, "GET", "/nowhere", "");
+    assert_eq!(status, 404);
+    assert_eq!(body["error"], "no such endpoint: GET /nowhere");
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    let _ = std::fs::remove_dir_all(TEST_OUTPUT_PATH);
+}
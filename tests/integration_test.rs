@@ -13,6 +13,8 @@
  * Instructor: Christopher Gilmore
  */
 
+#![allow(clippy::needless_return)]
+
 use chocanon::esend::{
     send_manager_report, send_member_report, send_provider_report,
 };
@@ -0,0 +1,169 @@
+/* File: nacha_test.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+use chocanon::db::{AccountType, BankInfo};
+use chocanon::nacha::{NachaEntry, Originator, create_nacha_file};
+use chrono::NaiveDate;
+
+fn create_a_file(entries: &[NachaEntry]) -> String {
+    create_nacha_file(
+        &Originator::chocan(),
+        entries,
+        NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+        NaiveDate::from_ymd_opt(2026, 10, 17)
+            .unwrap()
+            .and_hms_opt(0, 5, 0)
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+/// Joins the fields of a record, checking it is 94 characters long.
+fn record(fields: &[&str]) -> String {
+    let record = fields.concat();
+    assert_eq!(record.len(), 94, "{:?}", record);
+    record + "\n"
+}
+
+fn create_an_entry(
+    name: &str,
+    id: u32,
+    bank: BankInfo,
+    amount_cents: u64,
+) -> NachaEntry {
+    NachaEntry {
+        provider_name: name.to_string(),
+        provider_id: id,
+        bank,
+        amount_cents,
+    }
+}
+
+#[test]
+fn test_nacha_single_entry_matches_fixture() {
+    let bank =
+        BankInfo::new("091000019", "12345678", AccountType::Checking).unwrap();
+    let file =
+        create_a_file(&[create_an_entry("ProviderName1", 1, bank, 1099)]);
+    let expected = include_str!("fixtures/nacha_single_entry.ach");
+    assert_eq!(file, expected);
+}
+
+#[test]
+fn test_nacha_two_entries_matches_fixture() {
+    let checking =
+        BankInfo::new("091000019", "12345678", AccountType::Checking).unwrap();
+    let savings =
+        BankInfo::new("021000021", "987654321", AccountType::Savings).unwrap();
+    let file = create_a_file(&[
+        create_an_entry("ProviderName1", 1, checking, 3297),
+        create_an_entry("Provider With A Long Name", 123456789, savings, 50000),
+    ]);
+    let expected = include_str!("fixtures/nacha_two_entries.ach");
+    assert_eq!(file, expected);
+}
+
+#[test]
+fn test_nacha_savings_entry_matches_spec() {
+    let savings =
+        BankInfo::new("021000021", "987654321", AccountType::Savings).unwrap();
+    let file = create_a_file(&[create_an_entry(
+        "Provider With A Long Name",
+        123456789,
+        savings,
+        123456,
+    )]);
+
+    // Each record is written out field by field, by position in the NACHA
+    // rules, rather than taken from a file the code wrote.
+    let mut expected = String::new();
+    // File header.
+    expected += &record(&[
+        "1",                       // 1      Record type
+        "01",                      // 2-3    Priority code
+        " 123000220",              // 4-13   Immediate destination
+        "1234567890",              // 14-23  Immediate origin
+        "261017",                  // 24-29  File creation date
+        "0005",                    // 30-33  File creation time
+        "A",                       // 34     File id modifier
+        "094",                     // 35-37  Record size
+        "10",                      // 38-39  Blocking factor
+        "1",                       // 40     Format code
+        "CHOCAN BANK            ", // 41-63  Destination name
+        "CHOCOHOLICS ANONYMOUS  ", // 64-86  Origin name
+        "        ",                // 87-94  Reference code
+    ]);
+    // Batch header.
+    expected += &record(&[
+        "5",                    // 1      Record type
+        "220",                  // 2-4    Service class, credits only
+        "CHOCAN          ",     // 5-20   Company name
+        "                    ", // 21-40  Discretionary data
+        "1234567890",           // 41-50  Company id
+        "CCD",                  // 51-53  Standard entry class
+        "CHOCANPAY ",           // 54-63  Entry description
+        "      ",               // 64-69  Descriptive date
+        "261019",               // 70-75  Effective entry date
+        "   ",                  // 76-78  Settlement date
+        "1",                    // 79     Originator status
+        "12300022",             // 80-87  Originating DFI
+        "0000001",              // 88-94  Batch number
+    ]);
+    // Entry detail.
+    expected += &record(&[
+        "6",                      // 1      Record type
+        "32",                     // 2-3    Savings credit
+        "02100002",               // 4-11   Receiving DFI
+        "1",                      // 12     Check digit
+        "987654321        ",      // 13-29  Account number
+        "0000123456",             // 30-39  Amount, $1,234.56
+        "123456789      ",        // 40-54  Individual id
+        "PROVIDER WITH A LONG N", // 55-76  Individual name
+        "  ",                     // 77-78  Discretionary data
+        "0",                      // 79     Addenda indicator
+        "12300022",               // 80-87  Trace number, ODFI
+        "0000001",                // 88-94  Trace number, sequence
+    ]);
+    // Batch control.
+    expected += &record(&[
+        "8",                   // 1      Record type
+        "220",                 // 2-4    Service class, credits only
+        "000001",              // 5-10   Entry and addenda count
+        "0002100002",          // 11-20  Entry hash
+        "000000000000",        // 21-32  Total debits
+        "000000123456",        // 33-44  Total credits
+        "1234567890",          // 45-54  Company id
+        "                   ", // 55-73  Authentication code
+        "      ",              // 74-79  Reserved
+        "12300022",            // 80-87  Originating DFI
+        "0000001",             // 88-94  Batch number
+    ]);
+    // File control.
+    expected += &record(&[
+        "9",             // 1      Record type
+        "000001",        // 2-7    Batch count
+        "000001",        // 8-13   Block count
+        "00000001",      // 14-21  Entry and addenda count
+        "0002100002",    // 22-31  Entry hash
+        "000000000000",  // 32-43  Total debits
+        "000000123456",  // 44-55  Total credits
+        &" ".repeat(39), // 56-94  Reserved
+    ]);
+    // Padding to a block of 10 records.
+    for _ in 0..5 {
+        expected += &record(&[&"9".repeat(94)]);
+    }
+    assert_eq!(file, expected);
+}
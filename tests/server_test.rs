@@ -0,0 +1,146 @@
+/* File: server_test.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+use chocanon::client::Client;
+use chocanon::config::Config;
+use chocanon::db::{DB, LocationInfo, PersonInfo};
+use chocanon::esend::FileTransport;
+use chocanon::server::{Request, Server};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::process::{Command, Stdio};
+
+const TEST_DB_PATH: &str = "./test_server_session.db3";
+const TEST_OUTPUT_PATH: &str = "./test_server_session_output";
+
+fn create_a_unique_person(name: &str, id: u32) -> PersonInfo {
+    let location =
+        LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+    let email = format!("{}@pdx.edu", name);
+    PersonInfo::new(name, id, &location, &email).unwrap()
+}
+
+/// Starts a server on a free port of localhost with a provider who has a
+/// PIN, a member and a service, returning its address.
+fn start_server() -> String {
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    let _ = std::fs::remove_dir_all(TEST_OUTPUT_PATH);
+    let config = Config {
+        output_path: TEST_OUTPUT_PATH.into(),
+        ..Config::default()
+    };
+    let transport = Box::new(FileTransport::new(TEST_OUTPUT_PATH));
+    let db = DB::with_config(TEST_DB_PATH, transport, config).unwrap();
+    let actor = db.add_first_operator("manager", "password1").unwrap();
+    db.add_provider(&actor, &create_a_unique_person("ProviderName", 111111111))
+        .unwrap();
+    db.set_provider_pin(&actor, 111111111, "1234").unwrap();
+    db.add_member(&actor, &create_a_unique_person("MemberName", 222222222))
+        .unwrap();
+    db.add_service(&actor, 123456, "ServiceName", 10.25)
+        .unwrap();
+    let server = Server::bind("127.0.0.1:0", db).unwrap();
+    let address = server.local_addr().unwrap().to_string();
+    std::thread::spawn(move || server.run());
+    address
+}
+
+#[test]
+fn test_server_session() {
+    let address = start_server();
+
+    let mut raw = TcpStream::connect(&address).unwrap();
+    let mut reader = BufReader::new(raw.try_clone().unwrap());
+    let mut line = String::new();
+    raw.write_all(b"{\"op\":\"directory\"}\n").unwrap();
+    reader.read_line(&mut line).unwrap();
+    assert_eq!(line, "{\"error\":\"not logged in\",\"ok\":false}\n");
+
+    let mut client = Client::connect(&address).unwrap();
+    let error = client
+        .send(&Request::Login {
+            provider: 1,
+            pin: "1234".to_string(),
+        })
+        .unwrap();
+    assert_eq!(error.unwrap_err(), "invalid provider id or PIN");
+    let error = client
+        .send(&Request::Login {
+            provider: 111111111,
+            pin: "4321".to_string(),
+        })
+        .unwrap();
+    assert_eq!(error.unwrap_err(), "invalid provider id or PIN");
+    let response = client
+        .send(&Request::Login {
+            provider: 111111111,
+            pin: "1234".to_string(),
+        })
+        .unwrap()
+        .unwrap();
+    assert_eq!(response["provider"]["name"], "ProviderName");
+    let response = client
+        .send(&Request::ValidateMember { member: 222222222 })
+        .unwrap()
+        .unwrap();
+    assert_eq!(response["valid"], true);
+
+    let script = [
+        "111111111",
+        "1234",
+        "1",
+        "222222222",
+        "10-16-2026",
+        "999999",
+        "123456",
+        "y",
+        "Went well",
+        "2",
+        "3",
+        "1",
+        "0",
+    ];
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chocanon-client"))
+        .arg(&address)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all((script.join("\n") + "\n").as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let out = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(out.contains("Welcome, ProviderName."));
+    assert!(out.contains("invalid service code: 999999"));
+    assert!(out.contains("Service Name: ServiceName"));
+    assert!(out.contains("Consultation record added successfully."));
+    assert!(out.contains("123456 ServiceName $10.25"));
+    assert!(out.contains("Invalid Number"));
+    assert!(out.ends_with("Exiting provider terminal...\n"));
+
+    let response = client.send(&Request::Logout).unwrap().unwrap();
+    assert_eq!(response["ok"], true);
+    let db = DB::new(TEST_DB_PATH).unwrap();
+    let consultations = db.get_recent_consultations(10).unwrap();
+    assert_eq!(consultations.len(), 1);
+    assert_eq!(consultations[0].comments(), "Went well");
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    let _ = std::fs::remove_dir_all(TEST_OUTPUT_PATH);
+}
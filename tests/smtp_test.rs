@@ -0,0 +1,142 @@
+/* File: smtp_test.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+use chocanon::db::{Consultation, DB, LocationInfo, PersonInfo, ReportPeriod};
+use chocanon::esend::SmtpTransport;
+use chrono::{Duration, Local};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+const TEST_DB_PATH: &str = "./test_smtp_chocanon.db3";
+
+/// Starts a stand-in SMTP server that accepts emails until `count` have
+/// been received and returns the recipients and message bodies.
+fn start_smtp_stand_in(
+    count: usize,
+) -> (u16, thread::JoinHandle<Vec<(String, String)>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = thread::spawn(move || {
+        let mut emails = Vec::new();
+        while emails.len() < count {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            writer.write_all(b"220 stand-in ready\r\n").unwrap();
+            let mut to = String::new();
+            let mut data: Option<String> = None;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end_matches("\r\n");
+                if let Some(ref mut body) = data {
+                    if line == "." {
+                        emails.push((to.clone(), body.clone()));
+                        data = None;
+                        writer.write_all(b"250 queued\r\n").unwrap();
+                    } else {
+                        body.push_str(line);
+                        body.push('\n');
+                    }
+                    continue;
+                }
+                if let Some(rcpt) = line.strip_prefix("RCPT TO:") {
+                    to = rcpt.trim_matches(['<', '>']).to_string();
+                }
+                let reply: &[u8] = match line {
+                    "DATA" => {
+                        data = Some(String::new());
+                        b"354 go ahead\r\n"
+                    }
+                    "QUIT" => b"221 bye\r\n",
+                    _ => b"250 ok\r\n",
+                };
+                writer.write_all(reply).unwrap();
+                if line == "QUIT" {
+                    break;
+                }
+            }
+        }
+        emails
+    });
+    (port, handle)
+}
+
+fn create_a_person(name: &str, id: u32) -> PersonInfo {
+    let location =
+        LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+    PersonInfo::new(name, id, &location, &format!("{}@pdx.edu", name)).unwrap()
+}
+
+fn create_a_consultation(member_id: u32, provider_id: u32) -> Consultation {
+    let yesterday = Local::now() - Duration::days(1);
+    Consultation::new(
+        &yesterday.format("%m-%d-%Y %H:%M:%S").to_string(),
+        &yesterday.format("%m-%d-%Y").to_string(),
+        provider_id,
+        member_id,
+        123456,
+        "A consultation reported over SMTP",
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_member_reports_sent_over_smtp() {
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    let (port, handle) = start_smtp_stand_in(2);
+    let db = DB::with_transport(
+        TEST_DB_PATH,
+        Box::new(SmtpTransport::new("127.0.0.1", port)),
+    )
+    .unwrap();
+    let actor = db.add_first_operator("manager", "password1").unwrap();
+    db.add_service(&actor, 123456, "ServiceName123456", 99.99)
+        .unwrap();
+    db.add_member(&actor, &create_a_person("MemberName1", 1))
+        .unwrap();
+    db.add_member(&actor, &create_a_person("MemberName2", 2))
+        .unwrap();
+    db.add_provider(&actor, &create_a_person("ProviderName1", 61))
+        .unwrap();
+    db.add_consultation_record(&actor, &create_a_consultation(1, 61))
+        .unwrap();
+    db.add_consultation_record(&actor, &create_a_consultation(2, 61))
+        .unwrap();
+
+    let today = Local::now().date_naive();
+    let period = ReportPeriod::new(today - Duration::days(7), today).unwrap();
+    db.send_member_reports(&actor, &period, false).unwrap();
+
+    let emails = handle.join().unwrap();
+    let _ = std::fs::remove_file(TEST_DB_PATH);
+    assert_eq!(emails[0].0, "MemberName1@pdx.edu");
+    assert!(
+        emails[0]
+            .1
+            .contains("Subject: Member Report for MemberName1\n")
+    );
+    assert!(emails[0].1.contains("Provider name: ProviderName1\n"));
+    assert_eq!(emails[1].0, "MemberName2@pdx.edu");
+
+    let run = db.get_report_runs().unwrap().pop().unwrap();
+    assert_eq!(
+        run.files[0],
+        format!("smtp://127.0.0.1:{}/MemberName1@pdx.edu", port)
+    );
+}
@@ -0,0 +1,451 @@
+/* File: terminal_test.rs
+ *
+ * Authors:
+ * - Tanner Weber, tannerw@pdx.edu
+ * - Cristian Hernandez, cristhe@pdx.edu
+ * - Jethro Fernandez, jethrof@pdx.edu
+ * - Torin Costales, tcostal2@pdx.edu
+ * - Miles Turoczy, turoczy@pdx.edu
+ *
+ * Portland State University
+ * Dates: October 29 to December 5
+ * Course: CS 314, Fall 2025
+ * Instructor: Christopher Gilmore
+ */
+
+use chocanon::access::{Actor, Role};
+use chocanon::config::Config;
+use chocanon::db::{Consultation, DB, LocationInfo, PersonInfo};
+use chocanon::esend::FileTransport;
+use chocanon::{mterm, pterm};
+use std::io::{Cursor, Write};
+use std::process::{Command, Stdio};
+
+/// The operator account, a manager, the manager terminal is logged in with.
+const USERNAME: &str = "manager";
+const PASSWORD: &str = "password1";
+
+/// Opens a new database for a test, with emails written to its own
+/// directory and a manager's operator account, which the test makes its
+/// changes as.
+fn create_a_db(name: &str) -> (DB, Actor) {
+    let path = format!("./test_{}.db3", name);
+    let output = format!("./test_{}_output", name);
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_dir_all(&output);
+    let config = Config {
+        output_path: output.clone().into(),
+        ..Config::default()
+    };
+    let transport = Box::new(FileTransport::new(&output));
+    let db = DB::with_config(&path, transport, config).unwrap();
+    let actor = db.add_first_operator(USERNAME, PASSWORD).unwrap();
+    (db, actor)
+}
+
+fn remove_a_db(name: &str) {
+    let _ = std::fs::remove_file(format!("./test_{}.db3", name));
+    let _ = std::fs::remove_dir_all(format!("./test_{}_output", name));
+}
+
+fn create_a_unique_person(name: &str, id: u32) -> PersonInfo {
+    let location =
+        LocationInfo::new("1234 Main st", "Portland", "OR", 56789).unwrap();
+    let email = format!("{}@pdx.edu", name);
+    PersonInfo::new(name, id, &location, &email).unwrap()
+}
+
+/// Runs the manager terminal with a script of input lines, after logging
+/// in.
+fn run_mterm(db: &DB, script: &[&str]) -> String {
+    let script = [&[USERNAME, PASSWORD], script].concat();
+    run_mterm_logged_out(db, &script)
+}
+
+/// Runs the manager terminal with a script of input lines, starting at the
+/// login.
+fn run_mterm_logged_out(db: &DB, script: &[&str]) -> String {
+    let mut input = Cursor::new(script.join("\n") + "\n");
+    let mut out = Vec::new();
+    mterm::run_man_term(db, &Config::default(), &mut input, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// Runs the provider terminal with a script of input lines.
+fn run_pterm(db: &DB, script: &[&str]) -> String {
+    let mut input = Cursor::new(script.join("\n") + "\n");
+    let mut out = Vec::new();
+    pterm::run(db, &mut input, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_manager_session() {
+    let (db, _) = create_a_db("mterm_session");
+    let out = run_mterm(
+        &db,
+        &[
+            "6",
+            "123456",
+            "ServiceName",
+            "10.25",
+            "1",
+            "ProviderName",
+            "111111111",
+            "1234 Main st",
+            "Portland",
+            "or",
+            "56789",
+            "provider@pdx.edu",
+            "y",
+            "1",
+            "MemberName",
+            "222222222",
+            "1234 Main st",
+            "Portland",
+            "OR",
+            "56789",
+            "member@pdx.edu",
+            "n",
+            "8",
+            "111111111",
+            "123000220",
+            "12345678",
+            "n",
+            "10",
+            "42",
+            "2",
+            "n",
+            "222222222",
+            "0",
+        ],
+    );
+    assert!(out.starts_with("----MANAGER LOGIN----"));
+    assert!(out.contains("Logged in as manager (manager)."));
+    assert!(out.contains("Provider was successfully added."));
+    assert!(out.contains("Member was successfully added."));
+    assert!(out.contains("Bank account was successfully set."));
+    assert!(out.contains("No reports have been sent."));
+    assert!(out.contains("Invalid input."));
+    assert!(out.contains("Member removed successfully."));
+    assert!(db.is_valid_provider_id(111111111).unwrap());
+    assert!(!db.is_valid_member_id(222222222).unwrap());
+    assert_eq!(db.get_service_name(123456).unwrap(), "ServiceName");
+    remove_a_db("mterm_session");
+}
+
+#[test]
+fn test_manager_provider_reports_write_eft() {
+    let (db, actor) = create_a_db("mterm_eft");
+    db.add_service(&actor, 123456, "ServiceName", 10.25)
+        .unwrap();
+    db.add_provider(&actor, &create_a_unique_person("ProviderName", 111111111))
+        .unwrap();
+    db.add_member(&actor, &create_a_unique_person("MemberName", 222222222))
+        .unwrap();
+    let consultation = Consultation::new(
+        "01-14-2025 10:00:00",
+        "01-13-2025",
+        111111111,
+        222222222,
+        123456,
+        "Went well",
+    )
+    .unwrap();
+    db.add_consultation_record(&actor, &consultation).unwrap();
+    let out = run_mterm(&db, &["4", "01-17-2025", "0"]);
+    assert!(out.contains("Provider reports sent: 1 recipient(s)"));
+    assert!(out.contains("EFT records sent: 1 recipient(s), 1 file(s)"));
+    let eft = std::fs::read_dir("./test_mterm_eft_output/eft").unwrap();
+    assert_eq!(eft.count(), 1);
+    remove_a_db("mterm_eft");
+}
+
+#[test]
+fn test_provider_session() {
+    let (db, actor) = create_a_db("pterm_session");
+    db.add_service(&actor, 123456, "ServiceName", 10.25)
+        .unwrap();
+    db.add_provider(&actor, &create_a_unique_person("ProviderName", 111111111))
+        .unwrap();
+    db.set_provider_pin(&actor, 111111111, "1234").unwrap();
+    db.add_member(&actor, &create_a_unique_person("MemberName", 222222222))
+        .unwrap();
+    let out = run_pterm(
+        &db,
+        &[
+            "111111111",
+            "1234",
+            "3",
+            "333333333",
+            "3",
+            "222222222",
+            "1",
+            "222222222",
+            "10-16-2026",
+            "123456",
+            "y",
+            "Went well",
+            "2",
+            "111111111",
+            "9",
+            "0",
+        ],
+    );
+    assert!(out.contains("Invalid Number"));
+    assert!(out.contains("Validated"));
+    assert!(out.contains("Service Name: ServiceName"));
+    assert!(out.contains("Consultation record added successfully."));
+    assert!(out.contains("Retrieving Provider Directory."));
+    assert!(out.contains("Invalid option. Please enter 0, 1, 2, or 3."));
+    assert!(out.ends_with("Exiting provider terminal...\n\n"));
+    remove_a_db("pterm_session");
+}
+
+#[test]
+fn test_provider_session_invalid_provider() {
+    let (db, _) = create_a_db("pterm_invalid");
+    let out = run_pterm(&db, &["111111111", "1234"]);
+    assert!(out.contains("Invalid provider id or PIN."));
+    remove_a_db("pterm_invalid");
+}
+
+#[test]
+fn test_provider_session_pin() {
+    let (db, actor) = create_a_db("pterm_pin");
+    db.add_provider(&actor, &create_a_unique_person("ProviderName", 111111111))
+        .unwrap();
+    let out = run_pterm(&db, &["111111111", "1234"]);
+    assert!(out.contains("Invalid provider id or PIN."));
+    assert!(out.contains("Contact a manager to set or reset your PIN."));
+
+    db.set_provider_pin(&actor, 111111111, "1234").unwrap();
+    let out = run_pterm(&db, &["111111111", "4321"]);
+    assert!(out.contains("Invalid provider id or PIN."));
+    assert!(!out.contains("---Provider Terminal---"));
+    for _ in 0..4 {
+        run_pterm(&db, &["111111111", "4321"]);
+    }
+    let out = run_pterm(&db, &["111111111", "1234"]);
+    assert!(out.contains("Too many invalid PINs, locked out until"));
+    db.unlock_provider(&actor, 111111111).unwrap();
+    let out = run_pterm(&db, &["111111111", "1234", "0"]);
+    assert!(out.ends_with("Exiting provider terminal...\n\n"));
+    remove_a_db("pterm_pin");
+}
+
+#[test]
+fn test_manager_login_and_credentials() {
+    let (db, actor) = create_a_db("mterm_login");
+    db.add_provider(&actor, &create_a_unique_person("ProviderName", 111111111))
+        .unwrap();
+    let out = run_mterm_logged_out(&db, &["manager", "wrong", "back"]);
+    assert!(out.contains("Invalid username or password."));
+    assert!(!out.contains("----MANAGER TERMINAL----"));
+
+    let out = run_mterm(
+        &db,
+        &[
+            "16",
+            "pin",
+            "111111111",
+            "12",
+            "5678",
+            "5678",
+            "16",
+            "add",
+            "ann smith",
+            "ann",
+            "password2",
+            "password3",
+            "password2",
+            "password2",
+            "boss",
+            "clerk",
+            "16",
+            "list",
+            "0",
+        ],
+    );
+    assert!(out.contains("Invalid PIN, enter 4 to 8 digits."));
+    assert!(out.contains("Provider PIN was successfully set."));
+    assert!(out.contains("Invalid username, enter up to 25 letters"));
+    assert!(out.contains("They do not match."));
+    assert!(out.contains("Invalid role, enter auditor, clerk, manager: boss"));
+    assert!(out.contains("Operator account was successfully added."));
+    assert!(out.contains("ann (clerk) added "));
+    assert!(out.contains("manager (manager) added "));
+    db.login_provider(111111111, "5678").unwrap();
+    db.login_operator("ann", "password2").unwrap();
+
+    let out = run_mterm_logged_out(
+        &db,
+        &[
+            "ann",
+            "password2",
+            "16",
+            "remove",
+            "manager",
+            "6",
+            "123456",
+            "ServiceName",
+            "10.25",
+            "16",
+            "password",
+            "ann",
+            "password4",
+            "password4",
+            "0",
+        ],
+    );
+    assert!(out.contains("Logged in as ann (clerk)."));
+    assert!(out.contains(
+        "Error managing credentials: Unauthorized error: clerk ann may not \
+         edit operator accounts"
+    ));
+    assert!(out.contains("Error adding service: Unauthorized error"));
+    assert!(out.contains("Password was successfully changed."));
+    assert!(!db.is_valid_service_id(123456).unwrap());
+    db.login_operator("ann", "password4").unwrap();
+
+    let out = run_mterm(
+        &db,
+        &[
+            "16", "role", "manager", "clerk", "16", "role", "ann", "auditor",
+            "16", "remove", "manager", "16", "remove", "ann", "0",
+        ],
+    );
+    assert!(out.contains(
+        "Error managing credentials: Unauthorized error: manager is the last \
+         manager and must stay one"
+    ));
+    assert!(out.contains("Operator role was successfully changed."));
+    assert!(out.contains("Operator account was successfully removed."));
+    assert_eq!(
+        db.login_operator("manager", PASSWORD).unwrap().name(),
+        USERNAME
+    );
+    remove_a_db("mterm_login");
+
+    remove_a_db("mterm_no_accounts");
+    let db = DB::new("./test_mterm_no_accounts.db3").unwrap();
+    let out = run_mterm_logged_out(&db, &[]);
+    assert!(out.contains("Add one with: chocanon operator add USERNAME"));
+    remove_a_db("mterm_no_accounts");
+}
+
+#[test]
+fn test_session_ends_with_input() {
+    let (db, _) = create_a_db("mterm_eof");
+    let out = run_mterm(&db, &["1", "MemberName"]);
+    assert!(out.ends_with("9 digit ID: \n"));
+    let out = run_pterm(&db, &["abc"]);
+    assert!(out.ends_with("enter back to cancel.\n\n"));
+    remove_a_db("mterm_eof");
+}
+
+#[test]
+fn test_bad_input_asks_again() {
+    let (db, actor) = create_a_db("mterm_bad_input");
+    let out = run_mterm(
+        &db,
+        &[
+            "6",
+            "abc",
+            "123456",
+            "ServiceName",
+            "ten",
+            "10.257",
+            "10.25",
+            "8",
+            "back",
+            "11",
+            "pdf",
+            "back",
+            "0",
+        ],
+    );
+    assert!(out.contains("Invalid id, enter digits only: abc."));
+    assert!(out.contains("Invalid amount, enter dollars as 0.00: ten."));
+    assert!(out.contains("Service was successfully added."));
+    assert!(out.contains("Invalid choice, enter member, provider, manager"));
+    assert_eq!(db.get_service_fee(123456).unwrap(), 10.25);
+
+    db.add_provider(&actor, &create_a_unique_person("ProviderName", 111111111))
+        .unwrap();
+    db.set_provider_pin(&actor, 111111111, "1234").unwrap();
+    let out = run_pterm(
+        &db,
+        &["111111111", "1234", "2", "abc", "back", "1", "back", "0"],
+    );
+    assert!(out.contains("Invalid id, enter digits only: abc."));
+    assert!(out.contains("Invalid Number"));
+    assert!(out.ends_with("Exiting provider terminal...\n\n"));
+    remove_a_db("mterm_bad_input");
+}
+
+#[test]
+fn test_start_menu_session() {
+    remove_a_db("menu_session");
+    DB::new("./test_menu_session.db3")
+        .unwrap()
+        .add_first_operator(USERNAME, PASSWORD)
+        .unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chocanon"))
+        .args(["--config", "./tests/fixtures/empty.toml"])
+        .args(["--db", "./test_menu_session.db3"])
+        .args(["--output", "./test_menu_session_output"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"x\n2\nmanager\npassword1\n10\n0\n0\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let out = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(out.contains("Invalid input. Try again"));
+    assert!(out.contains("Chose manager terminal"));
+    assert!(out.contains("No reports have been sent."));
+    assert!(out.ends_with("Quitting\n"));
+    remove_a_db("menu_session");
+}
+
+#[test]
+fn test_start_menu_populate_needs_a_manager() {
+    remove_a_db("menu_populate");
+    let db = DB::new("./test_menu_populate.db3").unwrap();
+    let actor = db.add_first_operator(USERNAME, PASSWORD).unwrap();
+    db.add_operator(&actor, "ann", "password2", Role::Clerk)
+        .unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_chocanon"))
+        .args(["--config", "./tests/fixtures/empty.toml"])
+        .args(["--db", "./test_menu_populate.db3"])
+        .args(["--output", "./test_menu_populate_output"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            b"3\nann\npassword2\n3\nmanager\npassword1\n3\nmanager\npassword1\n0\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let out = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(out.contains("Only a manager can populate the database."));
+    assert!(out.contains("The sample providers log in with the PIN 1234."));
+    assert!(
+        out.contains("The database already has data, so it was left as is.")
+    );
+    db.login_provider(1, "1234").unwrap();
+    remove_a_db("menu_populate");
+}
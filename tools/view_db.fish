@@ -16,3 +16,23 @@ echo "PROVIDER_DIRECTORY"
 echo "-------------------------------------------------------------------------"
 sqlite3 -line chocanon.db3 'SELECT * FROM provider_directory;'
 echo "-------------------------------------------------------------------------"
+echo "PROVIDER_BANK_ACCOUNTS"
+echo "-------------------------------------------------------------------------"
+sqlite3 -line chocanon.db3 'SELECT * FROM provider_bank_accounts;'
+echo "-------------------------------------------------------------------------"
+echo "REPORT_RUNS"
+echo "-------------------------------------------------------------------------"
+sqlite3 -line chocanon.db3 'SELECT * FROM report_runs;'
+echo "-------------------------------------------------------------------------"
+echo "REPORT_FORMATS"
+echo "-------------------------------------------------------------------------"
+sqlite3 -line chocanon.db3 'SELECT * FROM report_formats;'
+echo "-------------------------------------------------------------------------"
+echo "REPORT_CSV_ATTACHMENTS"
+echo "-------------------------------------------------------------------------"
+sqlite3 -line chocanon.db3 'SELECT * FROM report_csv_attachments;'
+echo "-------------------------------------------------------------------------"
+echo "OUTBOX"
+echo "-------------------------------------------------------------------------"
+sqlite3 -line chocanon.db3 'SELECT id, kind, recipient, subject, status, attempts, last_error, next_attempt_at, delivered_to, created_at, updated_at FROM outbox;'
+echo "-------------------------------------------------------------------------"